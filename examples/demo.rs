@@ -3,7 +3,8 @@
 //! This example demonstrates the usage of the Base122 encoding library,
 //! showing encoding/decoding operations and efficiency comparisons.
 
-use base122_rs::{decode, encode};
+use base122_rs::corpus::{generate, Profile as Corpus};
+use base122_rs::{decode, encode, Engine, Profile};
 use std::env;
 use std::io::{self, Read, Write};
 
@@ -205,27 +206,41 @@ fn run_benchmark() {
 
     let densities = vec![0.0, 0.1, 0.2, 0.5];
     for &density in &densities {
-        let mut test_data = Vec::new();
-        for i in 0..1000 {
-            if (i as f64 / 1000.0) < density {
-                // Insert dangerous characters
-                let dangerous_chars = [0u8, 10, 13, 34, 38, 92];
-                test_data.push(dangerous_chars[i % dangerous_chars.len()]);
-            } else {
-                test_data.push(((i * 7) % 256) as u8);
-            }
-        }
+        let test_data = generate(Corpus::DangerousDensity(density), 1000, 122);
 
         let encoded = encode(&test_data);
         let efficiency = (test_data.len() as f64 / encoded.len() as f64) * 100.0;
+        let chars = encoded.chars().count();
 
         println!(
-            "Dangerous char density {:.0}%: efficiency {:.1}%",
+            "Dangerous chunk density {:.0}%: efficiency {:.1}%, {} chars",
             density * 100.0,
-            efficiency
+            efficiency,
+            chars
         );
     }
 
+    println!();
+    println!("=== Decode Throughput by Character Class ===");
+
+    let filename = Engine::new().with_profile(&Profile::FILENAME);
+    let classes = [
+        ("ASCII runs", Engine::STANDARD, vec![0x55; 1 << 16]),
+        ("2-byte escapes", Engine::STANDARD, vec![0; 1 << 16]),
+        ("3-byte escapes", filename, vec![0; 1 << 16]),
+    ];
+    for (class, engine, data) in classes {
+        let encoded = engine.encode(&data);
+        let start = std::time::Instant::now();
+        let rounds = 100;
+        for _ in 0..rounds {
+            std::hint::black_box(engine.decode(&encoded).unwrap());
+        }
+        let seconds = start.elapsed().as_secs_f64();
+        let throughput = (data.len() * rounds) as f64 / seconds / 1e6;
+        println!("{class:>16}: {throughput:.0} MB/s decoded");
+    }
+
     println!();
     println!("📈 Benchmark complete!");
 }
@@ -0,0 +1,41 @@
+//! Writes seed corpora for fuzz targets.
+//!
+//! ```text
+//! cargo run --example fuzz_seeds -- fuzz/corpus
+//! ```
+//!
+//! Encoder seeds go to `<DIR>/encode` and decoder seeds to `<DIR>/decode`,
+//! one file per seed, named after it.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::ExitCode;
+
+use base122_rs::corpus::{decode_seeds, encode_seeds, Seed};
+
+fn main() -> ExitCode {
+    let Some(dir) = env::args().nth(1) else {
+        eprintln!("Usage: fuzz_seeds <DIR>");
+        return ExitCode::FAILURE;
+    };
+
+    let dir = Path::new(&dir);
+    for (target, seeds) in [("encode", encode_seeds()), ("decode", decode_seeds())] {
+        if let Err(e) = write_seeds(&dir.join(target), &seeds) {
+            eprintln!("error: {}: {e}", dir.join(target).display());
+            return ExitCode::FAILURE;
+        }
+        println!("{target}: {} seeds", seeds.len());
+    }
+    ExitCode::SUCCESS
+}
+
+fn write_seeds(dir: &Path, seeds: &[Seed]) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    for seed in seeds {
+        fs::write(dir.join(&seed.name), &seed.input)?;
+    }
+    Ok(())
+}
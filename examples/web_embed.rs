@@ -0,0 +1,108 @@
+//! Embeds an image in a standalone HTML page and reports the savings.
+//!
+//! ```text
+//! cargo run --example web_embed -- logo.png page.html
+//! ```
+//!
+//! The image becomes a [`DataUri`] in a `data-src` attribute, and the
+//! decoder from [`js_decoder_snippet`] turns it back into an `<img>` source
+//! when the page loads. The sizes printed compare the Base122 URI with the
+//! Base64 URI a page would embed otherwise.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+use base122_rs::{decode_auto, js_decoder_snippet, DataUri, SnippetOptions};
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let [image, rest @ ..] = &args[..] else {
+        eprintln!("Usage: web_embed <IMAGE> [OUTPUT.html]");
+        return ExitCode::FAILURE;
+    };
+    let output = rest.first().map_or("embedded.html", String::as_str);
+
+    let data = match fs::read(image) {
+        Ok(data) => data,
+        Err(e) => {
+            eprintln!("error: {image}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let size = data.len();
+    let media_type = media_type(Path::new(image));
+    let uri = DataUri::new(media_type, data).to_string();
+    // What the page's decoder will do
+    assert_eq!(decode_auto(&uri).map(|data| data.len()), Ok(size));
+
+    let name = Path::new(image)
+        .file_name()
+        .map_or(image.clone(), |name| name.to_string_lossy().into_owned());
+    let html = page(&uri, &name);
+    if let Err(e) = fs::write(output, &html) {
+        eprintln!("error: {output}: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    let base122 = uri.len();
+    let base64 = "data:;base64,".len() + media_type.len() + size.div_ceil(3) * 4;
+    println!("image:        {size:>10} bytes");
+    println!("base64 URI:   {base64:>10} bytes");
+    println!("base122 URI:  {base122:>10} bytes");
+    println!(
+        "saved:        {:>10} bytes ({:.1}%)",
+        base64 as i64 - base122 as i64,
+        100.0 * (base64 as f64 - base122 as f64) / base64 as f64
+    );
+    println!("wrote {output}: {} bytes", html.len());
+    ExitCode::SUCCESS
+}
+
+/// Media type of an image, from its file extension.
+fn media_type(path: &Path) -> &'static str {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    match extension.to_ascii_lowercase().as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        _ => "application/octet-stream",
+    }
+}
+
+/// An HTML page showing the image in `uri`.
+///
+/// Standard Base122 output never contains `"` or `&`, so the URI can go in
+/// a double-quoted attribute as it is.
+fn page(uri: &str, name: &str) -> String {
+    let decoder = js_decoder_snippet(&SnippetOptions::new());
+    let name: String = name.chars().filter(|c| !"<>&\"".contains(*c)).collect();
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{name}</title>
+</head>
+<body>
+<img alt="{name}" data-src="{uri}">
+<script>
+{decoder}
+var images = document.querySelectorAll("img[data-src]");
+for (var i = 0; i < images.length; i++) {{
+  var uri = images[i].getAttribute("data-src");
+  var comma = uri.indexOf(",");
+  var type = uri.slice(5, uri.lastIndexOf(";", comma));
+  var blob = new Blob([base122Decode(uri.slice(comma + 1))], {{ type: type }});
+  images[i].src = URL.createObjectURL(blob);
+}}
+</script>
+</body>
+</html>
+"#
+    )
+}
@@ -0,0 +1,57 @@
+/*
+ * C API for the base122-rs streaming encoder and decoder.
+ *
+ * Build the library with the `ffi` feature, e.g.
+ *
+ *     cargo rustc --release --features ffi --lib --crate-type cdylib
+ *
+ * See the `ffi` module documentation for details on each function.
+ */
+
+#ifndef BASE122_H
+#define BASE122_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Status codes */
+#define BASE122_OK 0
+#define BASE122_NULL_POINTER 1
+#define BASE122_OUTPUT_TOO_SMALL 2
+#define BASE122_INVALID_INPUT 3
+
+typedef struct Base122Encoder Base122Encoder;
+typedef struct Base122Decoder Base122Decoder;
+
+/* Output buffer size enough for encoding `len` bytes in one feed call,
+ * and for base122_encoder_finish. Saturates at SIZE_MAX if the size does
+ * not fit in a size_t. */
+size_t base122_max_encoded_len(size_t len);
+
+Base122Encoder *base122_encoder_new(void);
+int base122_encoder_feed(Base122Encoder *encoder, const uint8_t *data,
+                         size_t len, uint8_t *out, size_t out_cap,
+                         size_t *out_len);
+/* Writes at most 2 bytes and resets the encoder. */
+int base122_encoder_finish(Base122Encoder *encoder, uint8_t *out,
+                           size_t out_cap, size_t *out_len);
+void base122_encoder_free(Base122Encoder *encoder);
+
+Base122Decoder *base122_decoder_new(void);
+/* `out_cap` must be at least `len + 1`. */
+int base122_decoder_feed(Base122Decoder *decoder, const uint8_t *data,
+                         size_t len, uint8_t *out, size_t out_cap,
+                         size_t *out_len);
+/* Checks for a truncated character and resets the decoder. */
+int base122_decoder_finish(Base122Decoder *decoder);
+void base122_decoder_free(Base122Decoder *decoder);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* BASE122_H */
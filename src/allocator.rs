@@ -0,0 +1,127 @@
+//! Encoding and decoding into vectors from a caller-provided allocator.
+//!
+//! The allocator API is unstable, so this goes through [`allocator_api2`],
+//! which mirrors it on stable Rust. Arena allocators such as `bumpalo`
+//! implement its trait directly.
+
+use allocator_api2::alloc::Allocator;
+use allocator_api2::vec::Vec;
+
+use crate::stream::EncodeState;
+use crate::{decoded_len, split_char, Accumulator, DecodeError, Engine, Profile};
+
+/// Encodes `data` into a vector allocated from `alloc`, holding the UTF-8
+/// bytes of the encoded text.
+///
+/// The exact encoded length is computed first, so the vector is allocated
+/// once and never grows: a bump arena wastes nothing on abandoned buffers.
+/// The bytes are always valid UTF-8 and identical to
+/// [`encode`](crate::encode) of `data`.
+///
+/// # Examples
+///
+/// ```rust
+/// use allocator_api2::alloc::Global;
+/// use base122_rs::{encode, encode_in};
+///
+/// let encoded = encode_in(b"\0frame data\n", Global);
+/// assert_eq!(&encoded[..], encode(b"\0frame data\n").as_bytes());
+/// ```
+pub fn encode_in<A: Allocator>(data: &[u8], alloc: A) -> Vec<u8, A> {
+    let mut encoded = Vec::with_capacity_in(Engine::STANDARD.encoded_len(data), alloc);
+    let mut state = EncodeState::default();
+    for &byte in data {
+        state.push(byte, &mut |byte| encoded.push(byte));
+    }
+    state.finish(&mut |byte| encoded.push(byte));
+    encoded
+}
+
+/// Decodes `encoded` into a vector allocated from `alloc`.
+///
+/// Like [`encode_in`], the vector is allocated once, at the exact decoded
+/// length of valid input.
+///
+/// # Errors
+///
+/// Fails like [`decode`](crate::decode) on invalid input. The vector is
+/// then dropped, returning its memory to `alloc`.
+///
+/// # Examples
+///
+/// ```rust
+/// use allocator_api2::alloc::Global;
+/// use base122_rs::{decode_in, encode};
+///
+/// let decoded = decode_in(&encode(b"\0frame data\n"), Global)?;
+/// assert_eq!(&decoded[..], b"\0frame data\n");
+/// # Ok::<(), base122_rs::DecodeError>(())
+/// ```
+pub fn decode_in<A: Allocator>(encoded: &str, alloc: A) -> Result<Vec<u8, A>, DecodeError> {
+    let mut decoded = Vec::with_capacity_in(decoded_len(&Profile::STANDARD, encoded), alloc);
+    let mut accumulator = Accumulator::default();
+    for (position, character) in encoded.char_indices() {
+        let (illegal, payload) = split_char(&Profile::STANDARD, position, character)?;
+        if let Some(illegal) = illegal {
+            decoded.extend(accumulator.push7(illegal));
+        }
+        decoded.extend(accumulator.push7(payload));
+    }
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::alloc::Layout;
+    use core::cell::Cell;
+    use core::ptr::NonNull;
+
+    use allocator_api2::alloc::{AllocError, Global};
+
+    use super::*;
+    use crate::corpus::{generate, Profile};
+    use crate::{decode, encode};
+
+    /// Counts the allocations made through it.
+    struct Counting<'a>(&'a Cell<usize>);
+
+    #[allow(unsafe_code)]
+    unsafe impl Allocator for Counting<'_> {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            self.0.set(self.0.get() + 1);
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            // SAFETY: every block was allocated by `Global` above
+            unsafe { Global.deallocate(ptr, layout) }
+        }
+    }
+
+    #[test]
+    fn test_allocates_once() {
+        for len in [1, 7, 100, 1000] {
+            let data = generate(Profile::DangerousDensity(0.5), len, len as u64);
+            let count = Cell::new(0);
+
+            let encoded = encode_in(&data, Counting(&count));
+            assert_eq!(&encoded[..], encode(&data).as_bytes());
+            assert_eq!(count.get(), 1, "len {len}");
+
+            let text = core::str::from_utf8(&encoded).unwrap();
+            let decoded = decode_in(text, Counting(&count)).unwrap();
+            assert_eq!(&decoded[..], &data[..]);
+            assert_eq!(count.get(), 2, "len {len}");
+        }
+    }
+
+    #[test]
+    fn test_decode_in_errors() {
+        let text = "ab\u{0800}";
+        assert_eq!(
+            decode_in(text, Global).unwrap_err(),
+            decode(text).unwrap_err()
+        );
+        assert!(decode_in("", Global).unwrap().is_empty());
+    }
+}
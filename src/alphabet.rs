@@ -0,0 +1,100 @@
+//! The character sets engines emit.
+
+use crate::Profile;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// The characters an [`Engine`](crate::Engine) emits.
+///
+/// Implemented by [`Profile`] for 7-bit chunks and by [`Base64Alphabet`]
+/// for 6-bit chunks; [`Engine::alphabet`](crate::Engine::alphabet) returns
+/// the one in use. The trait is sealed so that methods can be added without
+/// breaking changes. Custom escaping is available through
+/// [`Profile::new`].
+pub trait Alphabet: sealed::Sealed + Sync {
+    /// Short lowercase name, e.g. `"standard"`.
+    fn name(&self) -> &'static str;
+
+    /// The number of input bits each chunk carries.
+    fn chunk_bits(&self) -> u32;
+
+    /// Whether `c` can appear in a message, not counting the terminator.
+    fn emits(&self, c: char) -> bool;
+
+    /// The character that ends a message when the engine's terminator
+    /// option is enabled.
+    fn terminator(&self) -> char;
+}
+
+/// The Base64 alphabet used for 6-bit chunks.
+///
+/// `A`–`Z`, `a`–`z`, `0`–`9`, `+`, and `/`, with `=` as the terminator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Base64Alphabet;
+
+impl sealed::Sealed for Base64Alphabet {}
+
+impl Alphabet for Base64Alphabet {
+    fn name(&self) -> &'static str {
+        "base64"
+    }
+
+    fn chunk_bits(&self) -> u32 {
+        6
+    }
+
+    fn emits(&self, c: char) -> bool {
+        c.is_ascii_alphanumeric() || c == '+' || c == '/'
+    }
+
+    fn terminator(&self) -> char {
+        '='
+    }
+}
+
+impl sealed::Sealed for Profile {}
+
+impl Alphabet for Profile {
+    fn name(&self) -> &'static str {
+        Profile::name(self)
+    }
+
+    fn chunk_bits(&self) -> u32 {
+        7
+    }
+
+    fn emits(&self, c: char) -> bool {
+        if c.is_ascii() {
+            self.escape_index(c as u8).is_none()
+        } else {
+            !self.is_foreign(c) && self.split(c).is_some()
+        }
+    }
+
+    fn terminator(&self) -> char {
+        Profile::terminator(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChunkWidth, Engine};
+
+    #[test]
+    fn test_alphabet_covers_engine_output() {
+        let data: Vec<u8> = (0..=255).cycle().take(3000).collect();
+        for engine in [
+            Engine::new(),
+            Engine::new().with_profile(&Profile::FILENAME),
+            Engine::new().with_chunk_width(ChunkWidth::Six),
+        ] {
+            let alphabet = engine.alphabet();
+            assert_eq!(alphabet.chunk_bits(), engine.chunk_width().bits());
+            assert!(engine.encode(&data).chars().all(|c| alphabet.emits(c)));
+            assert!(!alphabet.emits(alphabet.terminator()));
+        }
+    }
+}
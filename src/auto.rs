@@ -0,0 +1,311 @@
+//! Decoding payloads from producers with unknown settings.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use crate::DecodeError;
+use crate::{Alphabet, Base64Alphabet, ChunkWidth, Engine, Profile};
+
+/// Profiles tried by [`detect_engine`], in order of preference.
+const PROFILES: [&Profile; 7] = [
+    &Profile::STANDARD,
+    &Profile::DISPLAY,
+    &Profile::C1_FREE,
+    &Profile::BIDI_SAFE,
+    &Profile::NORMALIZATION_STABLE,
+    &Profile::FILENAME,
+    &Profile::XML,
+];
+
+/// Guesses the engine that produced `encoded`.
+///
+/// Text made only of the Base64 alphabet, optionally followed by a single
+/// `=`, is taken to be [`ChunkWidth::Six`] output. Otherwise the result is
+/// the first profile, [`Profile::STANDARD`] first, whose encoder could
+/// have produced `encoded`: every character is one it emits, and the
+/// padding bits at the end are zero. The terminator option is set if
+/// `encoded` ends with that profile's terminator.
+/// If no profile fits, the default engine is returned and decoding with it
+/// reports the problem.
+///
+/// Short inputs can be ambiguous: a few 7-bit chunks may happen to look
+/// like Base64, and profiles share some escape characters. Callers that
+/// know the producer's settings should use its [`Engine`] directly.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{detect_engine, ChunkWidth, Engine, Profile};
+///
+/// let engine = Engine::new().with_profile(&Profile::DISPLAY).with_terminator(true);
+/// let encoded = engine.encode([0; 16]);
+/// assert_eq!(detect_engine(&encoded), engine);
+///
+/// assert_eq!(detect_engine("Zm9vYmFy").chunk_width(), ChunkWidth::Six);
+/// ```
+pub fn detect_engine(encoded: &str) -> Engine {
+    let (body, terminated) = match encoded.strip_suffix('=') {
+        Some(body) => (body, true),
+        None => (encoded, false),
+    };
+    if body.chars().all(|c| Base64Alphabet.emits(c)) {
+        return Engine::new()
+            .with_chunk_width(ChunkWidth::Six)
+            .with_terminator(terminated);
+    }
+
+    PROFILES
+        .iter()
+        .find_map(|&profile| {
+            let (body, terminated) = match encoded.strip_suffix(profile.terminator()) {
+                Some(body) => (body, true),
+                None => (encoded, false),
+            };
+            matches(profile, body).then(|| {
+                Engine::new()
+                    .with_profile(profile)
+                    .with_terminator(terminated)
+            })
+        })
+        .unwrap_or_default()
+}
+
+/// How likely some data is to be Base122 text already, as judged by
+/// [`looks_encoded`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Likelihood {
+    /// Not something an encoder produced, or indistinguishable from
+    /// ordinary text.
+    Unlikely,
+    /// Output some profile could have produced, with escapes but nothing
+    /// that rules out ordinary non-ASCII text.
+    Possible,
+    /// Output some profile could have produced, with control characters
+    /// that text almost never contains.
+    Likely,
+}
+
+/// Guesses whether `data` is already Base122 text, to catch a pipeline
+/// about to encode its input twice.
+///
+/// `data` can only be encoded if it is UTF-8 that some profile's encoder
+/// could have produced, ignoring a final line ending. Most plain text
+/// passes that test too, so [`Likely`](Likelihood::Likely) also takes a
+/// trait of encoded binary data that text almost never has: ASCII
+/// control characters other than tabs, which make up about a quarter of
+/// the ASCII in standard output, or escapes from three or more blocks of
+/// 128 code points, as profiles without control characters produce.
+/// Text with escapes from fewer blocks, such as a few accented letters,
+/// is [`Possible`](Likelihood::Possible), and printable ASCII
+/// [`Unlikely`](Likelihood::Unlikely). Payloads of a few bytes may not
+/// show either trait.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{encode, looks_encoded, Likelihood};
+///
+/// let encoded = encode(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR");
+/// assert_eq!(looks_encoded(&encoded), Likelihood::Likely);
+/// assert_eq!(looks_encoded("plain text\n"), Likelihood::Unlikely);
+/// assert_eq!(looks_encoded(b"\x89PNG\r\n"), Likelihood::Unlikely);
+/// ```
+pub fn looks_encoded(data: impl AsRef<[u8]>) -> Likelihood {
+    let Ok(text) = core::str::from_utf8(data.as_ref()) else {
+        return Likelihood::Unlikely;
+    };
+    let text = text
+        .strip_suffix('\n')
+        .map_or(text, |text| text.strip_suffix('\r').unwrap_or(text));
+    if text.is_empty() || !PROFILES.iter().any(|profile| matches(profile, text)) {
+        return Likelihood::Unlikely;
+    }
+
+    if text.chars().any(|c| c.is_ascii_control() && c != '\t') {
+        return Likelihood::Likely;
+    }
+    // Distinct blocks of 128 code points among escapes, until a third
+    let mut blocks = [0; 2];
+    let mut distinct = 0;
+    for block in text
+        .chars()
+        .filter(|c| !c.is_ascii())
+        .map(|c| c as u32 >> 7)
+    {
+        if !blocks[..distinct].contains(&block) {
+            if distinct == blocks.len() {
+                return Likelihood::Likely;
+            }
+            blocks[distinct] = block;
+            distinct += 1;
+        }
+    }
+    if distinct > 0 {
+        Likelihood::Possible
+    } else {
+        Likelihood::Unlikely
+    }
+}
+
+/// Decodes a payload whose encoding settings are not known in advance.
+///
+/// Accepts a bare encoded string or a `data:` URI. The payload of a
+/// `;base64` data URI is decoded as Base64, padding included; anything
+/// else is decoded with the engine chosen by [`detect_engine`].
+///
+/// # Errors
+///
+/// Returns the error reported by the chosen engine. Error positions are
+/// byte offsets into `input`, including any data URI header.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{decode_auto, encode, Engine, Profile};
+///
+/// let payload = b"\0binary\npayload";
+/// let filename = Engine::new().with_profile(&Profile::FILENAME);
+///
+/// assert_eq!(decode_auto(&encode(payload)).unwrap(), payload);
+/// assert_eq!(decode_auto(&filename.encode(payload)).unwrap(), payload);
+/// assert_eq!(decode_auto("data:text/plain;base64,aGk=").unwrap(), b"hi");
+/// ```
+#[cfg(feature = "alloc")]
+pub fn decode_auto(input: &str) -> Result<Vec<u8>, DecodeError> {
+    let (offset, engine, payload) = match split_data_uri(input) {
+        Some((header, payload)) if is_base64_uri(header) => (
+            input.len() - payload.len(),
+            Engine::new().with_chunk_width(ChunkWidth::Six),
+            payload.trim_end_matches('='),
+        ),
+        Some((_, payload)) => (input.len() - payload.len(), detect_engine(payload), payload),
+        None => (0, detect_engine(input), input),
+    };
+    engine
+        .decode(payload)
+        .map_err(|error| error.offset_by(offset))
+}
+
+/// Splits a `data:` URI into its header (between `data:` and the comma)
+/// and its payload.
+#[cfg(feature = "alloc")]
+fn split_data_uri(input: &str) -> Option<(&str, &str)> {
+    let scheme = input.get(..5)?;
+    if !scheme.eq_ignore_ascii_case("data:") {
+        return None;
+    }
+    input[5..].split_once(',')
+}
+
+/// Whether a data URI header declares Base64 content.
+#[cfg(feature = "alloc")]
+fn is_base64_uri(header: &str) -> bool {
+    header
+        .rsplit(';')
+        .next()
+        .is_some_and(|parameter| parameter.eq_ignore_ascii_case("base64"))
+}
+
+/// Whether `profile`'s encoder can produce `body`.
+///
+/// Besides checking each character, this checks that the chunks end the way
+/// the encoder ends them: with fewer than 7 bits left over after the last
+/// whole byte, all zero. Profiles that share escape characters often
+/// disagree on how many chunks an escape carries, which shows up here.
+fn matches(profile: &Profile, body: &str) -> bool {
+    let mut chunks = 0usize;
+    let mut last = 0;
+    for c in body.chars() {
+        if !profile.emits(c) {
+            return false;
+        }
+        (chunks, last) = match profile.split(c) {
+            // Only safe ASCII chunks are emitted without an escape
+            None => (chunks + 1, c as u8),
+            Some((Some(_), payload)) => (chunks + 2, payload),
+            Some((None, payload)) => (chunks + 1, payload),
+        };
+    }
+
+    let padding = (chunks * 7 % 8) as u32;
+    padding < 7 && u32::from(last) & ((1 << padding) - 1) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::{generate, Profile as Corpus};
+
+    #[test]
+    fn test_detect_engine_finds_profile() {
+        let data = generate(Corpus::DangerousDensity(0.2), 2000, 9);
+        for profile in PROFILES {
+            for terminator in [false, true] {
+                let engine = Engine::new()
+                    .with_profile(profile)
+                    .with_terminator(terminator);
+                assert_eq!(detect_engine(&engine.encode(&data)), engine);
+                assert_eq!(decode_auto(&engine.encode(&data)).unwrap(), data);
+            }
+        }
+
+        let six = Engine::new().with_chunk_width(ChunkWidth::Six);
+        assert_eq!(decode_auto(&six.encode(&data)).unwrap(), data);
+        assert_eq!(
+            detect_engine(&six.with_terminator(true).encode(&data)),
+            six.with_terminator(true)
+        );
+    }
+
+    #[test]
+    fn test_decode_auto_data_uri() {
+        let data = generate(Corpus::Image, 500, 4);
+        let uri = format!("data:image/png;charset=utf-8,{}", crate::encode(&data));
+        assert_eq!(decode_auto(&uri).unwrap(), data);
+
+        assert_eq!(decode_auto("DATA:;BASE64,Zm9vYg==").unwrap(), b"foob");
+        assert_eq!(decode_auto("data:,").unwrap(), b"");
+    }
+
+    #[test]
+    fn test_decode_auto_error_positions() {
+        // Neither Base64 nor any profile's output: reported by the standard
+        // engine, relative to the whole input
+        assert_eq!(detect_engine("a\u{4E00}\0"), Engine::new());
+        assert_eq!(
+            decode_auto("data:,a\u{4E00}\0"),
+            Err(DecodeError::InvalidCharacter {
+                position: 7,
+                character: '\u{4E00}'
+            })
+        );
+    }
+
+    #[test]
+    fn test_looks_encoded() {
+        for len in [1, 3, 16, 100, 1000] {
+            for corpus in [Corpus::Image, Corpus::DangerousDensity(0.2)] {
+                let data = generate(corpus, len, len as u64);
+                for profile in PROFILES {
+                    let encoded = Engine::new().with_profile(profile).encode(&data);
+                    let likelihood = looks_encoded(&encoded);
+                    if len >= 100 {
+                        assert_eq!(likelihood, Likelihood::Likely, "{}", profile.name());
+                    }
+                    assert_eq!(looks_encoded(encoded + "\r\n"), likelihood);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_looks_encoded_text() {
+        assert_eq!(looks_encoded(""), Likelihood::Unlikely);
+        assert_eq!(looks_encoded("Hello, world!\n"), Likelihood::Unlikely);
+        assert_eq!(looks_encoded("line one\nline two"), Likelihood::Unlikely);
+        assert_eq!(looks_encoded("{\"key\": 1}"), Likelihood::Unlikely);
+        assert_eq!(looks_encoded([0xFF, 0x01, 0x02]), Likelihood::Unlikely);
+        assert!(looks_encoded("caf\u{e9}") <= Likelihood::Possible);
+    }
+}
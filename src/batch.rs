@@ -0,0 +1,191 @@
+//! Batch encoding and decoding of many independent payloads.
+
+use alloc::{string::String, vec::Vec};
+
+use crate::{decode_to_vec, decoded_len, encode_to_vec, max_encoded_len, DecodeError, Profile};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// Encodes each item in `items` independently.
+///
+/// Equivalent to calling [`encode`](crate::encode) on every item, but each
+/// output is allocated once at its final size, so no buffer is ever grown
+/// or copied while encoding. With the `rayon` feature enabled the items are
+/// encoded in parallel on the global rayon thread pool; output order always
+/// matches input order.
+///
+/// Items can be any `AsRef<[u8]>`: `&[u8]`, `Vec<u8>`, `String`, byte
+/// arrays, and so on.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{decode, encode_batch};
+///
+/// let items: [&[u8]; 3] = [b"first", b"second", &[0, 10, 13]];
+/// let encoded = encode_batch(&items);
+///
+/// assert_eq!(encoded.len(), 3);
+/// for (item, encoded) in items.iter().zip(&encoded) {
+///     assert_eq!(&decode(encoded).unwrap()[..], *item);
+/// }
+/// ```
+pub fn encode_batch<T: AsRef<[u8]> + Sync>(items: &[T]) -> Vec<String> {
+    #[cfg(feature = "rayon")]
+    let encoded = items
+        .par_iter()
+        .map(|item| encode_item(item.as_ref()))
+        .collect();
+
+    #[cfg(not(feature = "rayon"))]
+    let encoded = items
+        .iter()
+        .map(|item| encode_item(item.as_ref()))
+        .collect();
+
+    encoded
+}
+
+/// Encodes a single batch item into an exactly pre-sized buffer.
+fn encode_item(item: &[u8]) -> String {
+    let mut buffer = Vec::with_capacity(max_encoded_len(item.len()));
+    encode_to_vec(item, &mut buffer);
+    String::from_utf8(buffer).unwrap_or_else(|_| String::new())
+}
+
+/// Decodes each item in `items` independently.
+///
+/// A malformed item yields an `Err` in its slot without affecting the rest
+/// of the batch; use [`BatchStats::from_results`] to summarize the outcome.
+/// As with [`encode_batch`], the `rayon` feature decodes items in parallel
+/// while preserving their order. Items can be `&str`, `String`, or any
+/// other `AsRef<str>`.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{decode_batch, encode, BatchStats};
+///
+/// let good = encode(b"payload");
+/// let results = decode_batch(&[&good, "\u{20AC}", ""]);
+///
+/// assert_eq!(results[0].as_deref(), Ok(&b"payload"[..]));
+/// assert!(results[1].is_err());
+/// assert_eq!(results[2].as_deref(), Ok(&b""[..]));
+///
+/// let stats = BatchStats::from_results(&results);
+/// assert_eq!((stats.succeeded, stats.failed), (2, 1));
+/// ```
+pub fn decode_batch<T: AsRef<str> + Sync>(items: &[T]) -> Vec<Result<Vec<u8>, DecodeError>> {
+    #[cfg(feature = "rayon")]
+    let decoded = items
+        .par_iter()
+        .map(|item| decode_item(item.as_ref()))
+        .collect();
+
+    #[cfg(not(feature = "rayon"))]
+    let decoded = items
+        .iter()
+        .map(|item| decode_item(item.as_ref()))
+        .collect();
+
+    decoded
+}
+
+/// Decodes a single batch item into a buffer of the exact size.
+fn decode_item(item: &str) -> Result<Vec<u8>, DecodeError> {
+    let mut buffer = Vec::with_capacity(decoded_len(&Profile::STANDARD, item));
+    decode_to_vec(item, &mut buffer)?;
+    Ok(buffer)
+}
+
+/// Aggregate outcome of a [`decode_batch`] call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchStats {
+    /// Number of items in the batch.
+    pub items: usize,
+    /// Number of items that decoded successfully.
+    pub succeeded: usize,
+    /// Number of items that failed to decode.
+    pub failed: usize,
+    /// Total number of bytes decoded from the successful items.
+    pub decoded_bytes: usize,
+    /// Index and error of the first failing item, if any.
+    pub first_error: Option<(usize, DecodeError)>,
+}
+
+impl BatchStats {
+    /// Summarizes the results of a batch decode.
+    pub fn from_results(results: &[Result<Vec<u8>, DecodeError>]) -> Self {
+        let mut stats = BatchStats {
+            items: results.len(),
+            ..BatchStats::default()
+        };
+
+        for (index, result) in results.iter().enumerate() {
+            match result {
+                Ok(decoded) => {
+                    stats.succeeded += 1;
+                    stats.decoded_bytes += decoded.len();
+                }
+                Err(error) => {
+                    stats.failed += 1;
+                    stats.first_error.get_or_insert((index, *error));
+                }
+            }
+        }
+
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode, encode};
+
+    #[test]
+    fn test_encode_batch_matches_encode() {
+        let items: Vec<Vec<u8>> = (0..50)
+            .map(|n| (0..n * 3).map(|i| (i * 31 % 256) as u8).collect())
+            .collect();
+        let encoded = encode_batch(&items);
+        assert_eq!(encoded.len(), items.len());
+        for (item, encoded) in items.iter().zip(&encoded) {
+            assert_eq!(*encoded, encode(item));
+        }
+    }
+
+    #[test]
+    fn test_encode_batch_empty() {
+        assert!(encode_batch::<&[u8]>(&[]).is_empty());
+        assert_eq!(encode_batch(&[&[]]), vec![String::new()]);
+    }
+
+    #[test]
+    fn test_decode_batch_isolates_errors() {
+        let first = encode(b"first");
+        let last = encode(&[0, 10, 13, 34, 38, 92]);
+        let items = [
+            first.clone(),
+            "bad\u{0800}".into(),
+            "\u{0680}".into(),
+            last.clone(),
+        ];
+
+        let results = decode_batch(&items);
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0], decode(&first));
+        assert!(results[1].is_err());
+        assert!(results[2].is_err());
+        assert_eq!(results[3], decode(&last));
+
+        let stats = BatchStats::from_results(&results);
+        assert_eq!(stats.items, 4);
+        assert_eq!(stats.succeeded, 2);
+        assert_eq!(stats.failed, 2);
+        assert_eq!(stats.decoded_bytes, 5 + 6);
+        assert_eq!(stats.first_error.map(|(index, _)| index), Some(1));
+    }
+}
@@ -0,0 +1,214 @@
+//! Binary data in text database columns and cache values.
+
+use alloc::vec::Vec;
+
+/// Binary data stored as Base122 text.
+///
+/// Written as the Base122 text of the bytes and read back from it, so a
+/// place that must hold text holds the data in about 8/7 of its size rather
+/// than 4/3 for Base64 or twice for hex:
+///
+/// - With the `sqlx` feature, a TEXT column, such as in a schema shared with
+///   other tools or a database without a convenient binary type. Works with
+///   any sqlx database whose `String` and `&str` map to its text type,
+///   including SQLite and PostgreSQL. Decoding a value that is not Base122
+///   text fails with a [`DecodeError`](crate::DecodeError) as the source of
+///   sqlx's error.
+/// - With the `redis` feature, a Redis string value, for caching layers
+///   whose keyspace holds text. Reading a value that is not Base122 text
+///   fails with a `TypeError`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "sqlx")]
+/// # mod example {
+/// use base122_rs::Base122Blob;
+/// use sqlx::SqliteConnection;
+///
+/// async fn store(db: &mut SqliteConnection, thumbnail: Vec<u8>) -> sqlx::Result<()> {
+///     sqlx::query("INSERT INTO images (thumbnail) VALUES (?)")
+///         .bind(Base122Blob(thumbnail))
+///         .execute(&mut *db)
+///         .await?;
+///     let (Base122Blob(thumbnail),): (Base122Blob,) =
+///         sqlx::query_as("SELECT thumbnail FROM images").fetch_one(db).await?;
+///     Ok(())
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Base122Blob(pub Vec<u8>);
+
+impl From<Vec<u8>> for Base122Blob {
+    fn from(data: Vec<u8>) -> Self {
+        Base122Blob(data)
+    }
+}
+
+impl From<Base122Blob> for Vec<u8> {
+    fn from(blob: Base122Blob) -> Self {
+        blob.0
+    }
+}
+
+#[cfg(feature = "sqlx")]
+mod with_sqlx {
+    use alloc::string::String;
+
+    use sqlx::database::Database;
+    use sqlx::decode::Decode;
+    use sqlx::encode::{Encode, IsNull};
+    use sqlx::error::BoxDynError;
+    use sqlx::types::Type;
+
+    use super::Base122Blob;
+    use crate::{decode, encode};
+
+    impl<DB: Database> Type<DB> for Base122Blob
+    where
+        String: Type<DB>,
+    {
+        fn type_info() -> DB::TypeInfo {
+            <String as Type<DB>>::type_info()
+        }
+
+        fn compatible(ty: &DB::TypeInfo) -> bool {
+            <String as Type<DB>>::compatible(ty)
+        }
+    }
+
+    impl<'q, DB: Database> Encode<'q, DB> for Base122Blob
+    where
+        String: Encode<'q, DB>,
+    {
+        fn encode_by_ref(&self, buf: &mut DB::ArgumentBuffer<'q>) -> Result<IsNull, BoxDynError> {
+            encode(&self.0).encode(buf)
+        }
+    }
+
+    impl<'r, DB: Database> Decode<'r, DB> for Base122Blob
+    where
+        &'r str: Decode<'r, DB>,
+    {
+        fn decode(value: DB::ValueRef<'r>) -> Result<Self, BoxDynError> {
+            let text = <&str as Decode<DB>>::decode(value)?;
+            Ok(Base122Blob(decode(text)?))
+        }
+    }
+}
+
+#[cfg(feature = "redis")]
+mod with_redis {
+    use alloc::string::{String, ToString};
+
+    use redis::{ErrorKind, FromRedisValue, RedisResult, RedisWrite, ToRedisArgs, Value};
+
+    use super::Base122Blob;
+    use crate::{decode, encode};
+
+    impl ToRedisArgs for Base122Blob {
+        fn write_redis_args<W: ?Sized + RedisWrite>(&self, out: &mut W) {
+            out.write_arg(encode(&self.0).as_bytes());
+        }
+    }
+
+    impl FromRedisValue for Base122Blob {
+        fn from_redis_value(v: &Value) -> RedisResult<Self> {
+            let text = String::from_redis_value(v)?;
+            decode(&text).map(Base122Blob).map_err(|error| {
+                (
+                    ErrorKind::TypeError,
+                    "Response was not Base122",
+                    error.to_string(),
+                )
+                    .into()
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode;
+
+    #[cfg(feature = "sqlx")]
+    fn run<F: core::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[cfg(feature = "sqlx")]
+    #[test]
+    fn test_sqlite_roundtrip() {
+        use sqlx::{Connection, SqliteConnection};
+
+        run(async {
+            let mut db = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+            sqlx::query("CREATE TABLE blobs (data TEXT NOT NULL)")
+                .execute(&mut db)
+                .await
+                .unwrap();
+
+            let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+            sqlx::query("INSERT INTO blobs (data) VALUES (?)")
+                .bind(Base122Blob(data.clone()))
+                .execute(&mut db)
+                .await
+                .unwrap();
+
+            let (text,): (String,) = sqlx::query_as("SELECT data FROM blobs")
+                .fetch_one(&mut db)
+                .await
+                .unwrap();
+            assert_eq!(text, encode(&data));
+            let (blob,): (Base122Blob,) = sqlx::query_as("SELECT data FROM blobs")
+                .fetch_one(&mut db)
+                .await
+                .unwrap();
+            assert_eq!(blob, Base122Blob(data));
+        });
+    }
+
+    #[cfg(feature = "sqlx")]
+    #[test]
+    fn test_sqlite_invalid_text() {
+        use sqlx::{Connection, SqliteConnection};
+
+        run(async {
+            let mut db = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+            let error = sqlx::query_as::<_, (Base122Blob,)>("SELECT 'price: \u{20AC}'")
+                .fetch_one(&mut db)
+                .await
+                .unwrap_err();
+            let sqlx::Error::ColumnDecode { source, .. } = error else {
+                panic!("unexpected error: {error}");
+            };
+            assert!(source.downcast_ref::<crate::DecodeError>().is_some());
+        });
+    }
+
+    #[cfg(feature = "redis")]
+    #[test]
+    fn test_redis_value() {
+        use redis::{ErrorKind, FromRedisValue, ToRedisArgs, Value};
+
+        let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let args = Base122Blob(data.clone()).to_redis_args();
+        assert_eq!(args, [encode(&data).into_bytes()]);
+
+        let value = Value::BulkString(args.into_iter().next().unwrap());
+        assert_eq!(
+            Base122Blob::from_redis_value(&value).unwrap(),
+            Base122Blob(data)
+        );
+
+        let value = Value::BulkString("price: \u{20AC}".into());
+        let error = Base122Blob::from_redis_value(&value).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::TypeError);
+    }
+}
@@ -0,0 +1,206 @@
+//! Encoding HTTP bodies on the fly.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::pin::Pin;
+use core::task::{ready, Context, Poll};
+
+use bytes::{Buf, Bytes};
+use http_body::{Body, Frame, SizeHint};
+
+use crate::stream::EncodeState;
+
+/// An [`http_body::Body`] that encodes another body as it streams.
+///
+/// Each data frame of the inner body is encoded as soon as it arrives and
+/// sent on as a frame of Base122 text, so a service can return an encoded
+/// response without buffering it. The frames together are
+/// [`encode`](crate::encode) of the inner body's data; trailers are passed
+/// through after the last of the text. A frame that completes no
+/// characters is not sent.
+///
+/// Any body works as the inner one; in axum, wrap a response body as
+/// `axum::body::Body::new(EncodeBody::new(body))`.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::pin::pin;
+/// use std::task::{Context, Poll, Waker};
+/// use base122_rs::{encode, EncodeBody};
+/// use http_body::Body;
+///
+/// let response = http::Response::new(EncodeBody::new(String::from("\0payload\n")));
+///
+/// let mut body = pin!(response.into_body());
+/// let mut cx = Context::from_waker(Waker::noop());
+/// let mut text = Vec::new();
+/// while let Poll::Ready(Some(Ok(frame))) = body.as_mut().poll_frame(&mut cx) {
+///     text.extend(frame.into_data().unwrap());
+/// }
+/// assert_eq!(text, encode(b"\0payload\n").as_bytes());
+/// ```
+#[derive(Debug)]
+pub struct EncodeBody<B> {
+    inner: Pin<Box<B>>,
+    state: EncodeState,
+    /// Trailers to send once the text before them is out.
+    trailers: Option<http::HeaderMap>,
+    done: bool,
+}
+
+impl<B: Body> EncodeBody<B> {
+    /// Wraps `inner`, whose data is encoded.
+    pub fn new(inner: B) -> Self {
+        EncodeBody {
+            inner: Box::pin(inner),
+            state: EncodeState::default(),
+            trailers: None,
+            done: false,
+        }
+    }
+
+    /// Encodes the rest of the text and marks the inner body as finished.
+    fn finish(&mut self) -> Option<Frame<Bytes>> {
+        self.done = true;
+        let mut encoded = Vec::with_capacity(2);
+        self.state.finish(&mut |byte| encoded.push(byte));
+        (!encoded.is_empty()).then(|| Frame::data(encoded.into()))
+    }
+}
+
+impl<B: Body> Body for EncodeBody<B> {
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, B::Error>>> {
+        let this = &mut *self;
+        loop {
+            if this.done {
+                return Poll::Ready(this.trailers.take().map(|t| Ok(Frame::trailers(t))));
+            }
+
+            let frame = match ready!(this.inner.as_mut().poll_frame(cx)) {
+                Some(Ok(frame)) => frame,
+                Some(Err(error)) => return Poll::Ready(Some(Err(error))),
+                None => match this.finish() {
+                    Some(frame) => return Poll::Ready(Some(Ok(frame))),
+                    None => continue,
+                },
+            };
+            let mut data = match frame.into_data() {
+                Ok(data) => data,
+                Err(frame) => {
+                    // Trailers end the body, so the text ends before them
+                    this.trailers = frame.into_trailers().ok();
+                    match this.finish() {
+                        Some(frame) => return Poll::Ready(Some(Ok(frame))),
+                        None => continue,
+                    }
+                }
+            };
+
+            let mut encoded = Vec::with_capacity(data.remaining() * 8 / 7 + 2);
+            while data.has_remaining() {
+                let chunk = data.chunk();
+                for &byte in chunk {
+                    this.state.push(byte, &mut |b| encoded.push(b));
+                }
+                let len = chunk.len();
+                data.advance(len);
+            }
+            if !encoded.is_empty() {
+                return Poll::Ready(Some(Ok(Frame::data(encoded.into()))));
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.done && self.trailers.is_none()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        let Some(len) = self.inner.size_hint().exact().filter(|_| !self.done) else {
+            return SizeHint::default();
+        };
+        // A character per chunk, or two bytes for a final shortened escape;
+        // bits and an escape left over from earlier frames add at most four
+        let mut hint = SizeHint::new();
+        hint.set_lower(len * 8 / 7);
+        hint.set_upper((len * 8).div_ceil(7) + 4);
+        hint
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode, encode};
+    use alloc::collections::VecDeque;
+    use core::convert::Infallible;
+    use core::task::Waker;
+
+    /// A body sending prepared frames, one per poll.
+    struct Frames(VecDeque<Frame<Bytes>>);
+
+    impl Body for Frames {
+        type Data = Bytes;
+        type Error = Infallible;
+
+        fn poll_frame(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Option<Result<Frame<Bytes>, Infallible>>> {
+            Poll::Ready(self.0.pop_front().map(Ok))
+        }
+    }
+
+    fn collect(body: impl Body<Data = Bytes, Error = Infallible>) -> Vec<Frame<Bytes>> {
+        let mut body = Box::pin(body);
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut frames = Vec::new();
+        while let Poll::Ready(Some(frame)) = body.as_mut().poll_frame(&mut cx) {
+            frames.push(frame.unwrap());
+        }
+        assert!(body.is_end_stream());
+        frames
+    }
+
+    #[test]
+    fn test_encodes_data_frames() {
+        let data: Vec<u8> = (0..=255).cycle().take(3000).collect();
+        for piece in [1, 2, 7, 100, 3000] {
+            let frames = data
+                .chunks(piece)
+                .map(|c| Frame::data(Bytes::copy_from_slice(c)));
+            let encoded: Vec<u8> = collect(EncodeBody::new(Frames(frames.collect())))
+                .into_iter()
+                .flat_map(|frame| frame.into_data().unwrap())
+                .collect();
+            assert_eq!(encoded, encode(&data).as_bytes());
+            assert_eq!(
+                decode(core::str::from_utf8(&encoded).unwrap()).unwrap(),
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn test_trailers_follow_text() {
+        let mut trailers = http::HeaderMap::new();
+        trailers.insert("x-checksum", http::HeaderValue::from_static("abc"));
+        let frames = Frames(VecDeque::from([
+            Frame::data(Bytes::from_static(b"\0")),
+            Frame::data(Bytes::new()),
+            Frame::trailers(trailers.clone()),
+        ]));
+
+        let frames = collect(EncodeBody::new(frames));
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].data_ref().unwrap(), encode(b"\0").as_bytes());
+        assert_eq!(frames[1].trailers_ref(), Some(&trailers));
+    }
+}
@@ -0,0 +1,156 @@
+//! Encoder that remembers the output for inputs it has seen.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use sha2::{Digest, Sha256};
+
+use crate::Engine;
+
+/// An encoder that returns the stored output for inputs it has encoded
+/// before.
+///
+/// Outputs are stored under the SHA-256 hash of their input, so a payload
+/// is encoded once however many times it is passed in, and the inputs
+/// themselves are not kept. Outputs are shared [`Arc<str>`]s, cheap to
+/// clone into every page that embeds them. This suits template renderers
+/// that embed the same small assets, such as icons and fonts, into many
+/// responses. Looking up an input hashes all of it, which is cheaper than
+/// encoding it but not free, so caching pays off for repeated inputs only.
+///
+/// The cache holds at most [`max_entries`](EncoderCache::max_entries)
+/// outputs; once full, new inputs are encoded without being stored.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use base122_rs::{encode, EncoderCache};
+///
+/// let mut cache = EncoderCache::new();
+/// let icon = b"\x89PNG\r\n\x1a\n icon";
+/// let first = cache.encode(icon);
+/// let second = cache.encode(icon);
+/// assert!(Arc::ptr_eq(&first, &second));
+/// assert_eq!(&*first, encode(icon));
+/// assert_eq!(cache.len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct EncoderCache {
+    engine: Engine,
+    max_entries: usize,
+    entries: HashMap<[u8; 32], Arc<str>>,
+}
+
+impl EncoderCache {
+    /// Default for [`max_entries`](EncoderCache::max_entries).
+    pub const DEFAULT_MAX_ENTRIES: usize = 1024;
+
+    /// Creates an empty cache encoding with [`Engine::STANDARD`].
+    pub fn new() -> Self {
+        EncoderCache {
+            engine: Engine::STANDARD,
+            max_entries: Self::DEFAULT_MAX_ENTRIES,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Selects the engine that encodes inputs, discarding stored outputs.
+    pub fn with_engine(mut self, engine: Engine) -> Self {
+        self.engine = engine;
+        self.entries.clear();
+        self
+    }
+
+    /// Sets the number of outputs the cache holds at most.
+    ///
+    /// Stored outputs beyond the new limit are kept until
+    /// [`clear`](EncoderCache::clear).
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// The engine that encodes inputs.
+    pub fn engine(&self) -> Engine {
+        self.engine
+    }
+
+    /// The number of outputs the cache holds at most.
+    pub fn max_entries(&self) -> usize {
+        self.max_entries
+    }
+
+    /// Encodes `data`, or returns its stored output if it was encoded
+    /// before.
+    pub fn encode(&mut self, data: impl AsRef<[u8]>) -> Arc<str> {
+        let data = data.as_ref();
+        let key = Sha256::digest(data).into();
+        if let Some(encoded) = self.entries.get(&key) {
+            return Arc::clone(encoded);
+        }
+        let encoded: Arc<str> = self.engine.encode(data).into();
+        if self.entries.len() < self.max_entries {
+            self.entries.insert(key, Arc::clone(&encoded));
+        }
+        encoded
+    }
+
+    /// The number of stored outputs.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no outputs are stored.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Discards all stored outputs.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl Default for EncoderCache {
+    fn default() -> Self {
+        EncoderCache::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Profile;
+
+    #[test]
+    fn test_cache_returns_stored_output() {
+        let engine = Engine::new().with_profile(&Profile::XML);
+        let mut cache = EncoderCache::new().with_engine(engine);
+        let inputs: Vec<Vec<u8>> = (0..10).map(|n| vec![n; n as usize * 7]).collect();
+        for _ in 0..3 {
+            for input in &inputs {
+                assert_eq!(*cache.encode(input), engine.encode(input));
+            }
+        }
+        assert_eq!(cache.len(), inputs.len());
+        assert!(Arc::ptr_eq(
+            &cache.encode(&inputs[3]),
+            &cache.encode(&inputs[3])
+        ));
+    }
+
+    #[test]
+    fn test_cache_limit() {
+        let mut cache = EncoderCache::new().with_max_entries(2);
+        for n in 0..5u8 {
+            assert_eq!(*cache.encode([n]), crate::encode(&[n]));
+        }
+        assert_eq!(cache.len(), 2);
+        assert!(!Arc::ptr_eq(&cache.encode([4]), &cache.encode([4])));
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert!(Arc::ptr_eq(&cache.encode([4]), &cache.encode([4])));
+    }
+}
@@ -0,0 +1,175 @@
+//! Encoding and decoding into fixed-capacity [`heapless`] containers.
+
+use core::fmt;
+
+use heapless::{String, Vec};
+
+use crate::slice::output_too_small;
+use crate::stream::DecodeState;
+use crate::{Base122Ext, DecodeError, Engine};
+
+/// Error returned when output does not fit a fixed-capacity container.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CapacityError {
+    required: usize,
+    capacity: usize,
+}
+
+impl CapacityError {
+    /// Length in bytes the complete output needs.
+    pub fn required(&self) -> usize {
+        self.required
+    }
+
+    /// Capacity in bytes of the container.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "output needs {} bytes but the capacity is {}",
+            self.required, self.capacity
+        )
+    }
+}
+
+impl core::error::Error for CapacityError {}
+
+/// Encodes `data` into a [`heapless::String`] of capacity `N`.
+///
+/// Checks the exact encoded length first, so on failure nothing is
+/// encoded and the error reports the capacity `data` needs. Use
+/// [`Engine::max_encoded_len`] to size `N` for inputs of a known maximum
+/// length.
+///
+/// # Errors
+///
+/// Returns [`CapacityError`] if the encoded text is longer than `N` bytes.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::encode_heapless;
+///
+/// let encoded = encode_heapless::<32>(b"\0sensor\n").unwrap();
+/// assert_eq!(encoded, base122_rs::encode(b"\0sensor\n").as_str());
+///
+/// let error = encode_heapless::<4>(b"\0sensor\n").unwrap_err();
+/// assert_eq!((error.required(), error.capacity()), (11, 4));
+/// ```
+pub fn encode_heapless<const N: usize>(data: &[u8]) -> Result<String<N>, CapacityError> {
+    check_capacity(data, N)?;
+    let mut encoded = String::new();
+    for c in data.iter().copied().base122_encode() {
+        // Cannot fail: the length was checked above
+        let _ = encoded.push(c);
+    }
+    Ok(encoded)
+}
+
+/// Encodes `data` into a [`heapless::Vec`] of capacity `N`, holding the
+/// UTF-8 bytes of the encoded text.
+///
+/// For byte-oriented transports whose buffers are `Vec<u8, N>`; otherwise
+/// like [`encode_heapless`].
+///
+/// # Errors
+///
+/// Returns [`CapacityError`] if the encoded text is longer than `N` bytes.
+pub fn encode_heapless_vec<const N: usize>(data: &[u8]) -> Result<Vec<u8, N>, CapacityError> {
+    Ok(encode_heapless::<N>(data)?.into_bytes())
+}
+
+/// Decodes `encoded` into a [`heapless::Vec`] of capacity `N`.
+///
+/// # Errors
+///
+/// Fails like [`decode`](crate::decode) on invalid input, and with
+/// [`DecodeError::OutputTooSmall`] at the first character whose bits do
+/// not fit, reporting the capacity the whole output needs.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{decode_heapless, encode_heapless, DecodeError};
+///
+/// let encoded = encode_heapless::<32>(b"\0sensor\n").unwrap();
+/// assert_eq!(decode_heapless::<8>(&encoded).unwrap(), b"\0sensor\n");
+/// assert!(matches!(
+///     decode_heapless::<4>(&encoded),
+///     Err(DecodeError::OutputTooSmall { .. })
+/// ));
+/// ```
+pub fn decode_heapless<const N: usize>(encoded: &str) -> Result<Vec<u8, N>, DecodeError> {
+    let mut decoded = Vec::new();
+    let mut state = DecodeState::default();
+    let mut full = false;
+    for (i, &byte) in encoded.as_bytes().iter().enumerate() {
+        state.push(byte, &mut |byte| full |= decoded.push(byte).is_err())?;
+        if full {
+            // The character this byte completes
+            let position = (0..=i).rev().find(|&i| encoded.is_char_boundary(i));
+            return Err(output_too_small(encoded, position.unwrap_or_default()));
+        }
+    }
+    state.finish()?;
+    Ok(decoded)
+}
+
+/// Fails unless the encoding of `data` fits in `capacity` bytes.
+fn check_capacity(data: &[u8], capacity: usize) -> Result<(), CapacityError> {
+    let required = Engine::STANDARD.encoded_len(data);
+    if required > capacity {
+        return Err(CapacityError { required, capacity });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::{generate, Profile};
+    use crate::encode;
+
+    #[test]
+    fn test_heapless_matches_encode() {
+        for len in 0..100 {
+            let data = generate(Profile::DangerousDensity(0.5), len, len as u64);
+            let encoded = encode_heapless::<256>(&data).unwrap();
+            assert_eq!(encoded, encode(&data).as_str());
+            assert_eq!(
+                encode_heapless_vec::<256>(&data).unwrap(),
+                encoded.as_bytes()
+            );
+            assert_eq!(decode_heapless::<100>(&encoded).unwrap(), data[..]);
+        }
+    }
+
+    #[test]
+    fn test_capacity_exceeded() {
+        let data = [0u8; 50];
+        let required = encode(&data).len();
+        assert_eq!(
+            encode_heapless::<16>(&data),
+            Err(CapacityError {
+                required,
+                capacity: 16
+            })
+        );
+        assert!(encode_heapless_vec::<16>(&data).is_err());
+
+        let encoded = encode(&data);
+        let error = decode_heapless::<49>(&encoded).unwrap_err();
+        let DecodeError::OutputTooSmall { position, required } = error else {
+            panic!("unexpected {error:?}");
+        };
+        assert!(encoded.is_char_boundary(position) && position > 0);
+        assert_eq!(required, data.len());
+        assert_eq!(decode_heapless::<50>(&encoded).unwrap(), data);
+    }
+}
@@ -0,0 +1,236 @@
+//! Inventories of the characters an engine emits.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::RangeInclusive;
+
+use crate::{ChunkWidth, Engine, Profile};
+
+/// The exact set of characters that can appear in an engine's output.
+///
+/// Produced by [`Engine::output_charset`], for configuring systems that
+/// validate or store encoded text, such as database collations, font
+/// subsets, or protocol filters. `Display` lists the ranges as code
+/// points, e.g. `U+0001-U+0009, U+000B-U+000C, ...`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharsetReport {
+    ranges: Vec<RangeInclusive<char>>,
+    terminator: Option<char>,
+}
+
+impl CharsetReport {
+    /// Builds the report for `engine`.
+    pub(crate) fn new(engine: &Engine) -> Self {
+        let alphabet = engine.alphabet();
+        let profile = engine.profile();
+        // The decoder accepts a whole shortened block, but the encoder only
+        // uses the code points of dangerous values there
+        let emits = |c: char| {
+            alphabet.emits(c)
+                && match (engine.chunk_width(), profile.split(c)) {
+                    (ChunkWidth::Seven, Some((None, payload))) if !c.is_ascii() => {
+                        profile.escape_index(payload).is_some()
+                    }
+                    _ => true,
+                }
+        };
+        CharsetReport {
+            ranges: collect_ranges(('\0'..='\u{FFFF}').filter(|&c| emits(c))),
+            terminator: engine.terminator().then(|| alphabet.terminator()),
+        }
+    }
+
+    /// Disjoint ranges of the characters messages are made of, in
+    /// ascending order. Excludes the terminator.
+    pub fn ranges(&self) -> &[RangeInclusive<char>] {
+        &self.ranges
+    }
+
+    /// The terminator, if the engine ends messages with one.
+    pub fn terminator(&self) -> Option<char> {
+        self.terminator
+    }
+
+    /// Whether `c` can appear in output, including as the terminator.
+    pub fn contains(&self, c: char) -> bool {
+        self.terminator == Some(c) || self.ranges.iter().any(|range| range.contains(&c))
+    }
+
+    /// Number of distinct characters that can appear in output, including
+    /// the terminator.
+    pub fn len(&self) -> usize {
+        let chars: usize = self
+            .ranges
+            .iter()
+            .map(|range| (*range.end() as u32 - *range.start() as u32) as usize + 1)
+            .sum();
+        chars + usize::from(self.terminator.is_some())
+    }
+
+    /// Whether no character can appear in output, which is never the case
+    /// for a valid engine.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// A regex character class matching any one character of output,
+    /// including the terminator, e.g. `[\u0001-\u0009\u000B-\u000C...]`.
+    ///
+    /// Code points are written as `\uXXXX`, which JavaScript, Python, Java,
+    /// .NET, PostgreSQL, and the `regex` crate all understand.
+    pub fn regex_class(&self) -> String {
+        let mut class = String::new();
+        let terminator = self.terminator.map(|c| c..=c);
+        write_class(&mut class, self.ranges.iter().cloned().chain(terminator));
+        class
+    }
+
+    /// Iterates over every character that can appear in output, in
+    /// ascending order, followed by the terminator.
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.ranges.iter().cloned().flatten().chain(self.terminator)
+    }
+}
+
+/// A regex matching exactly the strings `profile` encodes data into.
+///
+/// Checks that every character is one the encoder emits and that a
+/// shortened escape, which only ends a message, comes last. Lets services
+/// written in other languages reject malformed input without porting the
+/// decoder. Stricter than [`is_valid`](crate::is_valid), which accepts
+/// anything [`decode`](crate::decode) does. The pattern is anchored with
+/// `^` and `$` and uses the escapes described in
+/// [`CharsetReport::regex_class`]. It does not allow a terminator.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{validation_pattern, Profile};
+///
+/// let pattern = validation_pattern(&Profile::STANDARD);
+/// assert!(pattern.starts_with("^[\\u0001-\\u0009"));
+/// assert!(pattern.ends_with("]?$"));
+/// ```
+pub fn validation_pattern(profile: &'static Profile) -> String {
+    let report = Engine::new().with_profile(profile).output_charset();
+    let shortened: Vec<char> = profile
+        .dangerous()
+        .iter()
+        .filter_map(|&chunk| char::from_u32(profile.shortened(chunk)))
+        .collect();
+    let body = collect_ranges(report.chars().filter(|c| !shortened.contains(c)));
+
+    let mut pattern = String::from("^");
+    write_class(&mut pattern, body.into_iter());
+    pattern.push('*');
+    write_class(
+        &mut pattern,
+        collect_ranges(shortened.into_iter()).into_iter(),
+    );
+    pattern.push_str("?$");
+    pattern
+}
+
+/// Merges ascending characters into ranges of consecutive code points.
+fn collect_ranges(chars: impl Iterator<Item = char>) -> Vec<RangeInclusive<char>> {
+    let mut ranges: Vec<RangeInclusive<char>> = Vec::new();
+    for c in chars {
+        match ranges.last_mut() {
+            Some(last) if *last.end() as u32 + 1 == c as u32 => {
+                *last = *last.start()..=c;
+            }
+            _ => ranges.push(c..=c),
+        }
+    }
+    ranges
+}
+
+/// Appends a character class of `ranges` with `\uXXXX` escapes.
+fn write_class(out: &mut String, ranges: impl Iterator<Item = RangeInclusive<char>>) {
+    use core::fmt::Write;
+
+    out.push('[');
+    for range in ranges {
+        let (start, end) = (*range.start() as u32, *range.end() as u32);
+        // Writing to a String cannot fail
+        let _ = match end - start {
+            0 => write!(out, "\\u{start:04X}"),
+            1 => write!(out, "\\u{start:04X}\\u{end:04X}"),
+            _ => write!(out, "\\u{start:04X}-\\u{end:04X}"),
+        };
+    }
+    out.push(']');
+}
+
+impl fmt::Display for CharsetReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, range) in self.ranges.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            let (start, end) = (*range.start() as u32, *range.end() as u32);
+            if start == end {
+                write!(f, "U+{start:04X}")?;
+            } else {
+                write!(f, "U+{start:04X}-U+{end:04X}")?;
+            }
+        }
+        if let Some(terminator) = self.terminator {
+            write!(f, "; terminator U+{:04X}", terminator as u32)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Profile;
+
+    #[test]
+    fn test_standard_charset() {
+        let report = Engine::new().output_charset();
+        // 122 ASCII characters and 6 full escape blocks plus 6 shortened
+        assert_eq!(report.len(), 122 + 6 * 128 + 6);
+        assert_eq!(report.ranges()[0], '\u{1}'..='\u{9}');
+        assert!(report.contains('\u{780}') && !report.contains('\u{781}'));
+        assert!(!report.contains('\u{680}') && report.terminator().is_none());
+        assert!(report
+            .to_string()
+            .starts_with("U+0001-U+0009, U+000B-U+000C, "));
+
+        let framed = Engine::new().with_terminator(true).output_charset();
+        assert!(framed.contains('\u{680}'));
+        assert!(framed.to_string().ends_with("; terminator U+0680"));
+    }
+
+    #[test]
+    fn test_validation_pattern() {
+        let pattern = validation_pattern(&Profile::STANDARD);
+        assert!(pattern.starts_with("^[\\u0001-\\u0009\\u000B\\u000C\\u000E-\\u0021"));
+        assert!(pattern.ends_with("]*[\\u0780\\u078A\\u078D\\u07A2\\u07A6\\u07DC]?$"));
+        // Shortened escapes only appear in the final class
+        let (body, _) = pattern.split_once("]*").unwrap();
+        assert!(!body.contains("\\u0780"));
+
+        let framed = Engine::new().with_terminator(true).output_charset();
+        assert!(framed.regex_class().ends_with("\\u07DC\\u0680]"));
+    }
+
+    #[test]
+    fn test_charset_covers_output() {
+        let data: Vec<u8> = (0..=255).cycle().take(5000).collect();
+        for engine in [
+            Engine::new().with_profile(&Profile::NORMALIZATION_STABLE),
+            Engine::new().with_profile(&Profile::FILENAME),
+            Engine::new()
+                .with_chunk_width(ChunkWidth::Six)
+                .with_terminator(true),
+        ] {
+            let report = engine.output_charset();
+            assert!(engine.encode(&data).chars().all(|c| report.contains(c)));
+            assert_eq!(report.chars().count(), report.len());
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Encoding large payloads as an async stream of segments.
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use tokio::sync::mpsc;
+
+use crate::{encode, split_for_parallel};
+
+/// Segments encoded ahead of the one being consumed.
+const AHEAD: usize = 2;
+
+/// Encodes `data` as a [`Stream`] of segments, computed on Tokio's blocking
+/// thread pool.
+///
+/// The segments are the encodings of the pieces of
+/// [`split_for_parallel`], with `chunk_size` rounded down to a multiple
+/// of 7 bytes, so they can be sent as they arrive and joined into one
+/// string that [`decode`](crate::decode) turns back into `data`. An async
+/// handler can start writing the response after the first segment instead
+/// of waiting for the whole payload, without blocking its executor.
+///
+/// Encoding stays a couple of segments ahead of the consumer. Dropping the
+/// stream stops it after the segment in progress, so the work does not
+/// outlive the handler that asked for it.
+///
+/// # Panics
+///
+/// Panics if called outside a Tokio runtime.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::future::poll_fn;
+/// use std::pin::Pin;
+/// use base122_rs::{decode, encode_chunked_async};
+/// use futures_core::Stream;
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let data: Vec<u8> = (0..=255).cycle().take(100_000).collect();
+/// let mut segments = encode_chunked_async(data.clone(), 16 * 1024);
+///
+/// let mut encoded = String::new();
+/// while let Some(segment) = poll_fn(|cx| Pin::new(&mut segments).poll_next(cx)).await {
+///     encoded.push_str(&segment);
+/// }
+/// assert_eq!(decode(&encoded).unwrap(), data);
+/// # });
+/// ```
+pub fn encode_chunked_async(data: impl Into<Arc<[u8]>>, chunk_size: usize) -> EncodedChunks {
+    let data = data.into();
+    let (sender, receiver) = mpsc::channel(AHEAD);
+    tokio::task::spawn_blocking(move || {
+        for piece in split_for_parallel(&data, chunk_size) {
+            if sender.blocking_send(encode(piece)).is_err() {
+                // The stream was dropped
+                return;
+            }
+        }
+    });
+    EncodedChunks { receiver }
+}
+
+/// Stream returned by [`encode_chunked_async`].
+#[derive(Debug)]
+pub struct EncodedChunks {
+    receiver: mpsc::Receiver<String>,
+}
+
+impl Stream for EncodedChunks {
+    type Item = String;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<String>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode;
+    use core::future::poll_fn;
+
+    fn block_on<F: core::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    async fn next(segments: &mut EncodedChunks) -> Option<String> {
+        poll_fn(|cx| Pin::new(&mut *segments).poll_next(cx)).await
+    }
+
+    #[test]
+    fn test_segments_join_to_valid_encoding() {
+        let data: Vec<u8> = (0..=255).cycle().take(10_000).collect();
+        for chunk_size in [1, 7, 100, 4096, 20_000] {
+            let segments = block_on(async {
+                let mut stream = encode_chunked_async(data.clone(), chunk_size);
+                let mut segments = Vec::new();
+                while let Some(segment) = next(&mut stream).await {
+                    segments.push(segment);
+                }
+                segments
+            });
+            let expected: Vec<String> = split_for_parallel(&data, chunk_size).map(encode).collect();
+            assert_eq!(segments, expected);
+            assert_eq!(decode(&segments.concat()).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_dropping_stream_stops_encoding() {
+        let data = vec![0; 1_000_000];
+        let first = block_on(async {
+            let mut stream = encode_chunked_async(data, 7);
+            next(&mut stream).await
+        });
+        assert_eq!(first, Some(encode(&[0; 7])));
+    }
+}
@@ -0,0 +1,271 @@
+//! Deterministic test and benchmark data.
+//!
+//! Base122 throughput and output size depend on how many 7-bit chunks of
+//! the input are dangerous, so meaningful measurements need inputs with a
+//! known, reproducible mix. [`generate`] builds such inputs from a seed:
+//! the same profile, length, and seed always give the same bytes, on every
+//! platform and in every version of this crate.
+//!
+//! ```rust
+//! use base122_rs::corpus::{generate, Profile};
+//! use base122_rs::predict_overhead;
+//!
+//! let data = generate(Profile::DangerousDensity(0.25), 10_000, 42);
+//! assert_eq!(data, generate(Profile::DangerousDensity(0.25), 10_000, 42));
+//!
+//! let prediction = predict_overhead(&data);
+//! assert!(prediction.escapes > 0);
+//! ```
+//!
+//! [`encode_seeds`] and [`decode_seeds`] build starting corpora for fuzz
+//! targets from the same generator; the `fuzz_seeds` example writes them
+//! to disk.
+
+use alloc::{format, string::String, vec::Vec};
+
+use crate::{encode, Accumulator, ILLEGALS, ILLEGAL_INDEX, SAFE};
+
+/// Shape of the data produced by [`generate`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum Profile {
+    /// Uniformly random bytes, like compressed or encrypted payloads.
+    Random,
+    /// Printable ASCII words, spaces, and line breaks, like logs or JSON.
+    Text,
+    /// Runs of repeated bytes with frequent zeros, like raw bitmaps.
+    Image,
+    /// Random data in which each 7-bit chunk is dangerous with the given
+    /// probability, clamped to `0.0..=1.0`.
+    DangerousDensity(f64),
+}
+
+/// Generates `len` bytes of `profile`-shaped data from `seed`.
+pub fn generate(profile: Profile, len: usize, seed: u64) -> Vec<u8> {
+    let mut rng = SplitMix64(seed);
+    let mut data = Vec::with_capacity(len);
+
+    match profile {
+        Profile::Random => data.extend((0..len).map(|_| rng.next_u64() as u8)),
+        Profile::Text => {
+            const ALPHABET: &[u8] = b"etaoinshrdlucmfwypvbgkjqxz";
+            while data.len() < len {
+                let word_len = 1 + rng.below(9);
+                for _ in 0..word_len {
+                    data.push(ALPHABET[rng.below(ALPHABET.len())]);
+                }
+                data.push(if rng.below(12) == 0 { b'\n' } else { b' ' });
+            }
+            data.truncate(len);
+        }
+        Profile::Image => {
+            while data.len() < len {
+                let run = 1 + rng.below(32);
+                let value = if rng.below(3) == 0 {
+                    0
+                } else {
+                    rng.next_u64() as u8
+                };
+                data.extend((0..run).map(|_| value));
+            }
+            data.truncate(len);
+        }
+        Profile::DangerousDensity(density) => {
+            // Draw 7-bit chunks directly and pack them into bytes, so the
+            // density holds exactly at the level the encoder sees
+            let threshold = (density.clamp(0.0, 1.0) * (1u64 << 32) as f64) as u64;
+            let mut accumulator = Accumulator::default();
+            while data.len() < len {
+                let chunk = if rng.next_u64() >> 32 < threshold {
+                    ILLEGALS[rng.below(ILLEGALS.len())]
+                } else {
+                    rng.safe_chunk()
+                };
+                data.extend(accumulator.push7(chunk));
+            }
+            data.truncate(len);
+        }
+    }
+
+    data
+}
+
+/// A named input for a fuzz target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Seed {
+    /// A short, file-name-safe description of the input.
+    pub name: String,
+    /// The input itself.
+    pub input: Vec<u8>,
+}
+
+impl Seed {
+    fn new(name: impl Into<String>, input: impl Into<Vec<u8>>) -> Self {
+        Seed {
+            name: name.into(),
+            input: input.into(),
+        }
+    }
+}
+
+/// Input lengths around the 7-byte period of the chunking, where the final
+/// chunk changes shape.
+const SEED_LENGTHS: [usize; 12] = [0, 1, 2, 6, 7, 8, 13, 14, 15, 48, 49, 50];
+
+/// Seed inputs for fuzz targets that encode arbitrary bytes.
+///
+/// Covers lengths on both sides of every 7-byte boundary, data whose
+/// chunks are all dangerous (so the input may end on a shortened escape),
+/// and each of the corpus profiles.
+pub fn encode_seeds() -> Vec<Seed> {
+    let mut seeds = Vec::new();
+    for len in SEED_LENGTHS {
+        seeds.push(Seed::new(
+            format!("random-{len}"),
+            generate(Profile::Random, len, 0),
+        ));
+        let dangerous = generate(Profile::DangerousDensity(1.0), len, 0);
+        seeds.push(Seed::new(format!("dangerous-{len}"), dangerous));
+    }
+    for (i, &illegal) in ILLEGALS.iter().enumerate() {
+        // A single dangerous chunk followed by padding
+        seeds.push(Seed::new(format!("lone-dangerous-{i}"), [illegal << 1]));
+    }
+    seeds.push(Seed::new("zeros", [0; 64]));
+    seeds.push(Seed::new("ones", [0xFF; 64]));
+    seeds.push(Seed::new("text", generate(Profile::Text, 300, 0)));
+    seeds.push(Seed::new("image", generate(Profile::Image, 300, 0)));
+    seeds.push(Seed::new(
+        "half-dangerous",
+        generate(Profile::DangerousDensity(0.5), 300, 0),
+    ));
+    seeds
+}
+
+/// Seed inputs for fuzz targets that decode bytes.
+///
+/// Contains the encoding of every [`encode_seeds`] input plus malformed
+/// text the decoder must reject cleanly: truncated escapes, invalid
+/// continuation bytes, overlong and out-of-range sequences, the reserved
+/// terminator, and bytes that are not UTF-8 at all. A shortened escape in
+/// the middle of the input, which the decoder accepts, is included too.
+pub fn decode_seeds() -> Vec<Seed> {
+    let mut seeds: Vec<Seed> = encode_seeds()
+        .into_iter()
+        .map(|seed| Seed::new(format!("valid-{}", seed.name), encode(&seed.input)))
+        .collect();
+
+    let escaped = encode(&generate(Profile::DangerousDensity(1.0), 8, 0));
+    let mut truncated = escaped.into_bytes();
+    truncated.pop();
+    seeds.push(Seed::new("truncated-escape", truncated));
+
+    let malformed: [(&str, &[u8]); 10] = [
+        ("lone-lead", b"abc\xC2"),
+        ("lead-then-ascii", b"\xC2abc"),
+        ("lone-continuation", b"abc\x80"),
+        ("double-lead", b"\xC2\xC2\x80"),
+        ("overlong", b"\xC0\x80"),
+        ("unused-escape-block", "\u{0100}".as_bytes()),
+        ("terminator", "abc\u{0680}".as_bytes()),
+        ("shortened-mid-stream", "\u{0781}abc".as_bytes()),
+        ("three-byte", "\u{4E00}".as_bytes()),
+        ("not-utf8", b"\xFF\xFE\x00"),
+    ];
+    for (name, input) in malformed {
+        seeds.push(Seed::new(name, input));
+    }
+    seeds
+}
+
+/// SplitMix64: tiny, fast, and fully specified, so output never changes.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a value in `0..bound`.
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// Returns a random 7-bit chunk that is not dangerous.
+    fn safe_chunk(&mut self) -> u8 {
+        loop {
+            let chunk = (self.next_u64() & 0x7F) as u8;
+            if ILLEGAL_INDEX[chunk as usize] == SAFE {
+                return chunk;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::predict_overhead;
+
+    #[test]
+    fn test_generate_is_deterministic() {
+        for profile in [
+            Profile::Random,
+            Profile::Text,
+            Profile::Image,
+            Profile::DangerousDensity(0.5),
+        ] {
+            let data = generate(profile, 1000, 7);
+            assert_eq!(data.len(), 1000);
+            assert_eq!(data, generate(profile, 1000, 7));
+            assert_ne!(data, generate(profile, 1000, 8));
+        }
+        assert_eq!(&generate(Profile::Random, 4, 0), &[0xAF, 0xF4, 0x4F, 0xEC]);
+    }
+
+    #[test]
+    fn test_dangerous_density() {
+        let none = predict_overhead(generate(Profile::DangerousDensity(0.0), 7000, 1));
+        assert_eq!(none.escapes, 0);
+
+        let all = predict_overhead(generate(Profile::DangerousDensity(1.0), 7000, 1));
+        assert_eq!(all.escape_ratio(), 1.0);
+
+        // A dangerous chunk also absorbs the chunk after it: 2p / (1 + p)
+        let quarter = predict_overhead(generate(Profile::DangerousDensity(0.25), 70_000, 1));
+        assert!((quarter.escape_ratio() - 0.4).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_seeds() {
+        let encode_seeds = encode_seeds();
+        let decode_seeds = decode_seeds();
+        assert!(decode_seeds.len() > encode_seeds.len());
+
+        for seed in &decode_seeds {
+            assert!(!seed.name.contains(['/', '\\', ' ']), "{}", seed.name);
+            let decoded = core::str::from_utf8(&seed.input).map(crate::decode);
+            // Encoded seeds decode to their source, the rest must not
+            match encode_seeds
+                .iter()
+                .find(|s| seed.name == format!("valid-{}", s.name))
+            {
+                Some(source) => assert_eq!(decoded, Ok(Ok(source.input.clone()))),
+                None if seed.name == "shortened-mid-stream" => assert!(decoded.is_ok()),
+                None => assert!(!matches!(decoded, Ok(Ok(_))), "{}", seed.name),
+            }
+        }
+    }
+
+    #[test]
+    fn test_text_profile_is_printable() {
+        let text = generate(Profile::Text, 5000, 3);
+        assert!(text
+            .iter()
+            .all(|&b| b == b'\n' || b.is_ascii_graphic() || b == b' '));
+    }
+}
@@ -0,0 +1,167 @@
+//! `data:` URIs with Base122 payloads.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{Engine, Profile};
+
+/// A `data:` URI carrying its payload as Base122 text.
+///
+/// `Display` writes `data:<media type>;base122,<encoded>`, ready to use as
+/// a `src`, `href`, or CSS `url()` value and readable by
+/// [`decode_auto`](crate::decode_auto). The constructors for common asset
+/// types fill in the media type, so it is not copied around by hand.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{decode_auto, DataUri};
+///
+/// let uri = DataUri::png(b"\x89PNG\r\n\x1a\n").to_string();
+/// assert!(uri.starts_with("data:image/png;base122,"));
+/// assert_eq!(decode_auto(&uri).unwrap(), b"\x89PNG\r\n\x1a\n");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataUri {
+    media_type: String,
+    engine: Engine,
+    data: Vec<u8>,
+}
+
+impl DataUri {
+    /// Creates a URI for `data` of type `media_type`, encoded with
+    /// [`Engine::STANDARD`].
+    pub fn new(media_type: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        DataUri {
+            media_type: media_type.into(),
+            engine: Engine::STANDARD,
+            data: data.into(),
+        }
+    }
+
+    /// Creates an `image/png` URI.
+    pub fn png(data: impl Into<Vec<u8>>) -> Self {
+        DataUri::new("image/png", data)
+    }
+
+    /// Creates a `font/woff2` URI.
+    pub fn woff2(data: impl Into<Vec<u8>>) -> Self {
+        DataUri::new("font/woff2", data)
+    }
+
+    /// Creates an `image/svg+xml` URI.
+    pub fn svg(data: impl Into<Vec<u8>>) -> Self {
+        DataUri::new("image/svg+xml", data)
+    }
+
+    /// Selects the engine that encodes the payload.
+    ///
+    /// The decoder from [`js_decoder_snippet`](crate::js_decoder_snippet)
+    /// must be generated for the same engine.
+    pub fn with_engine(mut self, engine: Engine) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    /// The media type of the payload.
+    pub fn media_type(&self) -> &str {
+        &self.media_type
+    }
+
+    /// The engine that encodes the payload.
+    pub fn engine(&self) -> Engine {
+        self.engine
+    }
+
+    /// The payload.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl fmt::Display for DataUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "data:{};base122,{}",
+            self.media_type,
+            self.engine.encode(&self.data)
+        )
+    }
+}
+
+/// Formats `uri` as a value for an SVG `href` or `xlink:href` attribute.
+///
+/// The payload is encoded with [`Profile::XML`] regardless of the engine
+/// `uri` was built with, and markup characters in the media type are
+/// written as entities, so the result can go between single or double
+/// quotes without further escaping. Attribute parsing leaves it
+/// unchanged, and [`decode_auto`](crate::decode_auto) decodes it.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{decode_auto, embed_in_svg_attribute, DataUri};
+///
+/// let icon = DataUri::png(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR");
+/// let href = embed_in_svg_attribute(&icon);
+/// assert!(!href.contains(['"', '\'', '&', '<', '\n', '\r', '\0']));
+/// assert_eq!(decode_auto(&href).unwrap(), icon.data());
+/// ```
+pub fn embed_in_svg_attribute(uri: &DataUri) -> String {
+    let mut value = String::from("data:");
+    for c in uri.media_type.chars() {
+        match c {
+            '"' => value.push_str("&quot;"),
+            '&' => value.push_str("&amp;"),
+            '\'' => value.push_str("&apos;"),
+            '<' => value.push_str("&lt;"),
+            c => value.push(c),
+        }
+    }
+    value.push_str(";base122,");
+    value.push_str(&Engine::new().with_profile(&Profile::XML).encode(&uri.data));
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode_auto, encode};
+
+    #[test]
+    fn test_media_types() {
+        let data = [0, 1, 2, 255];
+        for (uri, media_type) in [
+            (DataUri::png(data), "image/png"),
+            (DataUri::woff2(data), "font/woff2"),
+            (DataUri::svg(data), "image/svg+xml"),
+        ] {
+            assert_eq!(uri.media_type(), media_type);
+            assert_eq!(
+                uri.to_string(),
+                format!("data:{media_type};base122,{}", encode(&data))
+            );
+        }
+    }
+
+    #[test]
+    fn test_svg_attribute() {
+        let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let href = embed_in_svg_attribute(&DataUri::new("image/x-\"<&'", data.clone()));
+        assert!(href.starts_with("data:image/x-&quot;&lt;&amp;&apos;;base122,"));
+        assert!(!href.contains(|c: char| c.is_control() || "\"'<".contains(c)));
+
+        let payload = href.split_once(',').unwrap().1;
+        assert_eq!(decode_auto(payload).unwrap(), data);
+    }
+
+    #[test]
+    fn test_with_engine_roundtrip() {
+        let engine = Engine::new().with_profile(&Profile::DISPLAY);
+        let uri = DataUri::new("application/wasm", b"\0asm\n".to_vec()).with_engine(engine);
+        assert_eq!(uri.engine(), engine);
+        assert_eq!(decode_auto(&uri.to_string()).unwrap(), uri.data());
+    }
+}
@@ -0,0 +1,95 @@
+//! Terminal-safe rendering of encoded text.
+
+use core::fmt;
+
+/// Renders `encoded` with characters that would garble a terminal shown as
+/// escapes.
+///
+/// Encoded text is valid UTF-8 but not necessarily printable: safe chunks
+/// include ASCII control characters such as ESC, and escapes can be C1
+/// controls, combining marks that merge with their neighbors, or
+/// right-to-left letters that reorder the line. Those are written as
+/// `\u{...}` with the code point in hex, and backslashes are doubled so the
+/// output stays unambiguous. Everything else is written as is.
+///
+/// This is for looking at encoded data, e.g. in logs or a debugger; the
+/// rendered text is not itself decodable.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{display_escaped, encode};
+///
+/// let encoded = encode(&[0x03, 0xF6, 0x40]);
+/// assert_eq!(display_escaped(&encoded).to_string(), r"\u{1}}H\u{780}");
+/// ```
+pub fn display_escaped(encoded: &str) -> impl fmt::Display + '_ {
+    DisplayEscaped(encoded)
+}
+
+struct DisplayEscaped<'a>(&'a str);
+
+impl fmt::Display for DisplayEscaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            if c == '\\' {
+                f.write_str("\\\\")?;
+            } else if needs_escape(c) {
+                write!(f, "\\u{{{:x}}}", c as u32)?;
+            } else {
+                fmt::Write::write_char(f, c)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether `c` is unprintable or would affect how its neighbors display.
+fn needs_escape(c: char) -> bool {
+    if c.is_ascii() {
+        return c.is_ascii_control();
+    }
+    // Hebrew through NKo and the Arabic extensions are right-to-left
+    let rtl = ('\u{0590}'..='\u{08FF}').contains(&c);
+    // `escape_debug` escapes controls, format characters, and combining
+    // marks (grapheme extenders), which covers the rest
+    let unprintable = c.escape_debug().nth(1).is_some();
+    rtl || unprintable || c.is_whitespace()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encode, Engine, Profile};
+
+    #[test]
+    fn test_display_escaped_marks_problem_characters() {
+        let cases = [
+            ("plain", "plain"),
+            ("a\\b", r"a\\b"),
+            ("\u{1B}[2J", r"\u{1b}[2J"),
+            ("\u{85}x", r"\u{85}x"),
+            ("e\u{301}", r"e\u{301}"),
+            ("\u{5D0}\u{A0}", r"\u{5d0}\u{a0}"),
+            ("\u{100}\u{4E00}", "\u{100}\u{4E00}"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(display_escaped(input).to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_display_profile_renders_unchanged() {
+        // Control characters aside, display-safe output needs no escapes
+        let data: Vec<u8> = (0..2000).map(|i| (i * 89 % 256) as u8).collect();
+        let encoded = Engine::new().with_profile(&Profile::DISPLAY).encode(&data);
+        assert!(encoded
+            .chars()
+            .filter(|c| !c.is_ascii())
+            .all(|c| !needs_escape(c)));
+        assert!(encode(&data)
+            .chars()
+            .filter(|c| !c.is_ascii())
+            .any(needs_escape));
+    }
+}
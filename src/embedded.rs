@@ -0,0 +1,327 @@
+//! Stream adapters over the [`embedded-io`](embedded_io) traits.
+//!
+//! [`EncodeWriter`] encodes everything written to it into an underlying
+//! [`Write`], and [`DecodeReader`] decodes Base122 text read from an
+//! underlying [`Read`]. Both keep only a small fixed-size buffer and work
+//! without an allocator, so firmware can stream Base122 over a UART or
+//! similar byte channel.
+//!
+//! ```rust
+//! use base122_rs::embedded::{DecodeReader, EncodeWriter};
+//! use embedded_io::{Read, Write};
+//!
+//! let mut wire = [0u8; 64];
+//! let mut writer = EncodeWriter::new(&mut wire[..]);
+//! writer.write_all(b"\0sensor\nframe").unwrap();
+//! let remaining = writer.finish().unwrap().len();
+//! let sent = wire.len() - remaining;
+//!
+//! let mut reader = DecodeReader::new(&wire[..sent]);
+//! let mut frame = [0u8; 13];
+//! reader.read_exact(&mut frame).unwrap();
+//! assert_eq!(&frame, b"\0sensor\nframe");
+//! ```
+
+use core::fmt;
+
+use embedded_io::{ErrorKind, ErrorType, Read, Write};
+
+use crate::stream::{DecodeState, EncodeState};
+use crate::DecodeError;
+
+/// Size of the internal buffers used by the adapters.
+const BUFFER_SIZE: usize = 64;
+
+/// Maximum number of bytes encoding a single input byte can emit: two
+/// chunks, the first completing an escape sequence.
+const MAX_PUSH_OUTPUT: usize = 3;
+
+/// Encodes bytes written to it and writes the Base122 text to `W`.
+///
+/// Encoded output is staged in a small internal buffer and written out
+/// when it fills up or on [`flush`](Write::flush). Because the last 7-bit
+/// chunk cannot be emitted until the input is known to have ended, the
+/// stream must be completed with [`finish`](EncodeWriter::finish); dropping
+/// the writer discards any buffered output.
+#[derive(Debug)]
+pub struct EncodeWriter<W> {
+    inner: W,
+    state: EncodeState,
+    buffer: [u8; BUFFER_SIZE],
+    len: usize,
+}
+
+impl<W: Write> EncodeWriter<W> {
+    /// Creates a writer encoding into `inner`.
+    pub fn new(inner: W) -> Self {
+        EncodeWriter {
+            inner,
+            state: EncodeState::default(),
+            buffer: [0; BUFFER_SIZE],
+            len: 0,
+        }
+    }
+
+    /// Encodes the end of the input, flushes, and returns the inner writer.
+    pub fn finish(mut self) -> Result<W, W::Error> {
+        let EncodeWriter {
+            state, buffer, len, ..
+        } = &mut self;
+        state.finish(&mut |byte| {
+            buffer[*len] = byte;
+            *len += 1;
+        });
+        self.flush()?;
+        Ok(self.inner)
+    }
+
+    /// Returns a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner writer.
+    ///
+    /// Writing to it directly will corrupt the encoded stream.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Writes all buffered output to the inner writer.
+    fn drain(&mut self) -> Result<(), W::Error> {
+        self.inner.write_all(&self.buffer[..self.len])?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+impl<W: Write> ErrorType for EncodeWriter<W> {
+    type Error = W::Error;
+}
+
+impl<W: Write> Write for EncodeWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        for &byte in buf {
+            if self.len + MAX_PUSH_OUTPUT > BUFFER_SIZE {
+                self.drain()?;
+            }
+            let EncodeWriter {
+                state, buffer, len, ..
+            } = self;
+            state.push(byte, &mut |encoded| {
+                buffer[*len] = encoded;
+                *len += 1;
+            });
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.drain()?;
+        self.inner.flush()
+    }
+}
+
+/// Error returned by [`DecodeReader`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ReadError<E> {
+    /// The inner reader failed.
+    Read(E),
+    /// The data read is not valid Base122.
+    Decode(DecodeError),
+}
+
+impl<E: fmt::Debug> fmt::Display for ReadError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Read(error) => write!(f, "read failed: {error:?}"),
+            ReadError::Decode(error) => write!(f, "decode failed: {error}"),
+        }
+    }
+}
+
+impl<E: fmt::Debug> core::error::Error for ReadError<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            ReadError::Read(_) => None,
+            ReadError::Decode(error) => Some(error),
+        }
+    }
+}
+
+impl<E: embedded_io::Error> embedded_io::Error for ReadError<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            ReadError::Read(error) => error.kind(),
+            ReadError::Decode(_) => ErrorKind::InvalidData,
+        }
+    }
+}
+
+/// Reads Base122 text from `R` and yields the decoded bytes.
+///
+/// Encoded input may be split across reads at any byte, including inside a
+/// 2-byte character. If the input ends in the middle of a character the
+/// final read reports [`DecodeError::UnexpectedEnd`].
+#[derive(Debug)]
+pub struct DecodeReader<R> {
+    inner: R,
+    state: DecodeState,
+    buffer: [u8; BUFFER_SIZE],
+    start: usize,
+    end: usize,
+    eof: bool,
+}
+
+impl<R: Read> DecodeReader<R> {
+    /// Creates a reader decoding from `inner`.
+    pub fn new(inner: R) -> Self {
+        DecodeReader {
+            inner,
+            state: DecodeState::default(),
+            buffer: [0; BUFFER_SIZE],
+            start: 0,
+            end: 0,
+            eof: false,
+        }
+    }
+
+    /// Returns a reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Consumes the adapter and returns the inner reader.
+    ///
+    /// Any decoded bytes not yet read are lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Reads and decodes input until at least one decoded byte is buffered
+    /// or the input ends.
+    fn fill(&mut self) -> Result<(), ReadError<R::Error>> {
+        // Each encoded byte carries at most 7 bits, plus up to 7 bits held
+        // back from a 2-byte character split across reads, so decoding
+        // `input` never produces more than `BUFFER_SIZE` bytes.
+        let mut input = [0u8; BUFFER_SIZE];
+        self.start = 0;
+        self.end = 0;
+
+        while self.end == 0 && !self.eof {
+            let read = self.inner.read(&mut input).map_err(ReadError::Read)?;
+            if read == 0 {
+                self.eof = true;
+                self.state.finish().map_err(ReadError::Decode)?;
+                break;
+            }
+
+            let DecodeReader {
+                state, buffer, end, ..
+            } = self;
+            state
+                .push_slice(&input[..read], &mut |byte| {
+                    buffer[*end] = byte;
+                    *end += 1;
+                })
+                .map_err(ReadError::Decode)?;
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> ErrorType for DecodeReader<R> {
+    type Error = ReadError<R::Error>;
+}
+
+impl<R: Read> Read for DecodeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.start == self.end {
+            self.fill()?;
+        }
+
+        let count = buf.len().min(self.end - self.start);
+        buf[..count].copy_from_slice(&self.buffer[self.start..self.start + count]);
+        self.start += count;
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode, encode};
+
+    #[test]
+    fn test_encode_writer_matches_encode() {
+        let data: Vec<u8> = (0..500).map(|i| (i * 7 % 256) as u8).collect();
+        let mut output = vec![0u8; 1000];
+
+        let mut writer = EncodeWriter::new(&mut output[..]);
+        for piece in data.chunks(13) {
+            writer.write_all(piece).unwrap();
+        }
+        let remaining = writer.finish().unwrap().len();
+        let written = output.len() - remaining;
+
+        assert_eq!(&output[..written], encode(&data).as_bytes());
+    }
+
+    #[test]
+    fn test_decode_reader_small_reads() {
+        let data: Vec<u8> = (0..500).map(|i| (i * i % 256) as u8).collect();
+        let encoded = encode(&data);
+
+        let mut reader = DecodeReader::new(encoded.as_bytes());
+        let mut decoded = Vec::new();
+        let mut buf = [0u8; 3];
+        loop {
+            let read = reader.read(&mut buf).unwrap();
+            if read == 0 {
+                break;
+            }
+            decoded.extend_from_slice(&buf[..read]);
+        }
+        assert_eq!(decoded, data);
+        assert_eq!(decoded, decode(&encoded).unwrap());
+    }
+
+    #[test]
+    fn test_decode_reader_reports_errors() {
+        let mut buf = [0u8; 16];
+
+        let mut reader = DecodeReader::new(&b"abc\xC2"[..]);
+        assert_eq!(reader.read(&mut buf), Ok(2));
+        assert_eq!(
+            reader.read(&mut buf),
+            Err(ReadError::Decode(DecodeError::UnexpectedEnd {
+                position: 4
+            }))
+        );
+
+        let mut reader = DecodeReader::new("ab€".as_bytes());
+        assert!(matches!(
+            reader.read(&mut buf),
+            Err(ReadError::Decode(DecodeError::InvalidByte {
+                position: 2,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_read_error_source() {
+        let decode_error = DecodeError::UnexpectedEnd { position: 7 };
+        let error: ReadError<ErrorKind> = ReadError::Decode(decode_error);
+        let source = core::error::Error::source(&error).unwrap();
+        assert_eq!(source.to_string(), decode_error.to_string());
+
+        let error: ReadError<ErrorKind> = ReadError::Read(ErrorKind::Other);
+        assert!(core::error::Error::source(&error).is_none());
+        assert_eq!(embedded_io::Error::kind(&error), ErrorKind::Other);
+    }
+}
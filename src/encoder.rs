@@ -0,0 +1,267 @@
+//! Encoding and decoding into reusable output buffers.
+
+use alloc::{string::String, vec::Vec};
+
+use crate::{
+    decode_to_vec, decoded_len, encode_profile_group, encode_profile_tail, max_encoded_len,
+    DecodeError, Profile,
+};
+
+/// Bytes of encoded text [`encode_into`] assembles before appending them.
+const BLOCK: usize = 1024;
+
+/// An encoder that owns its output buffer and reuses it across calls.
+///
+/// Each call to [`encode`](Encoder::encode) clears the buffer without
+/// releasing its memory, so once the buffer has grown to fit the largest
+/// payload seen, encoding performs no further allocations. This suits hot
+/// loops that encode a payload, hand the result off (write it to a socket,
+/// copy it into a response), and move on to the next one.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{decode, Encoder};
+///
+/// let mut encoder = Encoder::with_buffer(String::with_capacity(1024));
+/// for payload in ["first", "second", "third"] {
+///     let encoded = encoder.encode(payload);
+///     assert_eq!(decode(encoded).unwrap(), payload.as_bytes());
+/// }
+/// assert!(encoder.capacity() >= 1024);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Encoder {
+    buffer: String,
+}
+
+impl Encoder {
+    /// Creates an encoder with an empty buffer.
+    ///
+    /// The buffer is allocated on first use and grows as needed.
+    pub fn new() -> Self {
+        Encoder::default()
+    }
+
+    /// Creates an encoder that reuses the allocation of `buffer`.
+    ///
+    /// Any existing contents of `buffer` are discarded.
+    pub fn with_buffer(mut buffer: String) -> Self {
+        buffer.clear();
+        Encoder { buffer }
+    }
+
+    /// Creates an encoder whose buffer fits inputs up to `input_len` bytes
+    /// without reallocating.
+    pub fn with_capacity(input_len: usize) -> Self {
+        Encoder {
+            buffer: String::with_capacity(max_encoded_len(input_len)),
+        }
+    }
+
+    /// Encodes `data`, replacing the previous output.
+    ///
+    /// The returned string borrows the encoder's buffer and is valid until
+    /// the next call.
+    pub fn encode(&mut self, data: impl AsRef<[u8]>) -> &str {
+        self.buffer.clear();
+        encode_into(data.as_ref(), &mut self.buffer);
+        &self.buffer
+    }
+
+    /// Returns the output of the most recent [`encode`](Encoder::encode) call.
+    pub fn as_str(&self) -> &str {
+        &self.buffer
+    }
+
+    /// Returns the number of bytes the buffer can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+
+    /// Consumes the encoder and returns its buffer.
+    ///
+    /// The buffer holds the output of the most recent call, if any.
+    pub fn into_buffer(self) -> String {
+        self.buffer
+    }
+}
+
+/// Appends the encoding of `data` to `out`.
+///
+/// Nothing is allocated when `out` already has room for the encoded text,
+/// so a caller encoding many small payloads can clear and reuse one
+/// string instead of allocating a new one per call. The text appended is
+/// exactly [`encode`](crate::encode) of `data`.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{encode, encode_into};
+///
+/// let mut out = String::with_capacity(64);
+/// for payload in [&b"\0first"[..], b"second\n"] {
+///     out.clear();
+///     encode_into(payload, &mut out);
+///     assert_eq!(out, encode(payload));
+/// }
+///
+/// // Existing contents are kept
+/// let mut line = String::from("data: ");
+/// encode_into(b"\0", &mut line);
+/// assert_eq!(line, format!("data: {}", encode(b"\0")));
+/// ```
+pub fn encode_into(data: &[u8], out: &mut String) {
+    let profile = &Profile::STANDARD;
+    out.reserve(max_encoded_len(data.len()));
+
+    // Groups are encoded into a block on the stack, which is checked as
+    // UTF-8 and appended in one piece; the existing contents of `out` are
+    // never checked again
+    let whole = data.len() - data.len() % 7;
+    let mut pending = None;
+    let mut block = [0; BLOCK];
+    let mut len = 0;
+    for group in data[..whole].chunks_exact(7) {
+        if len > BLOCK - 16 {
+            push_block(out, &block[..len]);
+            len = 0;
+        }
+        len += encode_profile_group(profile, group, &mut pending, &mut block[len..]);
+    }
+    push_block(out, &block[..len]);
+
+    let mut tail = [0; 16];
+    let len = encode_profile_tail(profile, pending, &data[whole..], &mut tail);
+    push_block(out, &tail[..len]);
+}
+
+/// Appends `block`, a whole number of encoded characters, to `out`.
+fn push_block(out: &mut String, block: &[u8]) {
+    out.push_str(core::str::from_utf8(block).unwrap_or_default());
+}
+
+/// Appends the decoding of `encoded` to `out`, returning the number of
+/// bytes appended.
+///
+/// The counterpart of [`encode_into`]: nothing is allocated when `out`
+/// already has room for the decoded bytes, so a decode loop can reuse one
+/// buffer for every payload.
+///
+/// # Errors
+///
+/// Fails like [`decode`](crate::decode) on invalid input. `out` is then
+/// left as it was before the call.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{decode_into, encode};
+///
+/// let mut out = Vec::with_capacity(64);
+/// for payload in [&b"\0first"[..], b"second\n"] {
+///     out.clear();
+///     assert_eq!(decode_into(&encode(payload), &mut out)?, payload.len());
+///     assert_eq!(out, payload);
+/// }
+///
+/// assert!(decode_into("\u{0800}", &mut out).is_err());
+/// assert_eq!(out, b"second\n");
+/// # Ok::<(), base122_rs::DecodeError>(())
+/// ```
+pub fn decode_into(encoded: &str, out: &mut Vec<u8>) -> Result<usize, DecodeError> {
+    let start = out.len();
+    out.reserve(decoded_len(&Profile::STANDARD, encoded));
+    if let Err(error) = decode_to_vec(encoded, out) {
+        out.truncate(start);
+        return Err(error);
+    }
+    Ok(out.len() - start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode;
+
+    #[test]
+    fn test_encoder_matches_encode() {
+        let mut encoder = Encoder::new();
+        for len in [0, 1, 7, 100, 3, 0, 1000, 5] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 13 % 256) as u8).collect();
+            assert_eq!(encoder.encode(&data), encode(&data));
+            assert_eq!(encoder.as_str(), encode(&data));
+        }
+    }
+
+    #[test]
+    fn test_encoder_reuses_allocation() {
+        let mut encoder = Encoder::with_capacity(1000);
+        let capacity = encoder.capacity();
+        let data = vec![0xAB; 1000];
+
+        for _ in 0..10 {
+            encoder.encode(&data);
+            encoder.encode(b"small");
+        }
+        assert_eq!(encoder.capacity(), capacity);
+        assert_eq!(encoder.into_buffer(), encode(b"small"));
+    }
+
+    #[test]
+    fn test_encode_into_appends() {
+        let mut out = String::from("prefix ");
+        let mut expected = out.clone();
+        for len in [0, 1, 6, 7, 8, 300] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 29 % 256) as u8).collect();
+            encode_into(&data, &mut out);
+            expected += &encode(&data);
+            assert_eq!(out, expected);
+        }
+
+        out.clear();
+        let capacity = out.capacity();
+        encode_into(b"\0\n", &mut out);
+        assert_eq!(out.capacity(), capacity);
+    }
+
+    #[test]
+    fn test_encode_into_crosses_blocks() {
+        use crate::corpus::{generate, Profile};
+
+        // All-zero input leaves an escape pending at the end of every group
+        let inputs = [
+            vec![0; BLOCK * 3 + 5],
+            generate(Profile::DangerousDensity(0.5), BLOCK * 3, 1),
+            generate(Profile::Image, BLOCK * 2 + 3, 2),
+        ];
+        for data in inputs {
+            let mut out = String::from("prefix ");
+            encode_into(&data, &mut out);
+            assert_eq!(out, format!("prefix {}", encode(&data)));
+        }
+    }
+
+    #[test]
+    fn test_decode_into_appends() {
+        let mut out = b"prefix".to_vec();
+        let mut expected = out.clone();
+        for len in [0, 1, 6, 7, 8, 300] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 29 % 256) as u8).collect();
+            assert_eq!(decode_into(&encode(&data), &mut out), Ok(len));
+            expected.extend_from_slice(&data);
+            assert_eq!(out, expected);
+        }
+
+        let bad = format!("{}\u{0800}", encode(&[0xAB; 100]));
+        assert!(decode_into(&bad, &mut out).is_err());
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_encoder_with_buffer_discards_contents() {
+        let mut encoder = Encoder::with_buffer(String::from("stale"));
+        assert_eq!(encoder.as_str(), "");
+        assert_eq!(encoder.encode(b""), "");
+    }
+}
@@ -0,0 +1,763 @@
+//! Configurable encoder/decoder.
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+
+#[cfg(feature = "alloc")]
+use crate::{
+    decode_ascii_chars_to_vec, decode_profile_str_to_vec, decoded_len, encode_ascii_chunks_to_vec,
+    encode_profile_chunks_to_vec, encode_profile_to_vec, CharsetReport, DecodeError,
+};
+use crate::{Alphabet, Base64Alphabet, BitChunks, Prediction, Profile};
+
+/// How many input bits each encoded character carries.
+///
+/// Narrower chunks trade density for a plainer output alphabet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum ChunkWidth {
+    /// Seven bits per character, with dangerous chunks escaped as described
+    /// by the engine's [`Profile`]. This is Base122, about 1.14 characters
+    /// per input byte.
+    #[default]
+    Seven,
+    /// Six bits per character from the Base64 alphabet (`A`–`Z`, `a`–`z`,
+    /// `0`–`9`, `+`, `/`), 1.33 characters per input byte.
+    ///
+    /// The output is printable ASCII and needs no escapes, so the profile
+    /// is not used; it is the same text as unpadded standard Base64. The
+    /// terminator is `=`.
+    Six,
+}
+
+impl ChunkWidth {
+    /// The number of input bits per chunk.
+    pub const fn bits(self) -> u32 {
+        match self {
+            ChunkWidth::Seven => 7,
+            ChunkWidth::Six => 6,
+        }
+    }
+}
+
+/// A Base122 encoder/decoder with non-default options.
+///
+/// The free functions [`encode`](crate::encode) and
+/// [`decode`](crate::decode) use the default configuration; an `Engine`
+/// is only needed to change it. Engines are small `Copy` values built with
+/// `const` methods, so a configuration can live in a `const` item.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::Engine;
+///
+/// const FRAMED: Engine = Engine::new().with_terminator(true);
+///
+/// let encoded = FRAMED.encode(b"message");
+/// assert_eq!(FRAMED.decode(&encoded).unwrap(), b"message");
+///
+/// // A truncated message is detected
+/// let truncated = &encoded[..encoded.len() - 2];
+/// assert!(FRAMED.decode(truncated).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Engine {
+    profile: &'static Profile,
+    width: ChunkWidth,
+    terminator: bool,
+    length_trailer: bool,
+}
+
+impl Engine {
+    /// The default engine, which [`encode`](crate::encode) and
+    /// [`decode`](crate::decode) use.
+    ///
+    /// Output is compatible with other Base122 implementations. Derive
+    /// other configurations from it with the `with_*` methods.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use base122_rs::{encode, Engine};
+    ///
+    /// assert_eq!(Engine::STANDARD.encode(b"\0data"), encode(b"\0data"));
+    ///
+    /// const FRAMED: Engine = Engine::STANDARD.with_terminator(true);
+    /// assert!(FRAMED.encode(b"\0data").ends_with('\u{0680}'));
+    /// ```
+    pub const STANDARD: Engine = Engine::new();
+
+    /// Creates an engine with the default options, matching
+    /// [`encode`](crate::encode) and [`decode`](crate::decode).
+    pub const fn new() -> Self {
+        Engine {
+            profile: &Profile::STANDARD,
+            width: ChunkWidth::Seven,
+            terminator: false,
+            length_trailer: false,
+        }
+    }
+
+    /// Selects the escaping profile.
+    ///
+    /// Defaults to [`Profile::STANDARD`], the only profile other Base122
+    /// implementations understand.
+    pub const fn with_profile(mut self, profile: &'static Profile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// The escaping profile in use.
+    pub const fn profile(&self) -> &'static Profile {
+        self.profile
+    }
+
+    /// Selects how many input bits each character carries.
+    ///
+    /// Defaults to [`ChunkWidth::Seven`]. [`ChunkWidth::Six`] gives
+    /// printable ASCII output at the cost of density.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use base122_rs::{ChunkWidth, Engine};
+    ///
+    /// const ASCII: Engine = Engine::new().with_chunk_width(ChunkWidth::Six);
+    ///
+    /// let encoded = ASCII.encode(b"\0binary\n");
+    /// assert_eq!(encoded, "AGJpbmFyeQo");
+    /// assert_eq!(ASCII.decode(&encoded).unwrap(), b"\0binary\n");
+    /// ```
+    pub const fn with_chunk_width(mut self, width: ChunkWidth) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// The chunk width in use.
+    pub const fn chunk_width(&self) -> ChunkWidth {
+        self.width
+    }
+
+    /// The set of characters this engine emits: the profile for 7-bit
+    /// chunks, or the [`Base64Alphabet`] for 6-bit chunks.
+    pub fn alphabet(&self) -> &'static dyn Alphabet {
+        match self.width {
+            ChunkWidth::Seven => self.profile,
+            ChunkWidth::Six => &Base64Alphabet,
+        }
+    }
+
+    /// Lists exactly which characters can appear in this engine's output.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use base122_rs::{Engine, Profile};
+    ///
+    /// let charset = Engine::new().with_profile(&Profile::DISPLAY).output_charset();
+    /// assert!(!charset.contains('\n'));
+    /// println!("allowed: {charset}");
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn output_charset(&self) -> CharsetReport {
+        CharsetReport::new(self)
+    }
+
+    /// Sets whether encoded messages end with a reserved terminator.
+    ///
+    /// The terminator is a character the profile never otherwise produces
+    /// (U+0680 for the standard profile, `=` for 6-bit chunks). With it, decoding detects truncated input,
+    /// and several messages can be concatenated and later split apart with
+    /// [`decode_next`](Engine::decode_next) without a length prefix.
+    /// Decoders without this option reject terminated messages.
+    pub const fn with_terminator(mut self, terminator: bool) -> Self {
+        self.terminator = terminator;
+        self
+    }
+
+    /// Whether encoded messages end with a terminator.
+    pub const fn terminator(&self) -> bool {
+        self.terminator
+    }
+
+    /// Sets whether encoded messages carry the length of their payload.
+    ///
+    /// The final character of an encoding can hold up to 6 padding bits,
+    /// so an input cut at a character boundary still decodes, just to fewer
+    /// bytes. With this option the payload length is appended to the data
+    /// before encoding, as a varint of one byte per 7 bits, and decoding
+    /// fails with [`DecodeError::LengthMismatch`](crate::DecodeError) when
+    /// the payload does not match it. Combine with
+    /// [`with_terminator`](Engine::with_terminator) to also detect
+    /// truncation of the trailer itself.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use base122_rs::Engine;
+    ///
+    /// const SIZED: Engine = Engine::new().with_length_trailer(true);
+    ///
+    /// let encoded = SIZED.encode(b"payload");
+    /// assert_eq!(SIZED.decode(&encoded).unwrap(), b"payload");
+    /// assert!(SIZED.decode(&encoded[..encoded.len() - 1]).is_err());
+    /// ```
+    pub const fn with_length_trailer(mut self, length_trailer: bool) -> Self {
+        self.length_trailer = length_trailer;
+        self
+    }
+
+    /// Whether encoded messages carry the length of their payload.
+    pub const fn length_trailer(&self) -> bool {
+        self.length_trailer
+    }
+
+    /// Exact length in bytes of [`encode`](Engine::encode) of `data`,
+    /// computed without encoding it.
+    ///
+    /// Scans `data` once, since the length of escapes depends on the
+    /// profile and on where dangerous chunks fall. Useful for sizing a
+    /// buffer exactly before encoding into it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use base122_rs::{Engine, Profile};
+    ///
+    /// let engine = Engine::new().with_profile(&Profile::DISPLAY);
+    /// let data = b"\0\x01\x02 control bytes";
+    /// assert_eq!(engine.encoded_len(data), engine.encode(data).len());
+    /// ```
+    pub fn encoded_len(&self, data: impl AsRef<[u8]>) -> usize {
+        let framed = self.frame(data.as_ref());
+        let body = match self.width {
+            ChunkWidth::Seven => {
+                let profile = self.profile;
+                let mut chunks = framed.chunks::<7>();
+                let mut len = 0;
+                while let Some(bits) = chunks.next() {
+                    len += match profile.escape_index(bits) {
+                        None => 1,
+                        Some(index) => match chunks.next() {
+                            Some(payload) => utf8_len(profile.escape(index, payload)),
+                            None => utf8_len(profile.shortened(bits)),
+                        },
+                    };
+                }
+                len
+            }
+            ChunkWidth::Six => (framed.len() * 8).div_ceil(6),
+        };
+        body + self.terminator_len()
+    }
+
+    /// Upper bound on the length in bytes of [`encode`](Engine::encode)
+    /// of any `input_len` bytes.
+    ///
+    /// Saturates at `usize::MAX` for lengths no encoding could reach.
+    pub fn max_encoded_len(&self, input_len: usize) -> usize {
+        let input_len = self.framed_len(input_len);
+        let body = match self.width {
+            ChunkWidth::Seven => self.profile.max_encoded_len(input_len),
+            // Four 6-bit chunks per 3 bytes
+            ChunkWidth::Six => input_len.saturating_add(input_len.div_ceil(3)),
+        };
+        body.saturating_add(self.terminator_len())
+    }
+
+    /// Upper bound on the length of [`decode`](Engine::decode) of
+    /// `encoded_len` bytes of text.
+    ///
+    /// Every encoded byte carries at most one chunk.
+    pub fn max_decoded_len(&self, encoded_len: usize) -> usize {
+        encoded_len * self.width.bits() as usize / 8
+    }
+
+    /// Predicts the size of this engine's output for data resembling
+    /// `sample`. See [`predict_overhead`](crate::predict_overhead).
+    pub fn predict_overhead(&self, sample: impl AsRef<[u8]>) -> Prediction {
+        crate::stats::predict(*self, sample.as_ref())
+    }
+
+    /// Encodes `data` with this engine's options.
+    #[cfg(feature = "alloc")]
+    pub fn encode(&self, data: impl AsRef<[u8]>) -> String {
+        let data = data.as_ref();
+        let mut result = Vec::with_capacity(self.max_encoded_len(data.len()));
+        match self.width {
+            // The common case, without the trailer's extra chunk iterator
+            ChunkWidth::Seven if !self.length_trailer => {
+                encode_profile_to_vec(self.profile, data, &mut result);
+            }
+            ChunkWidth::Seven => {
+                let chunks = self.frame(data);
+                encode_profile_chunks_to_vec(self.profile, chunks.chunks::<7>(), &mut result);
+            }
+            ChunkWidth::Six => {
+                encode_ascii_chunks_to_vec(self.frame(data).chunks::<6>(), &mut result);
+            }
+        }
+
+        let mut encoded = String::from_utf8(result).unwrap_or_else(|_| String::new());
+        if self.terminator {
+            encoded.push(self.terminator_char());
+        }
+        encoded
+    }
+
+    /// Decodes a single message encoded with this engine's options.
+    ///
+    /// # Errors
+    ///
+    /// Besides the errors reported by [`decode`](crate::decode), a
+    /// terminated engine reports [`DecodeError::MissingTerminator`] when
+    /// the input does not end with a terminator and
+    /// [`DecodeError::TrailingData`] when anything follows it. With a
+    /// length trailer, [`DecodeError::MissingLength`] and
+    /// [`DecodeError::LengthMismatch`] report a missing or contradicted
+    /// payload length.
+    #[cfg(feature = "alloc")]
+    pub fn decode(&self, encoded: impl AsRef<str>) -> Result<Vec<u8>, DecodeError> {
+        let encoded = encoded.as_ref();
+        if !self.terminator {
+            return self.decode_message(encoded);
+        }
+
+        let (decoded, rest) = self.decode_next(encoded)?;
+        if !rest.is_empty() {
+            return Err(DecodeError::TrailingData {
+                position: encoded.len() - rest.len(),
+            });
+        }
+        Ok(decoded)
+    }
+
+    /// Decodes a single message whose decoded length is known in advance.
+    ///
+    /// See [`decode_exact`](crate::decode_exact).
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`decode`](Engine::decode), and with
+    /// [`DecodeError::LengthMismatch`] if the message decodes to any other
+    /// number of bytes than `expected_len`.
+    #[cfg(feature = "alloc")]
+    pub fn decode_exact(
+        &self,
+        encoded: impl AsRef<str>,
+        expected_len: usize,
+    ) -> Result<Vec<u8>, DecodeError> {
+        let encoded = encoded.as_ref();
+        let decoded = self.decode(encoded)?;
+        if decoded.len() != expected_len {
+            return Err(DecodeError::LengthMismatch {
+                position: encoded.len(),
+                expected: expected_len,
+                actual: decoded.len(),
+            });
+        }
+        Ok(decoded)
+    }
+
+    /// Decodes the first message of `encoded` and returns it along with
+    /// the remaining input.
+    ///
+    /// Without the terminator option the whole input is one message and
+    /// the remainder is always empty. Error positions are relative to
+    /// `encoded`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use base122_rs::Engine;
+    ///
+    /// let engine = Engine::new().with_terminator(true);
+    /// let stream = engine.encode(b"first") + &engine.encode(b"second");
+    ///
+    /// let (first, rest) = engine.decode_next(&stream).unwrap();
+    /// let (second, rest) = engine.decode_next(rest).unwrap();
+    /// assert_eq!((&first[..], &second[..], rest), (&b"first"[..], &b"second"[..], ""));
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn decode_next<'a>(&self, encoded: &'a str) -> Result<(Vec<u8>, &'a str), DecodeError> {
+        let (message, rest) = if self.terminator {
+            let terminator = self.terminator_char();
+            let end = encoded
+                .find(terminator)
+                .ok_or(DecodeError::MissingTerminator {
+                    position: encoded.len(),
+                })?;
+            (&encoded[..end], &encoded[end + terminator.len_utf8()..])
+        } else {
+            (encoded, "")
+        };
+
+        Ok((self.decode_message(message)?, rest))
+    }
+
+    /// Decodes `message`, which contains no terminator.
+    #[cfg(feature = "alloc")]
+    fn decode_message(&self, message: &str) -> Result<Vec<u8>, DecodeError> {
+        let mut decoded;
+        match self.width {
+            ChunkWidth::Seven => {
+                decoded = Vec::with_capacity(decoded_len(self.profile, message));
+                decode_profile_str_to_vec(self.profile, message, &mut decoded)?;
+            }
+            ChunkWidth::Six => {
+                decoded = Vec::with_capacity(self.max_decoded_len(message.len()));
+                decode_ascii_chars_to_vec(message.char_indices(), &mut decoded)?;
+            }
+        }
+        if self.length_trailer {
+            let (expected, actual) = read_trailer(&decoded).ok_or(DecodeError::MissingLength {
+                position: message.len(),
+            })?;
+            if expected != actual {
+                return Err(DecodeError::LengthMismatch {
+                    position: message.len(),
+                    expected,
+                    actual,
+                });
+            }
+            decoded.truncate(actual);
+        }
+        Ok(decoded)
+    }
+
+    /// Splits `data` for encoding together with its length trailer, if
+    /// enabled.
+    fn frame<'a>(&self, data: &'a [u8]) -> Framed<'a> {
+        if !self.length_trailer {
+            return Framed::new(data, &[]);
+        }
+        let mut trailer = [0; MAX_TRAILER_LEN];
+        let size = write_trailer(data.len(), &mut trailer);
+        // Whole numbers of chunks on either side of the split
+        let split = data.len() - data.len() % self.width.bits() as usize;
+        let mut framed = Framed::new(&data[..split], &data[split..]);
+        framed.push(&trailer[..size]);
+        framed
+    }
+
+    /// Length of `input_len` bytes of payload with its length trailer, if
+    /// enabled.
+    pub(crate) fn framed_len(&self, input_len: usize) -> usize {
+        if self.length_trailer {
+            input_len.saturating_add(trailer_len(input_len))
+        } else {
+            input_len
+        }
+    }
+
+    /// The character that ends a terminated message.
+    fn terminator_char(&self) -> char {
+        self.alphabet().terminator()
+    }
+
+    /// Bytes the terminator adds to each message.
+    pub(crate) fn terminator_len(&self) -> usize {
+        if self.terminator {
+            self.terminator_char().len_utf8()
+        } else {
+            0
+        }
+    }
+}
+
+/// Longest length trailer, that of a 64-bit length.
+const MAX_TRAILER_LEN: usize = 10;
+
+/// Input to encode, split into a borrowed head and a short owned tail so
+/// that a length trailer can follow the input without copying it.
+///
+/// The head must be a whole number of chunks long, so that the chunks of
+/// the head followed by those of the tail are the chunks of the whole.
+struct Framed<'a> {
+    head: &'a [u8],
+    tail: [u8; 6 + MAX_TRAILER_LEN],
+    tail_len: usize,
+}
+
+impl<'a> Framed<'a> {
+    /// Creates a frame of `head` followed by a `tail` of at most 6 bytes.
+    fn new(head: &'a [u8], tail: &[u8]) -> Self {
+        let mut framed = Framed {
+            head,
+            tail: [0; 6 + MAX_TRAILER_LEN],
+            tail_len: 0,
+        };
+        framed.push(tail);
+        framed
+    }
+
+    /// Appends `bytes` to the tail.
+    fn push(&mut self, bytes: &[u8]) {
+        self.tail[self.tail_len..self.tail_len + bytes.len()].copy_from_slice(bytes);
+        self.tail_len += bytes.len();
+    }
+
+    fn len(&self) -> usize {
+        self.head.len() + self.tail_len
+    }
+
+    fn chunks<const BITS: u32>(&self) -> impl Iterator<Item = u8> + '_ {
+        BitChunks::<BITS>::new(self.head).chain(BitChunks::<BITS>::new(&self.tail[..self.tail_len]))
+    }
+}
+
+/// Size of the length trailer for a payload of `len` bytes.
+fn trailer_len(len: usize) -> usize {
+    (usize::BITS - len.leading_zeros()).div_ceil(7).max(1) as usize
+}
+
+/// Writes the length trailer for a payload of `len` bytes to the start of
+/// `out` and returns its size.
+///
+/// The trailer is read backwards from the end of the decoded bytes: the
+/// last byte holds the lowest 7 bits of the length, and a set high bit
+/// means more significant bits precede it.
+fn write_trailer(len: usize, out: &mut [u8]) -> usize {
+    let size = trailer_len(len);
+    let mut rest = len as u64;
+    for (i, byte) in out[..size].iter_mut().rev().enumerate() {
+        let more = if i + 1 < size { 0x80 } else { 0 };
+        *byte = rest as u8 & 0x7F | more;
+        rest >>= 7;
+    }
+    size
+}
+
+/// Reads the length trailer at the end of `decoded` and returns the length
+/// it records and the length of the payload before it.
+#[cfg(feature = "alloc")]
+fn read_trailer(decoded: &[u8]) -> Option<(usize, usize)> {
+    let mut len = 0u64;
+    for (i, &byte) in decoded.iter().rev().take(MAX_TRAILER_LEN).enumerate() {
+        len |= u64::from(byte & 0x7F) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((usize::try_from(len).ok()?, decoded.len() - i - 1));
+        }
+    }
+    None
+}
+
+/// Length in bytes of the UTF-8 encoding of code point `c`.
+fn utf8_len(c: u32) -> usize {
+    match c {
+        0..=0x7F => 1,
+        0x80..=0x7FF => 2,
+        0x800..=0xFFFF => 3,
+        _ => 4,
+    }
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Engine::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode, encode};
+
+    #[test]
+    fn test_default_engine_matches_free_functions() {
+        let data: Vec<u8> = (0..300).map(|i| (i * 13 % 256) as u8).collect();
+        let engine = Engine::new();
+        assert_eq!(Engine::STANDARD, engine);
+        assert_eq!(Engine::default(), engine);
+        assert_eq!(engine.encode(&data), encode(&data));
+        assert_eq!(engine.decode(encode(&data)).unwrap(), data);
+    }
+
+    #[test]
+    fn test_terminated_roundtrip() {
+        let engine = Engine::new().with_terminator(true);
+        for data in [&b""[..], b"x", &[0; 8], b"\n\r\"&\\"] {
+            let encoded = engine.encode(data);
+            assert!(encoded.ends_with('\u{0680}'));
+            assert_eq!(engine.decode(&encoded).unwrap(), data);
+            assert!(decode(&encoded).is_err());
+        }
+    }
+
+    #[test]
+    fn test_terminated_errors() {
+        let engine = Engine::new().with_terminator(true);
+        let encoded = engine.encode(b"payload");
+
+        assert_eq!(
+            engine.decode("abc"),
+            Err(DecodeError::MissingTerminator { position: 3 })
+        );
+        assert_eq!(
+            engine.decode(format!("{encoded}x")),
+            Err(DecodeError::TrailingData {
+                position: encoded.len()
+            })
+        );
+    }
+
+    #[test]
+    fn test_terminator_follows_profile() {
+        let engine = Engine::new()
+            .with_profile(&Profile::FILENAME)
+            .with_terminator(true);
+        let encoded = engine.encode([0; 50]);
+        assert!(encoded.ends_with(Profile::FILENAME.terminator()));
+        assert_eq!(engine.decode(&encoded).unwrap(), [0; 50]);
+    }
+
+    #[test]
+    fn test_six_bit_width() {
+        let engine = Engine::new().with_chunk_width(ChunkWidth::Six);
+        // Unpadded Base64 test vectors from RFC 4648
+        for (data, expected) in [("", ""), ("f", "Zg"), ("foo", "Zm9v"), ("fooba", "Zm9vYmE")] {
+            assert_eq!(engine.encode(data), expected);
+            assert_eq!(engine.decode(expected).unwrap(), data.as_bytes());
+        }
+
+        let data: Vec<u8> = (0..=255).collect();
+        let encoded = engine.encode(&data);
+        assert!(encoded
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"+/".contains(&b)));
+        assert_eq!(engine.decode(&encoded).unwrap(), data);
+
+        let terminated = engine.with_terminator(true).encode(&data);
+        assert_eq!(terminated, encoded + "=");
+        assert_eq!(
+            engine.decode("Zm9v\n"),
+            Err(DecodeError::InvalidCharacter {
+                position: 4,
+                character: '\n'
+            })
+        );
+    }
+
+    #[test]
+    fn test_lengths_follow_engine() {
+        let profiles = [
+            &Profile::STANDARD,
+            &Profile::FILENAME,
+            &Profile::DISPLAY,
+            &Profile::BIDI_SAFE,
+            &Profile::NORMALIZATION_STABLE,
+            &Profile::XML,
+        ];
+        let data: Vec<u8> = (0..300).map(|i| (i * 37 % 256) as u8).collect();
+        for profile in profiles {
+            for width in [ChunkWidth::Seven, ChunkWidth::Six] {
+                for terminator in [false, true] {
+                    let engine = Engine::new()
+                        .with_profile(profile)
+                        .with_chunk_width(width)
+                        .with_terminator(terminator);
+                    for len in [0, 1, 6, 7, 8, 100, 300] {
+                        for input in [&data[..len], &[0; 300][..len]] {
+                            let encoded = engine.encode(input);
+                            assert_eq!(engine.encoded_len(input), encoded.len(), "{engine:?}");
+                            assert!(encoded.len() <= engine.max_encoded_len(len));
+                            assert!(len <= engine.max_decoded_len(encoded.len()));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_max_encoded_len_saturates() {
+        for profile in [&Profile::STANDARD, &Profile::NORMALIZATION_STABLE] {
+            for width in [ChunkWidth::Seven, ChunkWidth::Six] {
+                for trailer in [false, true] {
+                    let engine = Engine::new()
+                        .with_profile(profile)
+                        .with_chunk_width(width)
+                        .with_length_trailer(trailer)
+                        .with_terminator(true);
+                    assert_eq!(engine.max_encoded_len(usize::MAX), usize::MAX);
+                    let len = usize::MAX / 8 * 7 + 7;
+                    assert_eq!(engine.max_encoded_len(len), usize::MAX);
+                }
+            }
+        }
+        assert_eq!(crate::max_encoded_len(usize::MAX), usize::MAX);
+        assert_eq!(crate::max_encoded_len(7), 9);
+    }
+
+    #[test]
+    fn test_decode_exact() {
+        let engine = Engine::new()
+            .with_chunk_width(ChunkWidth::Six)
+            .with_terminator(true);
+        assert_eq!(engine.decode_exact("Zm9v=", 3).unwrap(), b"foo");
+        assert_eq!(
+            engine.decode_exact("Zm9=", 3),
+            Err(DecodeError::LengthMismatch {
+                position: 4,
+                expected: 3,
+                actual: 2
+            })
+        );
+        assert!(engine.decode_exact("Zm9v", 3).is_err());
+    }
+
+    #[test]
+    fn test_length_trailer() {
+        let data: Vec<u8> = (0..300).map(|i| (i * 37 % 256) as u8).collect();
+        for width in [ChunkWidth::Seven, ChunkWidth::Six] {
+            let engine = Engine::new()
+                .with_chunk_width(width)
+                .with_length_trailer(true);
+            for len in [0, 1, 6, 7, 127, 128, 300] {
+                let encoded = engine.encode(&data[..len]);
+                assert_eq!(engine.decode(&encoded).unwrap(), &data[..len]);
+                assert_eq!(engine.encoded_len(&data[..len]), encoded.len());
+                assert!(encoded.len() <= engine.max_encoded_len(len));
+            }
+
+            // Dropping the last character leaves a shorter, valid encoding
+            let encoded = engine.encode(&data);
+            let cut = encoded.char_indices().last().map_or(0, |(i, _)| i);
+            assert!(Engine::new()
+                .with_chunk_width(width)
+                .decode(&encoded[..cut])
+                .is_ok());
+            assert!(matches!(
+                engine.decode(&encoded[..cut]),
+                Err(DecodeError::LengthMismatch { .. } | DecodeError::MissingLength { .. })
+            ));
+        }
+
+        let mut trailer = [0; MAX_TRAILER_LEN];
+        for len in [0, 127, 128, 300, usize::MAX] {
+            let size = write_trailer(len, &mut trailer);
+            assert_eq!(size, trailer_len(len));
+            assert_eq!(read_trailer(&trailer[..size]), Some((len, 0)));
+        }
+        assert_eq!(
+            Engine::new().with_length_trailer(true).decode(""),
+            Err(DecodeError::MissingLength { position: 0 })
+        );
+    }
+
+    #[test]
+    fn test_decode_next_splits_messages() {
+        let engine = Engine::new().with_terminator(true);
+        let messages: [&[u8]; 4] = [b"one", b"", &[0, 0, 0], b"four"];
+        let stream: String = messages.iter().map(|m| engine.encode(m)).collect();
+
+        let mut rest = stream.as_str();
+        for message in messages {
+            let (decoded, next) = engine.decode_next(rest).unwrap();
+            assert_eq!(decoded, message);
+            rest = next;
+        }
+        assert!(rest.is_empty());
+    }
+}
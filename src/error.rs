@@ -0,0 +1,163 @@
+//! Error types.
+
+use core::fmt;
+
+/// Error returned when decoding invalid Base122 input.
+///
+/// Every variant records the byte offset of the offending data in the
+/// encoded input, so callers can point at or slice out the bad data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum DecodeError {
+    /// A character outside the range the encoder ever produces (above U+07FF).
+    InvalidCharacter {
+        /// Byte offset of the character in the encoded input.
+        position: usize,
+        /// The offending character.
+        character: char,
+    },
+    /// A 2-byte character that is not a valid dangerous-character escape.
+    InvalidEscape {
+        /// Byte offset of the character in the encoded input.
+        position: usize,
+        /// The offending character.
+        character: char,
+    },
+    /// A byte that cannot start or continue a Base122 character.
+    ///
+    /// Only reported when decoding raw bytes, e.g. from a stream, where the
+    /// input is not known to be valid UTF-8.
+    InvalidByte {
+        /// Byte offset in the encoded input.
+        position: usize,
+        /// The offending byte.
+        byte: u8,
+    },
+    /// The input ended in the middle of a 2-byte character.
+    UnexpectedEnd {
+        /// Byte offset at which the input ended.
+        position: usize,
+    },
+    /// The input ended without the terminator required by an
+    /// [`Engine`](crate::Engine) configured with one.
+    MissingTerminator {
+        /// Byte offset at which the input ended.
+        position: usize,
+    },
+    /// Data follows the terminator of a single terminated message.
+    TrailingData {
+        /// Byte offset of the first byte after the terminator.
+        position: usize,
+    },
+    /// The caller-provided output buffer filled up before decoding
+    /// finished.
+    OutputTooSmall {
+        /// Byte offset of the character whose bits did not fit.
+        position: usize,
+        /// Length in bytes the complete output needs.
+        required: usize,
+    },
+    /// The input ended without the length trailer required by an
+    /// [`Engine`](crate::Engine) configured with one.
+    MissingLength {
+        /// Byte offset at which the input ended.
+        position: usize,
+    },
+    /// The input decoded to a different number of bytes than expected,
+    /// e.g. because it was truncated.
+    LengthMismatch {
+        /// Byte offset at which the input ended.
+        position: usize,
+        /// Number of bytes expected.
+        expected: usize,
+        /// Number of bytes the input decoded to.
+        actual: usize,
+    },
+}
+
+impl DecodeError {
+    /// Byte offset in the encoded input at which decoding failed.
+    pub fn position(&self) -> usize {
+        match *self {
+            DecodeError::InvalidCharacter { position, .. }
+            | DecodeError::InvalidEscape { position, .. }
+            | DecodeError::InvalidByte { position, .. }
+            | DecodeError::UnexpectedEnd { position }
+            | DecodeError::MissingTerminator { position }
+            | DecodeError::TrailingData { position }
+            | DecodeError::OutputTooSmall { position, .. }
+            | DecodeError::MissingLength { position }
+            | DecodeError::LengthMismatch { position, .. } => position,
+        }
+    }
+
+    /// The same error with its position moved `offset` bytes later, for
+    /// input that was decoded after stripping a header.
+    pub(crate) fn offset_by(mut self, offset: usize) -> Self {
+        match &mut self {
+            DecodeError::InvalidCharacter { position, .. }
+            | DecodeError::InvalidEscape { position, .. }
+            | DecodeError::InvalidByte { position, .. }
+            | DecodeError::UnexpectedEnd { position }
+            | DecodeError::MissingTerminator { position }
+            | DecodeError::TrailingData { position }
+            | DecodeError::OutputTooSmall { position, .. }
+            | DecodeError::MissingLength { position }
+            | DecodeError::LengthMismatch { position, .. } => *position += offset,
+        }
+        self
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            DecodeError::InvalidCharacter {
+                position,
+                character,
+            } => write!(
+                f,
+                "invalid character {character:?} (U+{:04X}) at byte {position}",
+                character as u32
+            ),
+            DecodeError::InvalidEscape {
+                position,
+                character,
+            } => write!(
+                f,
+                "invalid escape sequence {character:?} (U+{:04X}) at byte {position}",
+                character as u32
+            ),
+            DecodeError::InvalidByte { position, byte } => {
+                write!(f, "invalid byte 0x{byte:02X} at byte {position}")
+            }
+            DecodeError::UnexpectedEnd { position } => {
+                write!(f, "input ends inside a 2-byte character at byte {position}")
+            }
+            DecodeError::MissingTerminator { position } => {
+                write!(f, "input ends without a terminator at byte {position}")
+            }
+            DecodeError::TrailingData { position } => {
+                write!(f, "unexpected data after terminator at byte {position}")
+            }
+            DecodeError::OutputTooSmall { position, required } => write!(
+                f,
+                "output buffer is full at byte {position}, {required} bytes needed"
+            ),
+            DecodeError::MissingLength { position } => {
+                write!(f, "input ends without a length trailer at byte {position}")
+            }
+            DecodeError::LengthMismatch {
+                position,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "decoded {actual} bytes instead of {expected} at byte {position}"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
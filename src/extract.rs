@@ -0,0 +1,175 @@
+//! Finding Base122 data URIs in HTML, CSS, and SVG documents.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::ops::Range;
+
+#[cfg(feature = "alloc")]
+use crate::{detect_engine, DecodeError};
+
+/// Returns an iterator over the `;base122` data URIs in `document`.
+///
+/// This is the reverse of embedding assets with [`DataUri`](crate::DataUri)
+/// or [`embed_in_svg_attribute`](crate::embed_in_svg_attribute): each URI
+/// is found lazily as the iterator advances, and decoded only when asked.
+/// A URI inside single or double quotes ends at the closing quote; an
+/// unquoted one, as in CSS `url(data:...)`, ends at the first whitespace,
+/// `)`, or `>`. Encoded output may contain all three, so embedded URIs
+/// should be quoted: standard output never contains `"`, and output of
+/// [`Profile::XML`] contains neither quote.
+///
+/// [`Profile::XML`]: crate::Profile::XML
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{extract_data_uris, DataUri};
+///
+/// let html = format!(
+///     r#"<img src="{}"><link rel="icon" href="{}">"#,
+///     DataUri::png(b"\x89PNG"),
+///     DataUri::svg(b"<svg/>"),
+/// );
+///
+/// let uris: Vec<_> = extract_data_uris(&html).collect();
+/// assert_eq!(uris.len(), 2);
+/// assert_eq!(uris[0].media_type, "image/png");
+/// assert_eq!(uris[1].decode().unwrap(), b"<svg/>");
+/// ```
+pub fn extract_data_uris(document: &str) -> DataUris<'_> {
+    DataUris {
+        document,
+        position: 0,
+    }
+}
+
+/// A data URI found by [`extract_data_uris`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddedUri<'a> {
+    /// Byte range of the whole URI, from `data:` on, in the document.
+    pub span: Range<usize>,
+    /// The media type and its parameters, without `;base122`; empty if the
+    /// URI does not give one.
+    pub media_type: &'a str,
+    /// The encoded payload.
+    pub payload: &'a str,
+}
+
+impl EmbeddedUri<'_> {
+    /// Decodes the payload with the engine chosen by
+    /// [`detect_engine`].
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`decode_auto`](crate::decode_auto), with error positions
+    /// as byte offsets in the document.
+    #[cfg(feature = "alloc")]
+    pub fn decode(&self) -> Result<Vec<u8>, DecodeError> {
+        let offset = self.span.end - self.payload.len();
+        detect_engine(self.payload)
+            .decode(self.payload)
+            .map_err(|error| error.offset_by(offset))
+    }
+}
+
+/// Iterator returned by [`extract_data_uris`].
+#[derive(Debug, Clone)]
+pub struct DataUris<'a> {
+    document: &'a str,
+    /// Byte offset where the search for the next URI starts.
+    position: usize,
+}
+
+impl<'a> Iterator for DataUris<'a> {
+    type Item = EmbeddedUri<'a>;
+
+    fn next(&mut self) -> Option<EmbeddedUri<'a>> {
+        let document = self.document;
+        while let Some(found) = document[self.position..].find(['d', 'D']) {
+            let start = self.position + found;
+            // Past this match whatever happens; `d` is a single byte
+            self.position = start + 1;
+            let Some(uri) = document[start..]
+                .get(..5)
+                .filter(|scheme| scheme.eq_ignore_ascii_case("data:"))
+                .and_then(|_| parse_uri(document, start))
+            else {
+                continue;
+            };
+            self.position = uri.span.end;
+            return Some(uri);
+        }
+        self.position = document.len();
+        None
+    }
+}
+
+/// Parses the `;base122` data URI starting at `start`, which is known to
+/// begin with `data:`.
+fn parse_uri(document: &str, start: usize) -> Option<EmbeddedUri<'_>> {
+    let header_start = start + 5;
+    let header_len = document[header_start..]
+        .find(|c: char| c == ',' || c.is_whitespace() || "\"'()<>".contains(c))?;
+    let header = &document[header_start..header_start + header_len];
+    let media_type = match header.rsplit_once(';') {
+        Some((media_type, encoding)) if encoding.eq_ignore_ascii_case("base122") => media_type,
+        None if header.eq_ignore_ascii_case("base122") => "",
+        _ => return None,
+    };
+    if !document[header_start + header_len..].starts_with(',') {
+        return None;
+    }
+
+    let payload_start = header_start + header_len + 1;
+    let rest = &document[payload_start..];
+    let payload_len = match document[..start].chars().next_back() {
+        Some(quote @ ('"' | '\'')) => rest.find(quote),
+        _ => rest.find(|c: char| c.is_whitespace() || c == ')' || c == '>'),
+    }
+    .unwrap_or(rest.len());
+    Some(EmbeddedUri {
+        span: start..payload_start + payload_len,
+        media_type,
+        payload: &rest[..payload_len],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{embed_in_svg_attribute, DataUri};
+
+    #[test]
+    fn test_extract_from_html_and_css() {
+        let data: Vec<u8> = (0..=255).collect();
+        let html = format!(
+            "<p>data: not a uri, data:text/plain;base64,aGk=</p>\n\
+             <img src=\"{}\">\n\
+             <style>a {{ background: url(DATA:;BASE122,{}) }}</style>\n\
+             <svg><image href='{}'/></svg>",
+            DataUri::png(data.clone()),
+            crate::encode(b"\0\0\0"),
+            embed_in_svg_attribute(&DataUri::woff2(data.clone())),
+        );
+
+        let uris: Vec<_> = extract_data_uris(&html).collect();
+        let types: Vec<_> = uris.iter().map(|uri| uri.media_type).collect();
+        assert_eq!(types, ["image/png", "", "font/woff2"]);
+        for uri in &uris {
+            assert!(html[uri.span.clone()].ends_with(uri.payload));
+        }
+        assert_eq!(uris[0].decode().unwrap(), data);
+        assert_eq!(uris[1].decode().unwrap(), b"\0\0\0");
+        assert_eq!(uris[2].decode().unwrap(), data);
+    }
+
+    #[test]
+    fn test_decode_error_positions() {
+        let html = "<img src=\"data:image/png;base122,ab\u{20AC}\">";
+        let uri = extract_data_uris(html).next().unwrap();
+        assert_eq!(uri.span, 10..38);
+        let error = uri.decode().unwrap_err();
+        assert_eq!(error.position(), 35);
+        assert_eq!(&html[35..38], "\u{20AC}");
+    }
+}
@@ -0,0 +1,395 @@
+//! C API for streaming encoding and decoding.
+//!
+//! An encoder or decoder context is created with `*_new`, fed input of any
+//! size with `*_feed`, completed with `*_finish`, and released with
+//! `*_free`. Every call writes into a buffer provided by the caller, so a C
+//! program can process payloads of any size in constant memory. A finished
+//! context is reset and can be fed a new, unrelated input.
+//!
+//! Build a shared or static library with
+//! `cargo rustc --release --features ffi --lib --crate-type cdylib` (or
+//! `staticlib`) and include `include/base122.h`.
+//!
+//! ```c
+//! Base122Encoder *encoder = base122_encoder_new();
+//! uint8_t out[base122_max_encoded_len(sizeof chunk)];
+//! size_t written;
+//! while ((n = fread(chunk, 1, sizeof chunk, in)) > 0) {
+//!     base122_encoder_feed(encoder, chunk, n, out, sizeof out, &written);
+//!     fwrite(out, 1, written, stdout);
+//! }
+//! base122_encoder_finish(encoder, out, sizeof out, &written);
+//! fwrite(out, 1, written, stdout);
+//! base122_encoder_free(encoder);
+//! ```
+//!
+//! Functions return [`BASE122_OK`] or one of the other `BASE122_*` status
+//! codes, and never unwind into the caller.
+
+// Every function here takes raw pointers from C
+#![allow(unsafe_code)]
+
+use alloc::boxed::Box;
+use core::ffi::c_int;
+use core::slice;
+
+use crate::stream::{DecodeState, EncodeState};
+
+/// The call succeeded.
+pub const BASE122_OK: c_int = 0;
+/// A required pointer was null.
+pub const BASE122_NULL_POINTER: c_int = 1;
+/// The output buffer is smaller than the call's documented bound. Nothing
+/// was consumed or written.
+pub const BASE122_OUTPUT_TOO_SMALL: c_int = 2;
+/// The input is not valid Base122. The context must be finished or freed
+/// before further use.
+pub const BASE122_INVALID_INPUT: c_int = 3;
+
+/// Output bytes [`base122_encoder_finish`] may write.
+const FINISH_MAX: usize = 2;
+
+/// Streaming encoder context.
+#[derive(Debug, Default)]
+pub struct Base122Encoder {
+    state: EncodeState,
+}
+
+/// Streaming decoder context.
+#[derive(Debug, Default)]
+pub struct Base122Decoder {
+    state: DecodeState,
+}
+
+/// Output buffer size that is always enough for one
+/// [`base122_encoder_feed`] call with `len` input bytes, and for
+/// [`base122_encoder_finish`].
+///
+/// Saturates at `SIZE_MAX` when the size does not fit in a `size_t`; no
+/// buffer can be that large, so such input must be fed in smaller pieces.
+#[no_mangle]
+pub extern "C" fn base122_max_encoded_len(len: usize) -> usize {
+    // Up to 8 bits per input byte go out as 7-bit chunks, plus the chunk
+    // carried over from the previous call completing an escape. `len * 8`
+    // could overflow, so the chunks are counted as `len + len / 7`.
+    len.saturating_add(len.div_ceil(7))
+        .saturating_add(FINISH_MAX)
+}
+
+/// Creates an encoder. Free it with [`base122_encoder_free`].
+#[no_mangle]
+pub extern "C" fn base122_encoder_new() -> *mut Base122Encoder {
+    Box::into_raw(Box::default())
+}
+
+/// Encodes `len` bytes at `data`, writing the encoded bytes to `out` and
+/// their count to `out_len`.
+///
+/// `out_cap` must be at least [`base122_max_encoded_len`]`(len)`. Output
+/// from all calls, concatenated, is UTF-8 text identical to encoding the
+/// whole input at once; a call's output may end partway through a
+/// character.
+///
+/// # Safety
+///
+/// `encoder` must come from [`base122_encoder_new`] and not be freed.
+/// `data` must be readable for `len` bytes, `out` writable for `out_cap`
+/// bytes, and `out_len` writable. `data` may be null if `len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn base122_encoder_feed(
+    encoder: *mut Base122Encoder,
+    data: *const u8,
+    len: usize,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> c_int {
+    let (Some(encoder), Some(out_len)) = (encoder.as_mut(), out_len.as_mut()) else {
+        return BASE122_NULL_POINTER;
+    };
+    let Some(data) = input(data, len) else {
+        return BASE122_NULL_POINTER;
+    };
+    if out.is_null() {
+        return BASE122_NULL_POINTER;
+    }
+    if out_cap < base122_max_encoded_len(len) {
+        return BASE122_OUTPUT_TOO_SMALL;
+    }
+
+    let out = slice::from_raw_parts_mut(out, out_cap);
+    let mut written = 0;
+    for &byte in data {
+        encoder.state.push(byte, &mut |encoded| {
+            out[written] = encoded;
+            written += 1;
+        });
+    }
+    *out_len = written;
+    BASE122_OK
+}
+
+/// Encodes the end of the input, writing the final bytes (at most 2) to
+/// `out` and their count to `out_len`, and resets the encoder.
+///
+/// # Safety
+///
+/// As for [`base122_encoder_feed`].
+#[no_mangle]
+pub unsafe extern "C" fn base122_encoder_finish(
+    encoder: *mut Base122Encoder,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> c_int {
+    let (Some(encoder), Some(out_len)) = (encoder.as_mut(), out_len.as_mut()) else {
+        return BASE122_NULL_POINTER;
+    };
+    if out.is_null() {
+        return BASE122_NULL_POINTER;
+    }
+    if out_cap < FINISH_MAX {
+        return BASE122_OUTPUT_TOO_SMALL;
+    }
+
+    let out = slice::from_raw_parts_mut(out, out_cap);
+    let mut written = 0;
+    encoder.state.finish(&mut |encoded| {
+        out[written] = encoded;
+        written += 1;
+    });
+    *out_len = written;
+    BASE122_OK
+}
+
+/// Frees an encoder. Does nothing if `encoder` is null.
+///
+/// # Safety
+///
+/// `encoder` must be null or come from [`base122_encoder_new`] and not
+/// already be freed.
+#[no_mangle]
+pub unsafe extern "C" fn base122_encoder_free(encoder: *mut Base122Encoder) {
+    if !encoder.is_null() {
+        drop(Box::from_raw(encoder));
+    }
+}
+
+/// Creates a decoder. Free it with [`base122_decoder_free`].
+#[no_mangle]
+pub extern "C" fn base122_decoder_new() -> *mut Base122Decoder {
+    Box::into_raw(Box::default())
+}
+
+/// Decodes `len` bytes of encoded text at `data`, writing the decoded
+/// bytes to `out` and their count to `out_len`.
+///
+/// The text may be split between calls anywhere, even inside a character.
+/// `out_cap` must be at least `len + 1`. On [`BASE122_INVALID_INPUT`],
+/// `out_len` still counts the bytes decoded before the error.
+///
+/// # Safety
+///
+/// `decoder` must come from [`base122_decoder_new`] and not be freed.
+/// `data` must be readable for `len` bytes, `out` writable for `out_cap`
+/// bytes, and `out_len` writable. `data` may be null if `len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn base122_decoder_feed(
+    decoder: *mut Base122Decoder,
+    data: *const u8,
+    len: usize,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> c_int {
+    let (Some(decoder), Some(out_len)) = (decoder.as_mut(), out_len.as_mut()) else {
+        return BASE122_NULL_POINTER;
+    };
+    let Some(data) = input(data, len) else {
+        return BASE122_NULL_POINTER;
+    };
+    if out.is_null() {
+        return BASE122_NULL_POINTER;
+    }
+    // Each encoded byte carries at most 7 bits, plus up to 14 left over
+    // from a previous call that ended inside an escape
+    if out_cap < len + 1 {
+        return BASE122_OUTPUT_TOO_SMALL;
+    }
+
+    let out = slice::from_raw_parts_mut(out, out_cap);
+    let mut written = 0;
+    let result = decoder.state.push_slice(data, &mut |decoded| {
+        out[written] = decoded;
+        written += 1;
+    });
+    *out_len = written;
+    match result {
+        Ok(()) => BASE122_OK,
+        Err(_) => BASE122_INVALID_INPUT,
+    }
+}
+
+/// Checks that the input did not end inside a character, and resets the
+/// decoder.
+///
+/// # Safety
+///
+/// `decoder` must come from [`base122_decoder_new`] and not be freed.
+#[no_mangle]
+pub unsafe extern "C" fn base122_decoder_finish(decoder: *mut Base122Decoder) -> c_int {
+    let Some(decoder) = decoder.as_mut() else {
+        return BASE122_NULL_POINTER;
+    };
+    match decoder.state.finish() {
+        Ok(()) => BASE122_OK,
+        Err(_) => BASE122_INVALID_INPUT,
+    }
+}
+
+/// Frees a decoder. Does nothing if `decoder` is null.
+///
+/// # Safety
+///
+/// `decoder` must be null or come from [`base122_decoder_new`] and not
+/// already be freed.
+#[no_mangle]
+pub unsafe extern "C" fn base122_decoder_free(decoder: *mut Base122Decoder) {
+    if !decoder.is_null() {
+        drop(Box::from_raw(decoder));
+    }
+}
+
+/// The input slice, or `None` if `data` is null with a nonzero length.
+unsafe fn input<'a>(data: *const u8, len: usize) -> Option<&'a [u8]> {
+    match (data.is_null(), len) {
+        (_, 0) => Some(&[]),
+        (true, _) => None,
+        (false, _) => Some(slice::from_raw_parts(data, len)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::{generate, Profile};
+    use crate::{decode, encode};
+    use core::ptr;
+
+    #[test]
+    fn test_ffi_streaming_round_trip() {
+        let data = generate(Profile::DangerousDensity(0.3), 1000, 8);
+        let encoder = base122_encoder_new();
+        let decoder = base122_decoder_new();
+        let mut out = [0u8; 64];
+        let mut out_len = 0;
+
+        let mut encoded = Vec::new();
+        for piece in data.chunks(13) {
+            let status = unsafe {
+                base122_encoder_feed(
+                    encoder,
+                    piece.as_ptr(),
+                    piece.len(),
+                    out.as_mut_ptr(),
+                    out.len(),
+                    &mut out_len,
+                )
+            };
+            assert_eq!(status, BASE122_OK);
+            encoded.extend_from_slice(&out[..out_len]);
+        }
+        let status =
+            unsafe { base122_encoder_finish(encoder, out.as_mut_ptr(), out.len(), &mut out_len) };
+        assert_eq!(status, BASE122_OK);
+        encoded.extend_from_slice(&out[..out_len]);
+        assert_eq!(encoded, encode(&data).into_bytes());
+
+        // Split inside escapes too
+        let mut decoded = Vec::new();
+        for piece in encoded.chunks(7) {
+            let status = unsafe {
+                base122_decoder_feed(
+                    decoder,
+                    piece.as_ptr(),
+                    piece.len(),
+                    out.as_mut_ptr(),
+                    out.len(),
+                    &mut out_len,
+                )
+            };
+            assert_eq!(status, BASE122_OK);
+            decoded.extend_from_slice(&out[..out_len]);
+        }
+        assert_eq!(unsafe { base122_decoder_finish(decoder) }, BASE122_OK);
+        assert_eq!(decoded, data);
+
+        unsafe {
+            base122_encoder_free(encoder);
+            base122_decoder_free(decoder);
+        }
+    }
+
+    #[test]
+    fn test_max_encoded_len_saturates() {
+        for len in [0, 1, 6, 7, 8, 1000] {
+            assert_eq!(base122_max_encoded_len(len), (len * 8).div_ceil(7) + 2);
+        }
+        assert_eq!(base122_max_encoded_len(usize::MAX), usize::MAX);
+        assert_eq!(base122_max_encoded_len(usize::MAX / 8 * 7 + 7), usize::MAX);
+    }
+
+    #[test]
+    fn test_ffi_errors() {
+        let decoder = base122_decoder_new();
+        let mut out = [0u8; 4];
+        let mut out_len = 0;
+        let feed = |input: &[u8], out: &mut [u8], out_len: &mut usize| unsafe {
+            base122_decoder_feed(
+                decoder,
+                input.as_ptr(),
+                input.len(),
+                out.as_mut_ptr(),
+                out.len(),
+                out_len,
+            )
+        };
+
+        assert_eq!(
+            feed(b"too long", &mut out, &mut out_len),
+            BASE122_OUTPUT_TOO_SMALL
+        );
+        assert_eq!(
+            feed(b"ab\xFF", &mut out, &mut out_len),
+            BASE122_INVALID_INPUT
+        );
+        assert_eq!(decode("ab").unwrap().len(), out_len);
+        assert_eq!(unsafe { base122_decoder_finish(decoder) }, BASE122_OK);
+
+        // A truncated escape is only detected at the end
+        assert_eq!(
+            feed("\u{0100}".as_bytes()[..1].as_ref(), &mut out, &mut out_len),
+            BASE122_OK
+        );
+        assert_eq!(
+            unsafe { base122_decoder_finish(decoder) },
+            BASE122_INVALID_INPUT
+        );
+
+        let status = unsafe {
+            base122_decoder_feed(
+                ptr::null_mut(),
+                ptr::null(),
+                0,
+                out.as_mut_ptr(),
+                4,
+                &mut out_len,
+            )
+        };
+        assert_eq!(status, BASE122_NULL_POINTER);
+        unsafe {
+            base122_decoder_free(decoder);
+            base122_encoder_free(ptr::null_mut());
+        }
+    }
+}
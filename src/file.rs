@@ -0,0 +1,99 @@
+//! Encoding files through memory maps.
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::encode;
+use crate::io::encode_blocks;
+
+/// Input bytes encoded per write: 7 × 128 KiB, a whole number of chunks.
+const BLOCK: usize = 7 << 17;
+
+/// Encodes the file at `path`.
+///
+/// The file is memory-mapped rather than read into a buffer, so only the
+/// encoded string is allocated.
+///
+/// # Errors
+///
+/// Returns any error from opening or mapping the file. The file must not
+/// be modified while it is being encoded.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// let encoded = base122_rs::encode_file("image.png")?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn encode_file(path: impl AsRef<Path>) -> io::Result<String> {
+    Ok(match map(path.as_ref())? {
+        Some(map) => encode(&map),
+        None => String::new(),
+    })
+}
+
+/// Encodes the file at `path` into `writer`.
+///
+/// The file is memory-mapped and encoded in blocks of about 1 MiB, so
+/// memory use stays constant however large the file is. The text written
+/// is exactly [`encode`] of the file's contents.
+///
+/// # Errors
+///
+/// Returns any error from opening or mapping the file or from writing.
+/// The file must not be modified while it is being encoded.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::fs::File;
+/// use std::io::BufWriter;
+///
+/// let mut output = BufWriter::new(File::create("video.b122")?);
+/// base122_rs::encode_file_to("video.mp4", &mut output)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn encode_file_to(path: impl AsRef<Path>, writer: impl Write) -> io::Result<()> {
+    match map(path.as_ref())? {
+        Some(map) => encode_blocks(&map, BLOCK, writer).map(drop),
+        None => Ok(()),
+    }
+}
+
+/// Maps the file at `path`, or returns `None` if it is empty, which cannot
+/// be mapped on every platform.
+fn map(path: &Path) -> io::Result<Option<Mmap>> {
+    let file = File::open(path)?;
+    if file.metadata()?.len() == 0 {
+        return Ok(None);
+    }
+    // SAFETY: the map is only read, and the caller is told not to modify
+    // the file while it is mapped
+    #[allow(unsafe_code)]
+    let map = unsafe { Mmap::map(&file)? };
+    Ok(Some(map))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::{generate, Profile};
+
+    #[test]
+    fn test_encode_file() {
+        let path = std::env::temp_dir().join(format!("base122-file-{}", std::process::id()));
+        for len in [0, 1, BLOCK + 3] {
+            let data = generate(Profile::Random, len, 6);
+            std::fs::write(&path, &data).unwrap();
+
+            let mut streamed = Vec::new();
+            encode_file_to(&path, &mut streamed).unwrap();
+            assert_eq!(encode_file(&path).unwrap().into_bytes(), streamed);
+            assert_eq!(streamed, encode(&data).into_bytes());
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,157 @@
+//! The standard wire format, as constants and low-level helpers.
+//!
+//! Input is read as a stream of 7-bit chunks, most significant bit first,
+//! with the last chunk padded with zero bits. A chunk that is not one of
+//! [`ILLEGALS`] is written as the ASCII byte with the same value. A chunk
+//! that is one of them is folded together with the chunk after it into a
+//! single 2-byte UTF-8 character:
+//!
+//! ```text
+//! 110iiif1 10ffffff
+//! ```
+//!
+//! `iii` is the index of the dangerous chunk in [`ILLEGALS`] and `fffffff`
+//! the 7 bits of the chunk after it. If the dangerous chunk is the last
+//! chunk of the input, `iii` is [`SHORTENED`] and the payload bits are the
+//! dangerous chunk itself. The fixed `1` keeps every escape at or above
+//! U+0080, so it is never an overlong encoding.
+//!
+//! These items are the single definition the encoder and decoder are built
+//! on, for ports to other languages and for tools that pick encoded text
+//! apart. [`Profile`](crate::Profile)s other than the standard one use
+//! escapes of their own.
+//!
+//! ```rust
+//! use base122_rs::format::{emit_escape, illegal_index, parse_escape, SHORTENED};
+//!
+//! let code_point = |escape: [u8; 2]| {
+//!     let text = std::str::from_utf8(&escape).unwrap();
+//!     u32::from(text.chars().next().unwrap())
+//! };
+//!
+//! // A newline chunk followed by the chunk 0x41
+//! let escape = emit_escape(illegal_index(b'\n').unwrap(), 0x41);
+//! assert_eq!(parse_escape(code_point(escape)), Some((Some(b'\n'), 0x41)));
+//!
+//! // A newline chunk at the end of the input
+//! let escape = emit_escape(SHORTENED, b'\n');
+//! assert_eq!(parse_escape(code_point(escape)), Some((None, b'\n')));
+//! ```
+
+/// Number of input bits carried by each chunk.
+pub const CHUNK_BITS: u32 = 7;
+
+/// The six "dangerous" characters that require special UTF-8 encoding.
+///
+/// These characters can cause issues in transmission or parsing and are
+/// encoded using 2-byte UTF-8 sequences instead of single bytes. A chunk's
+/// position in this array is its escape index.
+pub const ILLEGALS: [u8; 6] = [
+    0,  // null - can truncate strings
+    10, // newline - breaks single-line transmission
+    13, // carriage return - breaks single-line transmission
+    34, // double quote - breaks JSON/HTML attributes
+    38, // ampersand - conflicts with HTML entities
+    92, // backslash - conflicts with escape sequences
+];
+
+/// Escape index marking a dangerous chunk at the end of the input, whose
+/// escape carries the chunk itself instead of a following chunk.
+pub const SHORTENED: u8 = 0b111;
+
+/// Fixed bits of the first byte of an escape: the 2-byte UTF-8 lead and
+/// the bit that keeps escapes at or above U+0080.
+pub const ESCAPE_LEAD: u8 = 0b1100_0010;
+
+/// Fixed bits of the second byte of an escape, a UTF-8 continuation byte.
+pub const ESCAPE_CONTINUATION: u8 = 0b1000_0000;
+
+/// Entry in [`ILLEGAL_INDEX`] for chunks that are not dangerous.
+pub(crate) const SAFE: u8 = 0xFF;
+
+/// Lookup table from a 7-bit chunk to its index in [`ILLEGALS`], or [`SAFE`].
+///
+/// Replaces a linear search of `ILLEGALS` on every chunk with a single load.
+pub(crate) const ILLEGAL_INDEX: [u8; 128] = {
+    let mut table = [SAFE; 128];
+    let mut i = 0;
+    while i < ILLEGALS.len() {
+        table[ILLEGALS[i] as usize] = i as u8;
+        i += 1;
+    }
+    table
+};
+
+/// The escape index of `chunk`, or `None` if it is not dangerous.
+///
+/// Only the low 7 bits of `chunk` are used.
+pub const fn illegal_index(chunk: u8) -> Option<u8> {
+    match ILLEGAL_INDEX[(chunk & 0x7F) as usize] {
+        SAFE => None,
+        index => Some(index),
+    }
+}
+
+/// Builds the 2-byte UTF-8 sequence for a dangerous chunk.
+///
+/// `index` is the chunk's position in [`ILLEGALS`] (or [`SHORTENED`]) and
+/// `payload` the 7 bits carried alongside it. The layout is
+/// `110iiif1 10ffffff`. Only the low 3 bits of `index` and 7 bits of
+/// `payload` are used.
+#[inline]
+pub const fn emit_escape(index: u8, payload: u8) -> [u8; 2] {
+    [
+        ESCAPE_LEAD | ((index & 0b111) << 2) | ((payload & 0x7F) >> 6),
+        ESCAPE_CONTINUATION | (payload & 0b0011_1111),
+    ]
+}
+
+/// Splits the code point of a 2-byte escape into its parts.
+///
+/// Returns the dangerous chunk the escape stands for (`None` for the
+/// shortened marker, which carries no dangerous chunk of its own) and the
+/// 7-bit payload, or `None` if `c` is not a sequence the encoder emits.
+pub fn parse_escape(c: u32) -> Option<(Option<u8>, u8)> {
+    if !(0x80..=0x7FF).contains(&c) || c & 0x80 == 0 {
+        return None;
+    }
+
+    let illegal_index = ((c >> 8) & 7) as u8; // Extract illegal character index
+    let payload = (c & 127) as u8;
+    if illegal_index == SHORTENED {
+        return Some((None, payload));
+    }
+    ILLEGALS
+        .get(illegal_index as usize)
+        .map(|&illegal| (Some(illegal), payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escapes_round_trip() {
+        for index in (0..ILLEGALS.len() as u8).chain([SHORTENED]) {
+            for payload in 0..128 {
+                let escape = emit_escape(index, payload);
+                let text = core::str::from_utf8(&escape).unwrap();
+                let c = text.chars().next().unwrap() as u32;
+                let chunk = ILLEGALS.get(index as usize).copied();
+                assert_eq!(parse_escape(c), Some((chunk, payload)));
+            }
+        }
+        // Indices 6 and below U+0080 are never emitted
+        assert_eq!(parse_escape(0x680), None);
+        assert_eq!(parse_escape(0x41), None);
+    }
+
+    #[test]
+    fn test_illegal_index() {
+        for chunk in 0..128u8 {
+            let expected = ILLEGALS.iter().position(|&c| c == chunk);
+            assert_eq!(illegal_index(chunk), expected.map(|i| i as u8));
+        }
+        assert_eq!(CHUNK_BITS, 7);
+    }
+}
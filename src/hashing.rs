@@ -0,0 +1,149 @@
+//! Hashing input while encoding it.
+
+use alloc::{string::String, vec::Vec};
+
+use digest::{Digest, Output};
+
+use crate::max_encoded_len;
+use crate::stream::EncodeState;
+
+/// A streaming encoder that also feeds its input into a [`Digest`].
+///
+/// Input is passed to [`update`](DigestEncoder::update) in pieces of any
+/// size; [`finalize`](DigestEncoder::finalize) returns the encoded text,
+/// identical to [`encode`](crate::encode) of the whole input, together with
+/// its hash. Asset pipelines that need both an inline encoding and a
+/// content hash or integrity value get them in a single pass over the data.
+///
+/// With the `std` feature the encoder also implements [`std::io::Write`],
+/// so a file can be hashed and encoded with [`std::io::copy`].
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{encode, DigestEncoder};
+/// use sha2::{Digest, Sha256};
+///
+/// let mut encoder = DigestEncoder::<Sha256>::new();
+/// encoder.update(b"\0asset ");
+/// encoder.update(b"bytes\n");
+/// let (encoded, hash) = encoder.finalize();
+///
+/// assert_eq!(encoded, encode(b"\0asset bytes\n"));
+/// assert_eq!(hash, Sha256::digest(b"\0asset bytes\n"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct DigestEncoder<D> {
+    state: EncodeState,
+    digest: D,
+    output: Vec<u8>,
+}
+
+impl<D: Digest> DigestEncoder<D> {
+    /// Creates an encoder with a fresh hasher.
+    pub fn new() -> Self {
+        DigestEncoder::with_digest(D::new())
+    }
+
+    /// Creates an encoder that continues hashing with `digest`, e.g. one
+    /// already fed a prefix or created with a key.
+    pub fn with_digest(digest: D) -> Self {
+        DigestEncoder {
+            state: EncodeState::default(),
+            digest,
+            output: Vec::new(),
+        }
+    }
+
+    /// Hashes and encodes the next piece of input.
+    pub fn update(&mut self, data: impl AsRef<[u8]>) {
+        let data = data.as_ref();
+        self.digest.update(data);
+
+        let DigestEncoder { state, output, .. } = self;
+        output.reserve(max_encoded_len(data.len()));
+        for &byte in data {
+            state.push(byte, &mut |encoded| output.push(encoded));
+        }
+    }
+
+    /// Encodes the end of the input and returns the encoded text and the
+    /// hash of everything passed to [`update`](DigestEncoder::update).
+    pub fn finalize(self) -> (String, Output<D>) {
+        let DigestEncoder {
+            mut state,
+            digest,
+            mut output,
+        } = self;
+        state.finish(&mut |encoded| output.push(encoded));
+        // The encoder only emits ASCII and 2-byte escapes
+        let encoded = String::from_utf8(output).unwrap_or_default();
+        (encoded, digest.finalize())
+    }
+}
+
+impl<D: Digest> Default for DigestEncoder<D> {
+    fn default() -> Self {
+        DigestEncoder::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D: Digest> std::io::Write for DigestEncoder<D> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Encodes `data` and hashes it with `D` in one pass.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{encode, encode_with_digest};
+/// use sha2::{Digest, Sha256};
+///
+/// let (encoded, hash) = encode_with_digest::<Sha256>(b"logo.png bytes");
+/// assert_eq!(encoded, encode(b"logo.png bytes"));
+/// assert_eq!(hash, Sha256::digest(b"logo.png bytes"));
+/// ```
+pub fn encode_with_digest<D: Digest>(data: impl AsRef<[u8]>) -> (String, Output<D>) {
+    let mut encoder = DigestEncoder::<D>::new();
+    encoder.update(data);
+    encoder.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::{generate, Profile};
+    use crate::encode;
+    use sha2::{Sha256, Sha512};
+
+    #[test]
+    fn test_digest_encoder_matches_separate_passes() {
+        for len in [0, 1, 6, 7, 8, 100, 5000] {
+            let data = generate(Profile::DangerousDensity(0.2), len, 5);
+            for split in [1, 3, 7, 1000] {
+                let mut encoder = DigestEncoder::<Sha256>::new();
+                data.chunks(split).for_each(|piece| encoder.update(piece));
+                let (encoded, hash) = encoder.finalize();
+                assert_eq!(encoded, encode(&data), "len {len} split {split}");
+                assert_eq!(hash, Sha256::digest(&data));
+            }
+        }
+    }
+
+    #[test]
+    fn test_digest_encoder_io_copy() {
+        let data = generate(Profile::Image, 10_000, 9);
+        let mut encoder = DigestEncoder::<Sha512>::default();
+        std::io::copy(&mut &data[..], &mut encoder).unwrap();
+        assert_eq!(encoder.finalize(), encode_with_digest::<Sha512>(&data));
+    }
+}
@@ -0,0 +1,209 @@
+//! Encoding and decoding input that arrives in pieces.
+
+use alloc::{string::String, vec::Vec};
+
+use crate::stream::{DecodeState, EncodeState};
+use crate::{max_encoded_len, DecodeError};
+
+/// An encoder fed its input one piece at a time.
+///
+/// Input is passed to [`update`](Base122Encoder::update) in pieces of any
+/// size, such as blocks read from a file or frames from a socket, and each
+/// piece can be dropped as soon as it has been passed in.
+/// [`finalize`](Base122Encoder::finalize) returns the encoded text,
+/// identical to [`encode`](crate::encode) of the whole input. Only the
+/// encoded text is kept, so the input never has to be in memory at once.
+///
+/// With the `std` feature the encoder also implements [`std::io::Write`],
+/// so a file can be encoded with [`std::io::copy`].
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{encode, Base122Encoder};
+///
+/// let mut encoder = Base122Encoder::new();
+/// encoder.update(b"\0first block ");
+/// encoder.update(b"second block\n");
+///
+/// assert_eq!(encoder.finalize(), encode(b"\0first block second block\n"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Base122Encoder {
+    state: EncodeState,
+    output: Vec<u8>,
+}
+
+impl Base122Encoder {
+    /// Creates an encoder with no input yet.
+    pub fn new() -> Self {
+        Base122Encoder::default()
+    }
+
+    /// Creates an encoder whose output has room for the encoding of
+    /// `input_len` bytes of input.
+    pub fn with_capacity(input_len: usize) -> Self {
+        Base122Encoder {
+            state: EncodeState::default(),
+            output: Vec::with_capacity(max_encoded_len(input_len)),
+        }
+    }
+
+    /// Encodes the next piece of input.
+    pub fn update(&mut self, chunk: &[u8]) {
+        let Base122Encoder { state, output } = self;
+        output.reserve(max_encoded_len(chunk.len()));
+        for &byte in chunk {
+            state.push(byte, &mut |encoded| output.push(encoded));
+        }
+    }
+
+    /// Encodes the end of the input and returns the encoded text of
+    /// everything passed to [`update`](Base122Encoder::update).
+    pub fn finalize(self) -> String {
+        let Base122Encoder {
+            mut state,
+            mut output,
+        } = self;
+        state.finish(&mut |encoded| output.push(encoded));
+        // The encoder only emits ASCII and 2-byte escapes
+        String::from_utf8(output).unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Write for Base122Encoder {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A decoder fed Base122 text one piece at a time.
+///
+/// Each call to [`push`](Base122Decoder::push) decodes the next piece of
+/// text and returns the bytes it completed, so data arriving over a socket
+/// can be processed as it comes in. [`push_bytes`](Base122Decoder::push_bytes)
+/// takes raw bytes instead, which may split a 2-byte character between
+/// pieces. [`finish`](Base122Decoder::finish) checks that the text did not
+/// end in the middle of a character.
+///
+/// Errors carry the byte offset of the problem in the whole text. After an
+/// error the decoder should be discarded.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{encode, Base122Decoder};
+///
+/// let encoded = encode(b"\0payload\n");
+/// let (first, second) = encoded.as_bytes().split_at(1);
+///
+/// let mut decoder = Base122Decoder::new();
+/// let mut decoded = decoder.push_bytes(first)?.to_vec();
+/// decoded.extend_from_slice(decoder.push_bytes(second)?);
+/// decoder.finish()?;
+/// assert_eq!(decoded, b"\0payload\n");
+/// # Ok::<(), base122_rs::DecodeError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Base122Decoder {
+    state: DecodeState,
+    /// Bytes completed by the last push, reused between pushes.
+    output: Vec<u8>,
+}
+
+impl Base122Decoder {
+    /// Creates a decoder with no input yet.
+    pub fn new() -> Self {
+        Base122Decoder::default()
+    }
+
+    /// Decodes the next piece of text and returns the bytes it completed.
+    pub fn push(&mut self, text: &str) -> Result<&[u8], DecodeError> {
+        self.push_bytes(text.as_bytes())
+    }
+
+    /// Decodes the next piece of encoded bytes and returns the bytes it
+    /// completed.
+    ///
+    /// Pieces may start or end inside a 2-byte character.
+    pub fn push_bytes(&mut self, bytes: &[u8]) -> Result<&[u8], DecodeError> {
+        let Base122Decoder { state, output } = self;
+        output.clear();
+        output.reserve(bytes.len());
+        state.push_slice(bytes, &mut |decoded| output.push(decoded))?;
+        Ok(output)
+    }
+
+    /// Checks that the text did not end inside a character.
+    pub fn finish(self) -> Result<(), DecodeError> {
+        let Base122Decoder { mut state, .. } = self;
+        state.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode;
+
+    #[test]
+    fn test_encoder_matches_encode() {
+        let data: Vec<u8> = (0..=255).cycle().take(2000).collect();
+        for len in [0, 1, 6, 7, 8, 100, 2000] {
+            for piece in [1, 3, 7, 64, 4096] {
+                let mut encoder = Base122Encoder::new();
+                for chunk in data[..len].chunks(piece) {
+                    encoder.update(chunk);
+                }
+                assert_eq!(encoder.finalize(), encode(&data[..len]), "len {len}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_decoder_matches_decode() {
+        let data: Vec<u8> = (0..=255).cycle().take(2000).collect();
+        let encoded = encode(&data);
+        for piece in [1, 2, 3, 64, 4096] {
+            let mut decoder = Base122Decoder::new();
+            let mut decoded = Vec::new();
+            for chunk in encoded.as_bytes().chunks(piece) {
+                decoded.extend_from_slice(decoder.push_bytes(chunk).unwrap());
+            }
+            decoder.finish().unwrap();
+            assert_eq!(decoded, data, "piece {piece}");
+        }
+
+        let mut decoder = Base122Decoder::new();
+        assert_eq!(decoder.push("a").unwrap(), b"");
+        assert_eq!(
+            decoder.push("b€"),
+            Err(DecodeError::InvalidByte {
+                position: 2,
+                byte: 0xE2
+            })
+        );
+
+        let mut decoder = Base122Decoder::new();
+        decoder.push_bytes(&[b'a', 0xC2]).unwrap();
+        assert_eq!(
+            decoder.finish(),
+            Err(DecodeError::UnexpectedEnd { position: 2 })
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_encoder_io_copy() {
+        let data = b"\n\r\0\"&\\ copied through std::io";
+        let mut encoder = Base122Encoder::with_capacity(data.len());
+        std::io::copy(&mut &data[..], &mut encoder).unwrap();
+        assert_eq!(encoder.finalize(), encode(data));
+    }
+}
@@ -0,0 +1,111 @@
+//! Decoding into the buffer that holds the encoded text.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::stream::DecodeState;
+use crate::DecodeError;
+
+/// Decodes the Base122 text in `buffer` into the front of `buffer` and
+/// returns the decoded prefix.
+///
+/// Every encoded byte carries at most 7 bits, so the output never catches
+/// up with the input still to be read. This decodes without a second
+/// buffer, for memory-constrained services that already own the text.
+/// `buffer` need not be valid UTF-8; invalid bytes are reported like
+/// anything else [`decode`](crate::decode) rejects. Needs no allocator.
+///
+/// # Errors
+///
+/// Fails like [`decode`](crate::decode) on invalid input, with
+/// [`DecodeError::InvalidByte`] for bytes that are not part of a Base122
+/// character and [`DecodeError::UnexpectedEnd`] if the input ends inside
+/// one. The contents of `buffer` are then unspecified.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{decode_in_place, encode};
+///
+/// let mut buffer = encode(b"\0in place\n").into_bytes();
+/// let decoded = decode_in_place(&mut buffer).unwrap();
+/// assert_eq!(decoded, b"\0in place\n");
+/// ```
+pub fn decode_in_place(buffer: &mut [u8]) -> Result<&mut [u8], DecodeError> {
+    let mut state = DecodeState::default();
+    let mut written = 0;
+    for read in 0..buffer.len() {
+        let byte = buffer[read];
+        // Output lags input: `written` stays below `read` here
+        state.push(byte, &mut |decoded| {
+            buffer[written] = decoded;
+            written += 1;
+        })?;
+    }
+    state.finish()?;
+    Ok(&mut buffer[..written])
+}
+
+/// Decodes the Base122 text in `encoded` into its own allocation.
+///
+/// Like [`decode_in_place`], but takes and returns an owned buffer, e.g.
+/// from [`String::into_bytes`](alloc::string::String::into_bytes). The
+/// capacity is kept, so this never allocates.
+///
+/// # Errors
+///
+/// Fails like [`decode_in_place`].
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{decode_vec_in_place, encode};
+///
+/// let body = encode(&[0xFF; 100]).into_bytes();
+/// assert_eq!(decode_vec_in_place(body).unwrap(), [0xFF; 100]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn decode_vec_in_place(mut encoded: Vec<u8>) -> Result<Vec<u8>, DecodeError> {
+    let len = decode_in_place(&mut encoded)?.len();
+    encoded.truncate(len);
+    Ok(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::{generate, Profile};
+    use crate::{decode, encode};
+
+    #[test]
+    fn test_in_place_matches_decode() {
+        for len in 0..200 {
+            let data = generate(Profile::DangerousDensity(0.5), len, len as u64);
+            let encoded = encode(&data);
+            let mut buffer = encoded.clone().into_bytes();
+            assert_eq!(
+                decode_in_place(&mut buffer).unwrap(),
+                decode(&encoded).unwrap()
+            );
+
+            let decoded = decode_vec_in_place(encoded.into_bytes()).unwrap();
+            assert_eq!(decoded, data);
+        }
+    }
+
+    #[test]
+    fn test_in_place_errors() {
+        assert_eq!(
+            decode_in_place(&mut [b'a', 0xFF]),
+            Err(DecodeError::InvalidByte {
+                position: 1,
+                byte: 0xFF
+            })
+        );
+        assert_eq!(
+            decode_in_place(&mut [b'a', 0xC2]),
+            Err(DecodeError::UnexpectedEnd { position: 2 })
+        );
+        assert!(decode_vec_in_place("\u{0680}".into()).is_err());
+    }
+}
@@ -0,0 +1,573 @@
+//! Adapters between Base122 and `std::io`.
+
+use std::io::{self, BufRead, Read, Write};
+
+use crate::stream::{DecodeState, EncodeState};
+use crate::{
+    decode, encode_profile_groups, encode_profile_tail, max_encoded_len, DecodeError, Profile,
+};
+
+/// Size of the buffers [`DecodeReader`] reads and decodes into.
+const BUFFER_SIZE: usize = 8 * 1024;
+
+/// Input bytes [`encode_to_writer`] encodes per write: 7 KiB, a whole
+/// number of chunks.
+const BLOCK: usize = 7 << 10;
+
+/// Encodes `data` into `writer`, returning the number of bytes written.
+///
+/// The text is encoded a few kilobytes at a time and written as it is
+/// produced, so a server can stream an encoded response without building
+/// the whole string first. The text written is exactly
+/// [`encode`](crate::encode) of `data`.
+///
+/// Every piece is passed to [`write_all`](Write::write_all); wrap a writer
+/// that is slow to write to in small pieces in a
+/// [`BufWriter`](io::BufWriter).
+///
+/// # Errors
+///
+/// Returns any error from writing. Some of the text may have been written.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{encode, encode_to_writer};
+///
+/// let mut response = Vec::new();
+/// let written = encode_to_writer(b"\0binary body\n", &mut response)?;
+///
+/// assert_eq!(response, encode(b"\0binary body\n").as_bytes());
+/// assert_eq!(written, response.len());
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn encode_to_writer(data: &[u8], writer: impl Write) -> io::Result<usize> {
+    encode_blocks(data, BLOCK, writer)
+}
+
+/// Encodes `data` into `writer` in pieces of `block` bytes, returning the
+/// number of bytes written.
+///
+/// `block` must be a multiple of 7, so that pieces end on chunk
+/// boundaries. A dangerous chunk at the end of a piece is carried into
+/// the next, so the buffer never holds more than one piece's encoding.
+pub(crate) fn encode_blocks(
+    data: &[u8],
+    block: usize,
+    mut writer: impl Write,
+) -> io::Result<usize> {
+    let profile = &Profile::STANDARD;
+    let whole = data.len() - data.len() % 7;
+    let mut buffer = Vec::with_capacity(max_encoded_len(block.min(data.len())));
+    let mut written = 0;
+    let mut pending = None;
+    for piece in data[..whole].chunks(block) {
+        buffer.clear();
+        encode_profile_groups(profile, piece, &mut pending, &mut buffer);
+        writer.write_all(&buffer)?;
+        written += buffer.len();
+    }
+
+    let mut tail = [0; 16];
+    let len = encode_profile_tail(profile, pending, &data[whole..], &mut tail);
+    writer.write_all(&tail[..len])?;
+    Ok(written + len)
+}
+
+/// Decodes newline-delimited Base122 records from `reader`.
+///
+/// Newlines are dangerous characters and never appear in encoded output,
+/// so a file with one encoded payload per line can be split on line
+/// endings without any escaping. Each line, with its `\n` or `\r\n`
+/// terminator removed, is decoded independently; empty lines yield empty
+/// payloads.
+///
+/// Read failures are passed through. A line that is not valid Base122
+/// produces an error of kind [`io::ErrorKind::InvalidData`] wrapping the
+/// [`DecodeError`], and iteration can continue with
+/// the next line.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{decode_lines, encode};
+///
+/// let log = format!("{}\n{}\r\n", encode(b"first\n"), encode(&[0, 1, 2]));
+/// let records: Vec<Vec<u8>> = decode_lines(log.as_bytes())
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+///
+/// assert_eq!(records, vec![b"first\n".to_vec(), vec![0, 1, 2]]);
+/// ```
+pub fn decode_lines<R: BufRead>(reader: R) -> impl Iterator<Item = io::Result<Vec<u8>>> {
+    reader.lines().map(|line| {
+        let line = line?;
+        decode(&line).map_err(invalid_data)
+    })
+}
+
+/// Decodes all of the Base122 text in `reader`.
+///
+/// The text is read and decoded a block at a time, so only the decoded
+/// bytes are held in memory, never the whole text: decoding a 2 GB file
+/// needs about 1.75 GB rather than 3.75 GB. Reads interrupted by a signal
+/// are retried.
+///
+/// # Errors
+///
+/// Read failures are passed through. Invalid text, including text that
+/// ends in the middle of a character, produces an error of kind
+/// [`io::ErrorKind::InvalidData`] wrapping the [`DecodeError`], as with
+/// [`DecodeReader`].
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{decode_from_reader, encode};
+///
+/// let text = encode(b"\0archived payload\n");
+/// let decoded = decode_from_reader(text.as_bytes())?;
+///
+/// assert_eq!(decoded, b"\0archived payload\n");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn decode_from_reader(mut reader: impl Read) -> io::Result<Vec<u8>> {
+    let mut state = DecodeState::default();
+    let mut decoded = Vec::new();
+    let mut input = [0u8; BUFFER_SIZE];
+    loop {
+        let read = match reader.read(&mut input) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        };
+        decoded.reserve(read);
+        state
+            .push_slice(&input[..read], &mut |byte| decoded.push(byte))
+            .map_err(invalid_data)?;
+    }
+    state.finish().map_err(invalid_data)?;
+    Ok(decoded)
+}
+
+/// Encodes bytes written to it and writes the Base122 text to `W`.
+///
+/// Each [`write`](Write::write) encodes its bytes and passes the text on to
+/// the inner writer, so the adapter fits anywhere a [`Write`] does, such as
+/// the destination of [`io::copy`]. Wrap the inner writer in a
+/// [`BufWriter`](io::BufWriter) if it is slow to write to in small pieces.
+///
+/// Up to six bits of input and an unfinished escape wait for the bytes
+/// after them, since the text for them depends on what follows. They are
+/// written at the end of the input: by [`finish`](EncodeWriter::finish),
+/// which reports errors, or when the writer is dropped, which ignores them.
+/// [`flush`](Write::flush) flushes the inner writer but holds them back, so
+/// the text written so far stays a prefix of [`encode`](crate::encode) of
+/// the whole input.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io;
+/// use base122_rs::{encode, EncodeWriter};
+///
+/// let mut input = &b"\0file contents\n"[..];
+/// let mut writer = EncodeWriter::new(Vec::new());
+/// io::copy(&mut input, &mut writer)?;
+/// let text = writer.finish()?;
+///
+/// assert_eq!(text, encode(b"\0file contents\n").as_bytes());
+/// # Ok::<(), io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct EncodeWriter<W: Write> {
+    /// The inner writer, taken by `finish`.
+    inner: Option<W>,
+    state: EncodeState,
+    /// Text of the current write, reused between writes.
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> EncodeWriter<W> {
+    /// Creates a writer encoding into `inner`.
+    pub fn new(inner: W) -> Self {
+        EncodeWriter {
+            inner: Some(inner),
+            state: EncodeState::default(),
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Encodes the end of the input, flushes, and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_end()?;
+        let mut inner = self.inner.take().expect("writer is not finished");
+        inner.flush()?;
+        Ok(inner)
+    }
+
+    /// Returns a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        self.inner.as_ref().expect("writer is not finished")
+    }
+
+    /// Returns a mutable reference to the inner writer.
+    ///
+    /// Writing to it directly will corrupt the encoded stream.
+    pub fn get_mut(&mut self) -> &mut W {
+        self.inner.as_mut().expect("writer is not finished")
+    }
+
+    /// Writes the text for the bits and escape held back.
+    fn write_end(&mut self) -> io::Result<()> {
+        let EncodeWriter {
+            inner,
+            state,
+            buffer,
+        } = self;
+        buffer.clear();
+        state.finish(&mut |encoded| buffer.push(encoded));
+        inner
+            .as_mut()
+            .expect("writer is not finished")
+            .write_all(buffer)
+    }
+}
+
+impl<W: Write> Write for EncodeWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let EncodeWriter {
+            inner,
+            state,
+            buffer,
+        } = self;
+        buffer.clear();
+        buffer.reserve(max_encoded_len(buf.len()));
+        for &byte in buf {
+            state.push(byte, &mut |encoded| buffer.push(encoded));
+        }
+        inner
+            .as_mut()
+            .expect("writer is not finished")
+            .write_all(buffer)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.get_mut().flush()
+    }
+}
+
+impl<W: Write> Drop for EncodeWriter<W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            // Errors cannot be reported from here; `finish` reports them
+            let _ = self.write_end();
+        }
+    }
+}
+
+/// Reads Base122 text from `R` and yields the decoded bytes.
+///
+/// Text is read from the inner reader a block at a time and decoded as it
+/// arrives, so a large file or a socket can be decoded without holding the
+/// whole text in memory. A 2-byte character split between two reads is
+/// put back together. Invalid text, including text that ends in the middle
+/// of a character, produces an error of kind [`io::ErrorKind::InvalidData`]
+/// wrapping the [`DecodeError`].
+///
+/// The reader also implements [`BufRead`], over its buffer of decoded
+/// bytes.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io::{self, Read};
+/// use base122_rs::{encode, DecodeReader};
+///
+/// let text = encode(b"\0file contents\n");
+/// let mut reader = DecodeReader::new(text.as_bytes());
+/// let mut decoded = Vec::new();
+/// reader.read_to_end(&mut decoded)?;
+///
+/// assert_eq!(decoded, b"\0file contents\n");
+/// # Ok::<(), io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct DecodeReader<R> {
+    inner: R,
+    state: DecodeState,
+    /// Decoded bytes, of which `start..` are not yet read.
+    buffer: Vec<u8>,
+    start: usize,
+    eof: bool,
+}
+
+impl<R: Read> DecodeReader<R> {
+    /// Creates a reader decoding from `inner`.
+    pub fn new(inner: R) -> Self {
+        DecodeReader {
+            inner,
+            state: DecodeState::default(),
+            buffer: Vec::new(),
+            start: 0,
+            eof: false,
+        }
+    }
+
+    /// Returns a reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Consumes the adapter and returns the inner reader.
+    ///
+    /// Any decoded bytes not yet read are lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for DecodeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let decoded = self.fill_buf()?;
+        let count = buf.len().min(decoded.len());
+        buf[..count].copy_from_slice(&decoded[..count]);
+        self.consume(count);
+        Ok(count)
+    }
+}
+
+impl<R: Read> BufRead for DecodeReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let mut input = [0u8; BUFFER_SIZE];
+        while self.start == self.buffer.len() && !self.eof {
+            self.buffer.clear();
+            self.start = 0;
+
+            let read = self.inner.read(&mut input)?;
+            if read == 0 {
+                self.eof = true;
+                self.state.finish().map_err(invalid_data)?;
+                break;
+            }
+            let DecodeReader { state, buffer, .. } = self;
+            state
+                .push_slice(&input[..read], &mut |byte| buffer.push(byte))
+                .map_err(invalid_data)?;
+        }
+        Ok(&self.buffer[self.start..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.start = (self.start + amount).min(self.buffer.len());
+    }
+}
+
+/// Wraps a decode error for `std::io`.
+fn invalid_data(error: DecodeError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode;
+
+    #[test]
+    fn test_blocks_match_encode() {
+        use crate::corpus::{generate, Profile};
+
+        let inputs = [
+            vec![0; 500],
+            generate(Profile::DangerousDensity(0.5), 2000, 3),
+            generate(Profile::DangerousDensity(1.0), 2000, 4),
+            generate(Profile::Image, 2000, 5),
+        ];
+        for data in inputs {
+            for block in [7, 14, 49, 700] {
+                let mut output = Vec::new();
+                let written = encode_blocks(&data, block, &mut output).unwrap();
+                assert_eq!(output, encode(&data).into_bytes(), "block {block}");
+                assert_eq!(written, output.len());
+            }
+        }
+    }
+
+    /// A writer keeping only the length of its largest write.
+    #[derive(Default)]
+    struct Largest(usize);
+
+    impl Write for Largest {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0 = self.0.max(buf.len());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_blocks_carry_pending_escape() {
+        // One safe chunk, then a run of `\0` chunks: every group ends with
+        // a dangerous chunk waiting for its payload in the next block
+        let mut data = vec![0; 7 * 1000 + 3];
+        data[0] = 0x02;
+        for block in [7, 14, 700] {
+            let mut output = Vec::new();
+            encode_blocks(&data, block, &mut output).unwrap();
+            assert_eq!(output, encode(&data).into_bytes(), "block {block}");
+
+            let mut largest = Largest::default();
+            encode_blocks(&data, block, &mut largest).unwrap();
+            assert!(largest.0 <= max_encoded_len(block), "block {block}");
+        }
+    }
+
+    #[test]
+    fn test_encode_to_writer() {
+        for len in [0, 1, BLOCK - 1, BLOCK * 3 + 5] {
+            let data: Vec<u8> = (0..len).map(|i| (i * i % 251) as u8).collect();
+            let mut output = Vec::new();
+            let written = encode_to_writer(&data, &mut output).unwrap();
+            assert_eq!(output, encode(&data).into_bytes(), "len {len}");
+            assert_eq!(written, output.len());
+        }
+    }
+
+    #[test]
+    fn test_encode_to_writer_all_dangerous() {
+        // Nothing but `\0` chunks, so no block ends on a safe chunk
+        let data = vec![0; BLOCK * 5 + 3];
+        let mut output = Vec::new();
+        let written = encode_to_writer(&data, &mut output).unwrap();
+        assert_eq!(output, encode(&data).into_bytes());
+        assert_eq!(written, output.len());
+
+        let mut largest = Largest::default();
+        encode_to_writer(&data, &mut largest).unwrap();
+        assert!(largest.0 <= max_encoded_len(BLOCK));
+    }
+
+    #[test]
+    fn test_encode_writer() {
+        let data: Vec<u8> = (0..=255).cycle().take(3000).collect();
+        for piece in [1, 2, 7, 100, 3000] {
+            let mut writer = EncodeWriter::new(Vec::new());
+            for chunk in data.chunks(piece) {
+                writer.write_all(chunk).unwrap();
+                writer.flush().unwrap();
+            }
+            assert_eq!(writer.finish().unwrap(), encode(&data).as_bytes());
+        }
+
+        // Dropping the writer completes the text too
+        let mut text = Vec::new();
+        {
+            let mut writer = EncodeWriter::new(&mut text);
+            writer.write_all(b"\0\0\0").unwrap();
+            assert!(writer.get_ref().len() < encode(b"\0\0\0").len());
+        }
+        assert_eq!(text, encode(b"\0\0\0").as_bytes());
+    }
+
+    /// A reader returning at most `max` bytes per read.
+    struct Trickle<'a> {
+        data: &'a [u8],
+        max: usize,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let count = buf.len().min(self.max).min(self.data.len());
+            buf[..count].copy_from_slice(&self.data[..count]);
+            self.data = &self.data[count..];
+            Ok(count)
+        }
+    }
+
+    #[test]
+    fn test_decode_reader() {
+        let data: Vec<u8> = (0..=255).cycle().take(20_000).collect();
+        let text = encode(&data);
+        for max in [1, 2, 3, 100, BUFFER_SIZE + 1] {
+            let mut reader = DecodeReader::new(Trickle {
+                data: text.as_bytes(),
+                max,
+            });
+            let mut decoded = Vec::new();
+            reader.read_to_end(&mut decoded).unwrap();
+            assert_eq!(decoded, data, "max {max}");
+        }
+
+        // The first byte of an escape, without the second
+        let text = encode(b"\0");
+        let mut reader = DecodeReader::new(&text.as_bytes()[..1]);
+        let error = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            error.get_ref().unwrap().downcast_ref::<DecodeError>(),
+            Some(&DecodeError::UnexpectedEnd { position: 1 })
+        );
+    }
+
+    #[test]
+    fn test_decode_from_reader() {
+        let data: Vec<u8> = (0..=255).cycle().take(20_000).collect();
+        let text = encode(&data);
+        for max in [1, 3, BUFFER_SIZE + 1] {
+            let reader = Trickle {
+                data: text.as_bytes(),
+                max,
+            };
+            assert_eq!(decode_from_reader(reader).unwrap(), data, "max {max}");
+        }
+        assert_eq!(decode_from_reader(&b""[..]).unwrap(), b"");
+
+        let text = format!("{}\u{0800}", encode(b"ok"));
+        let error = decode_from_reader(text.as_bytes()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            error.get_ref().unwrap().downcast_ref::<DecodeError>(),
+            Some(&DecodeError::InvalidByte {
+                position: 3,
+                byte: 0xE0
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_lines_round_trip() {
+        let records: Vec<Vec<u8>> = (0..20)
+            .map(|n| (0..n * 5).map(|i| (i * 11 % 256) as u8).collect())
+            .collect();
+        let text: String = records.iter().map(|r| encode(r) + "\n").collect();
+
+        let decoded: Vec<Vec<u8>> = decode_lines(text.as_bytes())
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(decoded, records);
+    }
+
+    #[test]
+    fn test_decode_lines_continues_after_bad_line() {
+        let text = format!("{}\nbad\u{0800}\n{}", encode(b"a"), encode(b"b"));
+        let results: Vec<io::Result<Vec<u8>>> = decode_lines(text.as_bytes()).collect();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), b"a");
+        let error = results[1].as_ref().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            error.get_ref().unwrap().downcast_ref::<DecodeError>(),
+            Some(&DecodeError::InvalidCharacter {
+                position: 3,
+                character: '\u{0800}'
+            })
+        );
+        assert_eq!(results[2].as_ref().unwrap(), b"b");
+    }
+}
@@ -0,0 +1,417 @@
+//! Telemetry over text-only message brokers.
+//!
+//! MQTT brokers and similar IoT services often carry text payloads only and
+//! cap the size of each message. [`Chunker`] splits a binary payload into
+//! Base122 messages that each fit such a cap, and [`Reassembler`] puts the
+//! payload back together on the receiving side, whatever order the messages
+//! arrive in.
+//!
+//! Each message is the Base122 encoding of a 10-byte header followed by a
+//! slice of the payload. The header holds, big-endian, a 16-bit payload id,
+//! the 16-bit index of the message and the 16-bit number of messages for the
+//! payload, and the CRC-32 of the whole payload, which the reassembler
+//! checks once every message has arrived.
+//!
+//! ```rust
+//! use base122_rs::iot::{Chunker, Reassembler};
+//!
+//! let telemetry: Vec<u8> = (0..=255).cycle().take(1000).collect();
+//! let mut chunker = Chunker::new(256);
+//! let mut messages = chunker.split(&telemetry).unwrap();
+//! assert!(messages.iter().all(|message| message.len() <= 256));
+//!
+//! // The broker may deliver them in any order
+//! messages.reverse();
+//! let mut reassembler = Reassembler::new();
+//! let mut received = None;
+//! for message in &messages {
+//!     received = reassembler.push(message).unwrap();
+//! }
+//! assert_eq!(received, Some(telemetry));
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt;
+
+use crate::{decode, encode, max_encoded_len, DecodeError};
+
+/// Length of the header at the start of each decoded message.
+pub const HEADER_LEN: usize = 10;
+
+/// Splits payloads into messages of bounded size.
+///
+/// Every payload gets the next 16-bit id, wrapping around after 65535, so
+/// a receiver can tell the messages of concurrent payloads apart.
+#[derive(Debug, Clone)]
+pub struct Chunker {
+    max_message_len: usize,
+    next_id: u16,
+}
+
+impl Chunker {
+    /// Creates a chunker whose messages are at most `max_message_len`
+    /// bytes of UTF-8 each, the unit brokers count in.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_message_len` is less than 14, too small for the
+    /// header and one byte of payload.
+    pub fn new(max_message_len: usize) -> Self {
+        assert!(
+            max_encoded_len(HEADER_LEN + 1) <= max_message_len,
+            "messages must hold at least 14 bytes"
+        );
+        Chunker {
+            max_message_len,
+            next_id: 0,
+        }
+    }
+
+    /// The most bytes of UTF-8 in a message.
+    pub fn max_message_len(&self) -> usize {
+        self.max_message_len
+    }
+
+    /// The most payload bytes a single message carries.
+    pub fn max_fragment_len(&self) -> usize {
+        // `n` bytes encode to at most `ceil(8n / 7) + 1` bytes of UTF-8
+        (self.max_message_len - 1) * 7 / 8 - HEADER_LEN
+    }
+
+    /// Splits `payload` into messages, in order.
+    ///
+    /// An empty payload is sent as a single message.
+    pub fn split(&mut self, payload: &[u8]) -> Result<Vec<String>, PayloadTooLarge> {
+        let fragment_len = self.max_fragment_len();
+        let count = payload.len().div_ceil(fragment_len).max(1);
+        let count = u16::try_from(count).map_err(|_| PayloadTooLarge {
+            max_len: fragment_len * u16::MAX as usize,
+        })?;
+
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        let checksum = crc32(payload);
+
+        let fragments = payload.chunks(fragment_len);
+        let fragments = fragments.chain(payload.is_empty().then_some(&[][..]));
+        let mut message = Vec::with_capacity(HEADER_LEN + fragment_len);
+        let messages = fragments.enumerate().map(|(index, fragment)| {
+            message.clear();
+            message.extend_from_slice(&id.to_be_bytes());
+            message.extend_from_slice(&(index as u16).to_be_bytes());
+            message.extend_from_slice(&count.to_be_bytes());
+            message.extend_from_slice(&checksum.to_be_bytes());
+            message.extend_from_slice(fragment);
+            encode(&message)
+        });
+        Ok(messages.collect())
+    }
+}
+
+/// Error returned by [`Chunker::split`] for a payload that needs more than
+/// 65535 messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PayloadTooLarge {
+    /// The longest payload the chunker can split.
+    pub max_len: usize,
+}
+
+impl fmt::Display for PayloadTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "payload is longer than {} bytes", self.max_len)
+    }
+}
+
+impl core::error::Error for PayloadTooLarge {}
+
+/// A payload whose messages have only partly arrived.
+#[derive(Debug, Clone)]
+struct Partial {
+    count: u16,
+    checksum: u32,
+    fragments: Vec<Option<Vec<u8>>>,
+    received: u16,
+    /// When the first message arrived, to evict the oldest payload.
+    started: u64,
+}
+
+/// Puts payloads back together from the messages of a [`Chunker`].
+///
+/// Messages of a payload may arrive in any order and interleaved with
+/// those of other payloads; duplicates are ignored while the payload is
+/// incomplete, though a message arriving again after its payload completed
+/// starts that payload over. At most
+/// [`max_pending`](Reassembler::max_pending) incomplete payloads are kept,
+/// and a message starting one more discards the payload that started
+/// longest ago, which covers payloads whose remaining messages were lost.
+#[derive(Debug, Clone)]
+pub struct Reassembler {
+    pending: BTreeMap<u16, Partial>,
+    max_pending: usize,
+    /// Number of payloads started so far.
+    started: u64,
+}
+
+impl Reassembler {
+    /// Default for [`max_pending`](Reassembler::max_pending).
+    pub const DEFAULT_MAX_PENDING: usize = 16;
+
+    /// Creates a reassembler with no pending payloads.
+    pub fn new() -> Self {
+        Reassembler {
+            pending: BTreeMap::new(),
+            max_pending: Self::DEFAULT_MAX_PENDING,
+            started: 0,
+        }
+    }
+
+    /// Sets the number of incomplete payloads kept at most.
+    pub fn with_max_pending(mut self, max_pending: usize) -> Self {
+        self.max_pending = max_pending.max(1);
+        self
+    }
+
+    /// The number of incomplete payloads kept at most.
+    pub fn max_pending(&self) -> usize {
+        self.max_pending
+    }
+
+    /// The number of payloads waiting for more messages.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Takes in one message, returning the payload it completes, if any.
+    ///
+    /// A message whose id matches a pending payload but whose count or
+    /// checksum does not is taken to start a new payload that reuses the
+    /// id, replacing the old one.
+    pub fn push(&mut self, message: &str) -> Result<Option<Vec<u8>>, ReassembleError> {
+        let mut decoded = decode(message)?;
+        if decoded.len() < HEADER_LEN {
+            return Err(ReassembleError::Malformed);
+        }
+        let field = |at: usize| u16::from_be_bytes([decoded[at], decoded[at + 1]]);
+        let (id, index, count) = (field(0), field(2), field(4));
+        let checksum = u32::from_be_bytes([decoded[6], decoded[7], decoded[8], decoded[9]]);
+        if index >= count {
+            return Err(ReassembleError::Malformed);
+        }
+        decoded.drain(..HEADER_LEN);
+
+        if count == 1 {
+            self.pending.remove(&id);
+            return check(id, checksum, decoded).map(Some);
+        }
+
+        let stale = self
+            .pending
+            .get(&id)
+            .is_some_and(|partial| partial.count != count || partial.checksum != checksum);
+        if stale {
+            self.pending.remove(&id);
+        }
+        if !self.pending.contains_key(&id) && self.pending.len() >= self.max_pending {
+            let oldest = self
+                .pending
+                .iter()
+                .min_by_key(|(_, partial)| partial.started);
+            if let Some((&oldest, _)) = oldest {
+                self.pending.remove(&oldest);
+            }
+        }
+        let started = &mut self.started;
+        let partial = self.pending.entry(id).or_insert_with(|| {
+            *started += 1;
+            Partial {
+                count,
+                checksum,
+                fragments: vec![None; count as usize],
+                received: 0,
+                started: *started,
+            }
+        });
+
+        let fragment = &mut partial.fragments[index as usize];
+        if fragment.is_none() {
+            *fragment = Some(decoded);
+            partial.received += 1;
+        }
+        if partial.received < partial.count {
+            return Ok(None);
+        }
+        let fragments = core::mem::take(&mut partial.fragments);
+        self.pending.remove(&id);
+        let payload = fragments.into_iter().flatten().flatten().collect();
+        check(id, checksum, payload).map(Some)
+    }
+
+    /// Discards all pending payloads.
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+}
+
+impl Default for Reassembler {
+    fn default() -> Self {
+        Reassembler::new()
+    }
+}
+
+/// Error returned by [`Reassembler::push`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum ReassembleError {
+    /// The message is not valid Base122.
+    Decode(DecodeError),
+    /// The message is too short for a header, or its index is out of range.
+    Malformed,
+    /// The reassembled payload does not match its checksum.
+    Checksum {
+        /// Id of the payload.
+        id: u16,
+    },
+}
+
+impl fmt::Display for ReassembleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReassembleError::Decode(error) => write!(f, "decode failed: {error}"),
+            ReassembleError::Malformed => f.write_str("message has no valid header"),
+            ReassembleError::Checksum { id } => write!(f, "payload {id} failed its checksum"),
+        }
+    }
+}
+
+impl core::error::Error for ReassembleError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            ReassembleError::Decode(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<DecodeError> for ReassembleError {
+    fn from(error: DecodeError) -> Self {
+        ReassembleError::Decode(error)
+    }
+}
+
+/// Returns `payload` if it matches `checksum`.
+fn check(id: u16, checksum: u32, payload: Vec<u8>) -> Result<Vec<u8>, ReassembleError> {
+    if crc32(&payload) != checksum {
+        return Err(ReassembleError::Checksum { id });
+    }
+    Ok(payload)
+}
+
+/// Lookup table for [`crc32`], one entry per byte value.
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// CRC-32 (IEEE 802.3, as in zip and PNG) of `data`.
+fn crc32(data: &[u8]) -> u32 {
+    let crc = data.iter().fold(!0u32, |crc, &byte| {
+        CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    });
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages_fit_and_reassemble() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+
+        let payload: Vec<u8> = (0..=255).cycle().take(3000).collect();
+        for max_message_len in [14, 15, 64, 256, 4096] {
+            let mut chunker = Chunker::new(max_message_len);
+            let messages = chunker.split(&payload).unwrap();
+            assert!(messages.iter().all(|m| m.len() <= max_message_len));
+
+            // Out of order, with a duplicate
+            let mut reassembler = Reassembler::new();
+            let mut order: Vec<usize> = (0..messages.len()).rev().collect();
+            if messages.len() > 1 {
+                order.insert(1, messages.len() - 1);
+            }
+            let mut received = Vec::new();
+            for i in order {
+                received.extend(reassembler.push(&messages[i]).unwrap());
+            }
+            assert_eq!(
+                received,
+                core::slice::from_ref(&payload),
+                "max {max_message_len}"
+            );
+            assert_eq!(reassembler.pending(), 0);
+        }
+
+        let mut chunker = Chunker::new(14);
+        let empty = chunker.split(&[]).unwrap();
+        assert_eq!(empty.len(), 1);
+        assert_eq!(Reassembler::new().push(&empty[0]), Ok(Some(Vec::new())));
+        assert_eq!(
+            chunker.split(&[0; 65536]),
+            Err(PayloadTooLarge { max_len: 65535 })
+        );
+    }
+
+    #[test]
+    fn test_interleaved_lost_and_corrupt() {
+        let mut chunker = Chunker::new(32);
+        let first = chunker.split(&[1; 100]).unwrap();
+        let second = chunker.split(&[2; 100]).unwrap();
+        let lost = chunker.split(&[3; 100]).unwrap();
+
+        let mut reassembler = Reassembler::new().with_max_pending(2);
+        reassembler.push(&lost[0]).unwrap();
+        let mut received = Vec::new();
+        for (a, b) in first.iter().zip(&second) {
+            received.extend(reassembler.push(a).unwrap());
+            received.extend(reassembler.push(b).unwrap());
+        }
+        assert_eq!(received, [vec![1; 100], vec![2; 100]]);
+        assert_eq!(reassembler.pending(), 0);
+
+        // A flipped payload bit survives decoding but not the checksum
+        let mut message = decode(&first[0]).unwrap();
+        message[HEADER_LEN] ^= 1;
+        let mut reassembler = Reassembler::new();
+        reassembler.push(&encode(&message)).unwrap();
+        for message in &first[1..first.len() - 1] {
+            assert_eq!(reassembler.push(message), Ok(None));
+        }
+        assert_eq!(
+            reassembler.push(&first[first.len() - 1]),
+            Err(ReassembleError::Checksum { id: 0 })
+        );
+        assert_eq!(
+            reassembler.push("\u{0181}"),
+            Err(ReassembleError::Malformed)
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! Encoding and decoding as iterator adapters.
+
+use crate::{split_char, Accumulator, DecodeError, Profile};
+
+/// Extension methods that encode and decode inside iterator pipelines.
+///
+/// Implemented for every iterator; the methods are available on iterators
+/// of the matching item type. Neither adapter allocates, so they also work
+/// without `alloc`.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{encode, Base122Ext};
+///
+/// let encoded: String = b"\0piped\n".iter().copied().base122_encode().collect();
+/// assert_eq!(encoded, encode(b"\0piped\n"));
+///
+/// let decoded: Result<Vec<u8>, _> = encoded.chars().base122_decode().collect();
+/// assert_eq!(decoded.unwrap(), b"\0piped\n");
+/// ```
+pub trait Base122Ext: Iterator + Sized {
+    /// Encodes a byte iterator into the characters of its Base122 text.
+    fn base122_encode(self) -> EncodeIter<Self>
+    where
+        Self: Iterator<Item = u8>,
+    {
+        EncodeIter {
+            bytes: self,
+            bits: 0,
+            bit_count: 0,
+            pending: None,
+        }
+    }
+
+    /// Decodes the characters of Base122 text into bytes.
+    ///
+    /// Yields the first error and then stops, with error positions as byte
+    /// offsets in the UTF-8 text.
+    fn base122_decode(self) -> DecodeIter<Self>
+    where
+        Self: Iterator<Item = char>,
+    {
+        DecodeIter {
+            chars: self,
+            accumulator: Accumulator::default(),
+            position: 0,
+            queued: None,
+            failed: false,
+        }
+    }
+}
+
+impl<I: Iterator> Base122Ext for I {}
+
+/// Iterator returned by [`Base122Ext::base122_encode`].
+#[derive(Debug, Clone)]
+pub struct EncodeIter<I> {
+    bytes: I,
+    /// Input bits not yet formed into a chunk, right-aligned.
+    bits: u16,
+    bit_count: u32,
+    /// A dangerous chunk waiting for the chunk that follows it.
+    pending: Option<u8>,
+}
+
+impl<I: Iterator<Item = u8>> EncodeIter<I> {
+    /// The next 7-bit chunk, with the last one padded with zero bits.
+    fn next_chunk(&mut self) -> Option<u8> {
+        if self.bit_count < 7 {
+            match self.bytes.next() {
+                Some(byte) => {
+                    self.bits = (self.bits << 8) | byte as u16;
+                    self.bit_count += 8;
+                }
+                None if self.bit_count > 0 => {
+                    let chunk = (self.bits << (7 - self.bit_count)) as u8 & 0x7F;
+                    self.bit_count = 0;
+                    return Some(chunk);
+                }
+                None => return None,
+            }
+        }
+        self.bit_count -= 7;
+        let chunk = (self.bits >> self.bit_count) as u8 & 0x7F;
+        self.bits &= (1 << self.bit_count) - 1;
+        Some(chunk)
+    }
+}
+
+impl<I: Iterator<Item = u8>> Iterator for EncodeIter<I> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let profile = &Profile::STANDARD;
+        loop {
+            let Some(chunk) = self.next_chunk() else {
+                // A dangerous chunk at the very end has no payload
+                let dangerous = self.pending.take()?;
+                return char::from_u32(profile.shortened(dangerous));
+            };
+            if let Some(dangerous) = self.pending.take() {
+                let index = profile.escape_index(dangerous).unwrap_or_default();
+                return char::from_u32(profile.escape(index, chunk));
+            }
+            if profile.escape_index(chunk).is_none() {
+                return Some(chunk as char);
+            }
+            self.pending = Some(chunk);
+        }
+    }
+}
+
+/// Iterator returned by [`Base122Ext::base122_decode`].
+#[derive(Debug, Clone)]
+pub struct DecodeIter<I> {
+    chars: I,
+    accumulator: Accumulator,
+    /// Byte offset of the next character.
+    position: usize,
+    /// Second byte completed by an escape, if any.
+    queued: Option<u8>,
+    failed: bool,
+}
+
+impl<I: Iterator<Item = char>> Iterator for DecodeIter<I> {
+    type Item = Result<u8, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(byte) = self.queued.take() {
+            return Some(Ok(byte));
+        }
+        while !self.failed {
+            let character = self.chars.next()?;
+            let position = self.position;
+            self.position += character.len_utf8();
+
+            let (illegal, payload) = match split_char(&Profile::STANDARD, position, character) {
+                Ok(split) => split,
+                Err(error) => {
+                    self.failed = true;
+                    return Some(Err(error));
+                }
+            };
+            let first = illegal.and_then(|illegal| self.accumulator.push7(illegal));
+            let second = self.accumulator.push7(payload);
+            match (first, second) {
+                (Some(first), second) => {
+                    self.queued = second;
+                    return Some(Ok(first));
+                }
+                (None, Some(byte)) => return Some(Ok(byte)),
+                (None, None) => {}
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::{generate, Profile as Shape};
+    use crate::encode;
+
+    #[test]
+    fn test_adapters_match_encode() {
+        for len in 0..120 {
+            let data = generate(Shape::DangerousDensity(0.5), len, len as u64);
+            let encoded: String = data.iter().copied().base122_encode().collect();
+            assert_eq!(encoded, encode(&data));
+
+            let decoded: Result<Vec<u8>, _> = encoded.chars().base122_decode().collect();
+            assert_eq!(decoded.unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_decode_adapter_stops_at_error() {
+        let mut iter = "AB\u{0680}CD".chars().base122_decode();
+        assert_eq!(iter.next(), Some(Ok(0x41 << 1 | 0x42 >> 6)));
+        assert_eq!(
+            iter.next(),
+            Some(Err(DecodeError::InvalidEscape {
+                position: 2,
+                character: '\u{0680}'
+            }))
+        );
+        assert_eq!(iter.next(), None);
+    }
+}
@@ -0,0 +1,204 @@
+//! JavaScript decoders for embedding in generated pages.
+
+use alloc::string::String;
+use core::fmt::Write;
+
+use crate::{ChunkWidth, Engine};
+
+/// Options for [`js_decoder_snippet`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnippetOptions<'a> {
+    engine: Engine,
+    function_name: &'a str,
+}
+
+impl<'a> SnippetOptions<'a> {
+    /// Creates options for a `base122Decode` function that decodes the
+    /// output of [`encode`](crate::encode).
+    pub const fn new() -> Self {
+        SnippetOptions {
+            engine: Engine::new(),
+            function_name: "base122Decode",
+        }
+    }
+
+    /// Selects the engine whose output the function decodes.
+    pub const fn with_engine(mut self, engine: Engine) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    /// Sets the name of the generated function, which must be a
+    /// JavaScript identifier made of ASCII letters, digits, `_`, and `$`.
+    pub const fn with_function_name(mut self, name: &'a str) -> Self {
+        self.function_name = name;
+        self
+    }
+}
+
+impl Default for SnippetOptions<'_> {
+    fn default() -> Self {
+        SnippetOptions::new()
+    }
+}
+
+/// Returns the JavaScript source of a function that decodes a string
+/// encoded with the chosen engine into a `Uint8Array`.
+///
+/// The function is self-contained and uses nothing newer than ES5, so
+/// generated HTML can inline it and decode embedded payloads without
+/// loading anything else. To stay small it does not validate its input:
+/// malformed text decodes to unspecified bytes rather than throwing.
+///
+/// # Panics
+///
+/// Panics if the function name is not a valid identifier.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{encode, js_decoder_snippet, SnippetOptions};
+///
+/// let script = js_decoder_snippet(&SnippetOptions::new());
+/// let html = format!(
+///     "<script>{script}\nconst data = base122Decode(\"{}\");</script>",
+///     encode(b"\x89PNG\r\n\x1a\n"),
+/// );
+/// assert!(html.contains("function base122Decode(s)"));
+/// ```
+pub fn js_decoder_snippet(options: &SnippetOptions<'_>) -> String {
+    let name = options.function_name;
+    assert!(
+        is_identifier(name),
+        "{name:?} is not a JavaScript identifier"
+    );
+
+    let engine = options.engine;
+    let mut js = String::new();
+    // Writing to a `String` cannot fail
+    let _ = match engine.chunk_width() {
+        ChunkWidth::Six => write_base64(&mut js, name, engine),
+        _ => write_base122(&mut js, name, engine),
+    };
+    js
+}
+
+/// The 6-bit engine's output is unpadded Base64, so defer to `atob`.
+fn write_base64(js: &mut String, name: &str, engine: Engine) -> core::fmt::Result {
+    writeln!(js, "function {name}(s) {{")?;
+    writeln!(js, "  s = s.replace(/=+$/, \"\");")?;
+    writeln!(js, "  var b = atob(s + \"===\".slice((s.length + 3) % 4));")?;
+    writeln!(js, "  var out = new Uint8Array(b.length);")?;
+    writeln!(
+        js,
+        "  for (var i = 0; i < b.length; i++) out[i] = b.charCodeAt(i);"
+    )?;
+    writeln!(js, "  var n = out.length;")?;
+    write_strip_trailer(js, engine)?;
+    writeln!(js, "  return out.subarray(0, n);")?;
+    write!(js, "}}")
+}
+
+fn write_base122(js: &mut String, name: &str, engine: Engine) -> core::fmt::Result {
+    let profile = engine.profile();
+
+    // Escape blocks (`c >> 7`) to the dangerous value they stand for, or
+    // -1 for shortened escapes that carry only their payload
+    writeln!(js, "function {name}(s) {{")?;
+    write!(js, "  var e = {{")?;
+    for (i, (block, dangerous)) in profile.escape_blocks().enumerate() {
+        let separator = if i == 0 { "" } else { ", " };
+        let value = dangerous.map_or(-1, i16::from);
+        write!(js, "{separator}{block}: {value}")?;
+    }
+    writeln!(js, "}};")?;
+    writeln!(
+        js,
+        "  var out = new Uint8Array(s.length * 2), n = 0, acc = 0, bits = 0;"
+    )?;
+    writeln!(js, "  function push(c) {{")?;
+    writeln!(js, "    acc = (acc << 7) | c;")?;
+    writeln!(js, "    bits += 7;")?;
+    writeln!(js, "    if (bits >= 8) {{")?;
+    writeln!(js, "      bits -= 8;")?;
+    writeln!(js, "      out[n++] = acc >> bits;")?;
+    writeln!(js, "      acc &= (1 << bits) - 1;")?;
+    writeln!(js, "    }}")?;
+    writeln!(js, "  }}")?;
+    writeln!(js, "  for (var i = 0; i < s.length; i++) {{")?;
+    writeln!(js, "    var c = s.charCodeAt(i);")?;
+    if engine.terminator() {
+        let terminator = profile.terminator() as u32;
+        writeln!(js, "    if (c === {terminator:#x}) break;")?;
+    }
+    writeln!(js, "    if (c < 128) {{")?;
+    writeln!(js, "      push(c);")?;
+    writeln!(js, "      continue;")?;
+    writeln!(js, "    }}")?;
+    writeln!(js, "    if (e[c >> 7] >= 0) push(e[c >> 7]);")?;
+    writeln!(js, "    push(c & 127);")?;
+    writeln!(js, "  }}")?;
+    write_strip_trailer(js, engine)?;
+    writeln!(js, "  return out.subarray(0, n);")?;
+    write!(js, "}}")
+}
+
+/// Drops the length trailer from the first `n` bytes of `out`, without
+/// checking it.
+fn write_strip_trailer(js: &mut String, engine: Engine) -> core::fmt::Result {
+    if engine.length_trailer() {
+        writeln!(js, "  while (n > 0 && out[--n] & 128);")?;
+    }
+    Ok(())
+}
+
+/// Whether `name` is an ASCII JavaScript identifier.
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Profile;
+
+    #[test]
+    fn test_standard_snippet_tables() {
+        let js = js_decoder_snippet(&SnippetOptions::new().with_function_name("$b122"));
+        assert!(js.starts_with("function $b122(s) {\n"));
+        assert!(js.contains("var e = {1: 0, 3: 10, 5: 13, 7: 34, 9: 38, 11: 92, 15: -1};"));
+        assert!(!js.contains("break"));
+
+        let framed = Engine::new().with_terminator(true);
+        let js = js_decoder_snippet(&SnippetOptions::new().with_engine(framed));
+        assert!(js.contains("if (c === 0x680) break;"));
+
+        let six = Engine::new().with_chunk_width(ChunkWidth::Six);
+        assert!(js_decoder_snippet(&SnippetOptions::new().with_engine(six)).contains("atob"));
+
+        let sized = Engine::new().with_length_trailer(true);
+        let js = js_decoder_snippet(&SnippetOptions::new().with_engine(sized));
+        assert!(js.contains("out[--n] & 128"));
+    }
+
+    #[test]
+    fn test_snippet_covers_every_escape() {
+        for profile in [&Profile::FILENAME, &Profile::DISPLAY, &Profile::BIDI_SAFE] {
+            let engine = Engine::new().with_profile(profile);
+            let js = js_decoder_snippet(&SnippetOptions::new().with_engine(engine));
+            for &dangerous in profile.dangerous() {
+                assert!(js.contains(&format!(": {dangerous}")), "{profile:?}");
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "not a JavaScript identifier")]
+    fn test_snippet_rejects_bad_name() {
+        js_decoder_snippet(&SnippetOptions::new().with_function_name("x; alert(1)"));
+    }
+}
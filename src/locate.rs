@@ -0,0 +1,103 @@
+//! Mapping decoded data back to the encoded text that carries it.
+
+use core::ops::Range;
+
+use crate::parse_escape;
+
+/// Returns the byte range of the characters in `encoded` that carry the
+/// decoded byte at `byte_offset`.
+///
+/// A decoded byte spans two or three 7-bit chunks, and an escape carries
+/// up to two chunks, so the range covers one to three characters. Returns
+/// `None` if `encoded` decodes to `byte_offset` bytes or fewer.
+///
+/// `encoded` is assumed to be valid; invalid characters count as one chunk
+/// each. Useful for pointing at the text behind a corrupt or mismatched
+/// byte, as the CLI `verify` command does.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{encode, locate_encoded_span};
+///
+/// let encoded = encode(b"\0\0 payload");
+/// // The first byte is chunks 0 and 1, both carried by the leading escape
+/// let span = locate_encoded_span(&encoded, 0).unwrap();
+/// assert_eq!(span, 0..2);
+/// assert!(!encoded[span].is_ascii());
+/// assert_eq!(locate_encoded_span(&encoded, 10), None);
+/// ```
+pub fn locate_encoded_span(encoded: &str, byte_offset: usize) -> Option<Range<usize>> {
+    let first_chunk = byte_offset * 8 / 7;
+    let last_chunk = (byte_offset * 8 + 7) / 7;
+
+    let mut start = None;
+    let mut seen = 0;
+    for (position, character) in encoded.char_indices() {
+        seen += chunks_in(character);
+        if seen > first_chunk && start.is_none() {
+            start = Some(position);
+        }
+        if seen > last_chunk {
+            return Some(start?..position + character.len_utf8());
+        }
+    }
+    None
+}
+
+/// Byte offset of the character carrying chunk `chunk` in `encoded`, or
+/// `encoded.len()` if there is no such character.
+pub(crate) fn chunk_position(encoded: &str, chunk: usize) -> usize {
+    let mut seen = 0;
+    for (position, character) in encoded.char_indices() {
+        seen += chunks_in(character);
+        if seen > chunk {
+            return position;
+        }
+    }
+    encoded.len()
+}
+
+/// Number of 7-bit chunks `character` stands for.
+fn chunks_in(character: char) -> usize {
+    // An escape carries its dangerous chunk plus the next one, except for
+    // the shortened form at the end
+    match parse_escape(character as u32) {
+        Some((Some(_), _)) => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode, encode};
+
+    #[test]
+    fn test_span_carries_the_byte() {
+        let data: Vec<u8> = (0..200)
+            .map(|i| if i % 9 == 0 { 0 } else { i as u8 })
+            .collect();
+        let encoded = encode(&data);
+
+        for offset in 0..data.len() {
+            // Text up to the end of the span decodes the byte, text before
+            // its start does not
+            let span = locate_encoded_span(&encoded, offset).unwrap();
+            let through = decode(&encoded[..span.end]).unwrap();
+            assert!(through.len() > offset && through[..=offset] == data[..=offset]);
+            assert!(decode(&encoded[..span.start]).unwrap().len() <= offset);
+        }
+        assert_eq!(locate_encoded_span(&encoded, data.len()), None);
+    }
+
+    #[test]
+    fn test_span_bounds() {
+        // Byte 1 is bits 8..16: chunks 1 and 2
+        assert_eq!(locate_encoded_span("abcd", 1), Some(1..3));
+        // Byte 6 is bits 48..56: chunks 6 and 7, the last full byte
+        assert_eq!(locate_encoded_span("abcdefgh", 6), Some(6..8));
+        assert_eq!(locate_encoded_span("abcdefgh", 7), None);
+        assert_eq!(locate_encoded_span("", 0), None);
+    }
+}
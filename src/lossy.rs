@@ -0,0 +1,195 @@
+//! Decoding that skips invalid input and reports what it skipped.
+
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+use core::ops::Range;
+
+use crate::{decoded_len, display_escaped, split_char, DecodeError, Profile, WordWriter};
+
+/// Characters of context captured on each side of a skipped span.
+const CONTEXT_CHARS: usize = 8;
+
+/// The input skipped by [`decode_lossy`], for triaging corrupted records.
+///
+/// `Display` writes one line per skipped span, with invisible and
+/// direction-changing characters escaped as by
+/// [`display_escaped`], so the report can go straight into a log.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    /// Skipped spans, in input order.
+    pub skipped: Vec<Skipped>,
+}
+
+impl Report {
+    /// Whether the input decoded without skipping anything.
+    pub fn is_clean(&self) -> bool {
+        self.skipped.is_empty()
+    }
+
+    /// Total length in bytes of the skipped input.
+    pub fn skipped_len(&self) -> usize {
+        self.skipped.iter().map(|skipped| skipped.span.len()).sum()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_clean() {
+            return f.write_str("nothing skipped");
+        }
+        for (i, skipped) in self.skipped.iter().enumerate() {
+            if i > 0 {
+                f.write_str("\n")?;
+            }
+            write!(f, "{skipped}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A run of adjacent invalid characters skipped by [`decode_lossy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Skipped {
+    /// Byte range of the run in the encoded input.
+    pub span: Range<usize>,
+    /// Why the first character of the run was rejected.
+    pub error: DecodeError,
+    /// Up to 8 characters preceding the run.
+    pub before: String,
+    /// Up to 8 characters following the run.
+    pub after: String,
+}
+
+impl fmt::Display for Skipped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "skipped bytes {}..{} ({}) after \"{}\" and before \"{}\"",
+            self.span.start,
+            self.span.end,
+            self.error,
+            display_escaped(&self.before),
+            display_escaped(&self.after),
+        )
+    }
+}
+
+/// Decodes `encoded`, skipping characters that [`decode`](crate::decode)
+/// would reject, and reports every skipped span.
+///
+/// The bits an invalid character stood for are unknown, so the bytes
+/// decoded after a skipped span are misaligned unless the corruption
+/// replaced characters rather than inserting them. Use this to salvage and
+/// triage damaged records, not where the output must be correct.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{decode_lossy, encode};
+///
+/// let mut corrupted = encode(b"some record");
+/// corrupted.insert(4, '\u{2028}');
+///
+/// let (_, report) = decode_lossy(&corrupted);
+/// assert_eq!(report.skipped.len(), 1);
+/// assert_eq!(report.skipped[0].span, 4..7);
+/// assert!(report.to_string().starts_with("skipped bytes 4..7 (invalid character"));
+/// ```
+pub fn decode_lossy(encoded: &str) -> (Vec<u8>, Report) {
+    let mut decoded = Vec::with_capacity(decoded_len(&Profile::STANDARD, encoded));
+    let mut report = Report::default();
+    let mut writer = WordWriter::new(&mut decoded);
+
+    // The error and start of the run of invalid characters being skipped
+    let mut run: Option<(DecodeError, usize)> = None;
+    for (position, character) in encoded.char_indices() {
+        match split_char(&Profile::STANDARD, position, character) {
+            Ok((illegal, payload)) => {
+                if let Some((error, start)) = run.take() {
+                    report
+                        .skipped
+                        .push(skipped(encoded, start..position, error));
+                }
+                if let Some(illegal) = illegal {
+                    writer.push(illegal);
+                }
+                writer.push(payload);
+            }
+            Err(error) => {
+                run.get_or_insert((error, position));
+            }
+        }
+    }
+    if let Some((error, start)) = run {
+        report
+            .skipped
+            .push(skipped(encoded, start..encoded.len(), error));
+    }
+
+    writer.finish();
+    (decoded, report)
+}
+
+/// Describes the skipped `span` of `encoded` with its context.
+fn skipped(encoded: &str, span: Range<usize>, error: DecodeError) -> Skipped {
+    let before = encoded[..span.start].chars().rev().take(CONTEXT_CHARS);
+    let after = encoded[span.end..].chars().take(CONTEXT_CHARS);
+    Skipped {
+        before: before.collect::<Vec<_>>().into_iter().rev().collect(),
+        after: after.collect(),
+        span,
+        error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode, encode};
+
+    #[test]
+    fn test_clean_input_matches_decode() {
+        let data: Vec<u8> = (0..=255).collect();
+        let (decoded, report) = decode_lossy(&encode(&data));
+        assert_eq!(decoded, data);
+        assert!(report.is_clean());
+        assert_eq!(report.to_string(), "nothing skipped");
+    }
+
+    #[test]
+    fn test_replaced_characters_are_skipped() {
+        // Removing the last character of a 7-byte input leaves whole bytes,
+        // so swapping it for garbage loses exactly one byte
+        let encoded = encode(b"0123456789abcd");
+        let mut corrupted = String::from(&encoded[..7]);
+        corrupted.push_str("\u{0800}\u{0680}");
+        corrupted.push_str(&encoded[8..]);
+
+        let (decoded, report) = decode_lossy(&corrupted);
+        assert_eq!(
+            decoded,
+            decode(&format!("{}{}", &encoded[..7], &encoded[8..])).unwrap()
+        );
+        assert_eq!(report.skipped.len(), 1);
+        let skipped = &report.skipped[0];
+        assert_eq!(skipped.span, 7..12);
+        assert!(matches!(
+            skipped.error,
+            DecodeError::InvalidCharacter { position: 7, .. }
+        ));
+        assert_eq!(skipped.before, &encoded[..7]);
+        assert_eq!(skipped.after, &encoded[8..16]);
+        assert_eq!(report.skipped_len(), 5);
+    }
+
+    #[test]
+    fn test_skipped_at_both_ends() {
+        let (decoded, report) = decode_lossy("\u{0680}AB\u{0680}");
+        assert_eq!(decoded, decode("AB").unwrap());
+        let spans: Vec<_> = report.skipped.iter().map(|s| s.span.clone()).collect();
+        assert_eq!(spans, [0..2, 4..6]);
+        assert_eq!(report.skipped[1].before, "\u{0680}AB");
+        assert!(report.skipped[1].after.is_empty());
+        assert_eq!(report.to_string().lines().count(), 2);
+    }
+}
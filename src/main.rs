@@ -0,0 +1,831 @@
+//! Base122 command-line tool.
+//!
+//! Built only with the `cli` feature, so depending on the library never
+//! pulls in the binary:
+//!
+//! ```text
+//! cargo install base122-rs --features cli
+//! base122 encode image.png -o image.b122
+//! base122 decode image.b122 -o image.png
+//! base122 htmlize image.png -o page.html
+//! base122 charset display --regex
+//! base122 extract page.html -o assets
+//! base122 transcode --from standard --to xml payloads.txt -o migrated.txt
+//! ```
+//!
+//! With the `http` feature, `--url` reads the input from the web instead.
+//! With the `clipboard` feature, `--paste` reads it from the system
+//! clipboard and `--copy` puts the output there.
+
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, BufReader, BufWriter, IsTerminal, Read, Write};
+use std::path::Path;
+use std::process::ExitCode;
+
+use base122_rs::{
+    decode, diff_decoded, display_escaped, encode, extract_data_uris, js_decoder_snippet,
+    locate_encoded_span, looks_encoded, verify_roundtrip, CharsetReport, DataUri, Engine,
+    Likelihood, Profile, SnippetOptions,
+};
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+const USAGE: &str = "\
+Usage: base122 <COMMAND> [OPTIONS] [FILE]
+
+Commands:
+  encode    Encode FILE (or stdin) as Base122 text
+  decode    Decode Base122 text from FILE (or stdin)
+  verify    Check that FILE (or stdin) survives an encode/decode round trip
+  htmlize   Write an HTML page that embeds FILE (or stdin) as Base122 and
+            decodes it in the browser
+  extract   Decode every Base122 data URI in the HTML, CSS, or SVG
+            document FILE (or stdin) into files in the directory given
+            by --output (default: current directory)
+  charset   List the characters encoded output can contain, for the
+            profile PROFILE (default: standard)
+  transcode Re-encode the Base122 payloads in FILE (or stdin), one per
+            line, from one profile to another
+  help      Print this message
+
+Options:
+  -o, --output <FILE>    Write to FILE instead of stdout
+      --url <URL>        Read the input from URL instead of FILE
+                         (requires the `http` feature)
+      --format <FORMAT>  Output format of encode: `raw` (default) or
+                         `data-uri`
+      --copy             Copy the output to the clipboard instead of
+                         writing it (requires the `clipboard` feature)
+      --paste            Read the input from the clipboard instead of
+                         FILE (requires the `clipboard` feature)
+      --detect-type      Print the type of the decoded data, detected
+                         from its first bytes, and add the matching
+                         extension to --output if it has none
+      --guard            Refuse to encode input that already looks like
+                         Base122 text, and warn if it might be
+
+Options of charset:
+      --json             Print the ranges as JSON
+      --regex            Print a regular expression character class
+      --terminator       Include the terminator of the profile
+
+Options of transcode:
+      --from <PROFILE>   Profile the input is encoded with (default:
+                         standard)
+      --to <PROFILE>     Profile to encode the output with (default:
+                         standard)
+
+With no FILE, or when FILE is -, read standard input.";
+
+/// Built-in profiles, selectable by name.
+const PROFILES: [&Profile; 7] = [
+    &Profile::STANDARD,
+    &Profile::FILENAME,
+    &Profile::DISPLAY,
+    &Profile::C1_FREE,
+    &Profile::BIDI_SAFE,
+    &Profile::NORMALIZATION_STABLE,
+    &Profile::XML,
+];
+
+/// Looks up a built-in profile by name.
+fn find_profile(name: &str) -> Result<&'static Profile> {
+    PROFILES
+        .into_iter()
+        .find(|profile| profile.name() == name)
+        .ok_or_else(|| {
+            let names: Vec<_> = PROFILES.iter().map(|p| p.name()).collect();
+            format!("unknown profile '{name}' (expected {})", names.join(", ")).into()
+        })
+}
+
+/// Media type of data URIs whose input has no known type.
+const DEFAULT_MEDIA_TYPE: &str = "application/octet-stream";
+
+/// Output format of the encode command.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// The encoded text alone.
+    #[default]
+    Raw,
+    /// `data:<type>;base122,<encoded>`, using the input's media type if
+    /// known.
+    DataUri,
+}
+
+impl Format {
+    fn parse(value: &str) -> Result<Self> {
+        match value {
+            "raw" => Ok(Format::Raw),
+            "data-uri" => Ok(Format::DataUri),
+            other => Err(format!("unknown format '{other}' (expected raw or data-uri)").into()),
+        }
+    }
+}
+
+/// Parsed command-line options shared by all commands.
+#[derive(Debug, Default)]
+struct Options {
+    input: Option<String>,
+    url: Option<String>,
+    output: Option<String>,
+    format: Format,
+    copy: bool,
+    paste: bool,
+    detect_type: bool,
+    guard: bool,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self> {
+        let mut options = Options::default();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-o" | "--output" => {
+                    let path = args.next().ok_or("missing value for --output")?;
+                    options.output = Some(path.clone());
+                }
+                "--url" => {
+                    let url = args.next().ok_or("missing value for --url")?;
+                    options.url = Some(url.clone());
+                }
+                "--format" => {
+                    let format = args.next().ok_or("missing value for --format")?;
+                    options.format = Format::parse(format)?;
+                }
+                "--copy" => options.copy = true,
+                "--paste" => options.paste = true,
+                "--detect-type" => options.detect_type = true,
+                "--guard" => options.guard = true,
+                "-" => options.input = None,
+                flag if flag.starts_with('-') => {
+                    return Err(format!("unknown option '{flag}'").into());
+                }
+                path if options.input.is_none() => options.input = Some(path.to_string()),
+                extra => return Err(format!("unexpected argument '{extra}'").into()),
+            }
+        }
+
+        let inputs = [
+            options.input.is_some(),
+            options.url.is_some(),
+            options.paste,
+        ];
+        if inputs.into_iter().filter(|&given| given).count() > 1 {
+            return Err("give only one of FILE, --url, and --paste".into());
+        }
+        if options.copy && options.output.is_some() {
+            return Err("--copy cannot be combined with --output".into());
+        }
+        Ok(options)
+    }
+
+    fn read_input(&self) -> Result<Vec<u8>> {
+        Ok(self.read_typed_input()?.0)
+    }
+
+    /// Reads the input along with its media type, if known.
+    fn read_typed_input(&self) -> Result<(Vec<u8>, Option<String>)> {
+        if let Some(url) = &self.url {
+            return fetch(url);
+        }
+        if self.paste {
+            return Ok((paste()?.into_bytes(), None));
+        }
+        match &self.input {
+            Some(path) => Ok((fs::read(path).map_err(|e| format!("{path}: {e}"))?, None)),
+            None => {
+                let mut buffer = Vec::new();
+                io::stdin().read_to_end(&mut buffer)?;
+                Ok((buffer, None))
+            }
+        }
+    }
+
+    /// Reads the input along with its media type, guessing the type from
+    /// the file extension if the source does not give one.
+    fn read_media(&self) -> Result<(Vec<u8>, String)> {
+        let (data, media_type) = self.read_typed_input()?;
+        let media_type = media_type
+            .or_else(|| self.input.as_deref().and_then(guess_media_type))
+            .unwrap_or_else(|| DEFAULT_MEDIA_TYPE.to_string());
+        Ok((data, media_type))
+    }
+
+    /// Fails if `--format` was given to a command without formats.
+    fn reject_format(&self, command: &str) -> Result<()> {
+        if self.format != Format::Raw {
+            return Err(format!("--format is not supported by {command}").into());
+        }
+        Ok(())
+    }
+
+    /// Fails if `--detect-type` was given to a command other than decode.
+    fn reject_detect_type(&self, command: &str) -> Result<()> {
+        if self.detect_type {
+            return Err(format!("--detect-type is not supported by {command}").into());
+        }
+        Ok(())
+    }
+
+    /// Fails if `--guard` was given to a command other than encode.
+    fn reject_guard(&self, command: &str) -> Result<()> {
+        if self.guard {
+            return Err(format!("--guard is not supported by {command}").into());
+        }
+        Ok(())
+    }
+
+    /// Whether output goes to a terminal-like stream that expects a final
+    /// line ending.
+    fn writes_stdout(&self) -> bool {
+        self.output.is_none() && !self.copy
+    }
+
+    fn write_output(&self, data: &[u8]) -> Result<()> {
+        if self.copy {
+            let text = String::from_utf8(data.to_vec())
+                .map_err(|_| "output is not text and cannot be copied")?;
+            return copy(text);
+        }
+        match &self.output {
+            Some(path) => fs::write(path, data).map_err(|e| format!("{path}: {e}"))?,
+            None => io::stdout().lock().write_all(data)?,
+        }
+        Ok(())
+    }
+}
+
+/// Downloads `url`, returning the body and its `Content-Type`.
+#[cfg(feature = "http")]
+fn fetch(url: &str) -> Result<(Vec<u8>, Option<String>)> {
+    let mut response = ureq::get(url).call().map_err(|e| format!("{url}: {e}"))?;
+    // `text/html; charset=utf-8` becomes `text/html;charset=utf-8`, as data
+    // URIs have no whitespace
+    let media_type = response
+        .headers()
+        .get("content-type")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| {
+            value
+                .split(';')
+                .map(str::trim)
+                .collect::<Vec<_>>()
+                .join(";")
+        });
+    let body = response
+        .body_mut()
+        .with_config()
+        .limit(u64::MAX)
+        .read_to_vec()
+        .map_err(|e| format!("{url}: {e}"))?;
+    Ok((body, media_type))
+}
+
+#[cfg(not(feature = "http"))]
+fn fetch(_url: &str) -> Result<(Vec<u8>, Option<String>)> {
+    Err("--url requires base122 to be built with the `http` feature".into())
+}
+
+/// Reads the text on the system clipboard.
+#[cfg(feature = "clipboard")]
+fn paste() -> Result<String> {
+    let mut clipboard = arboard::Clipboard::new().map_err(|e| format!("clipboard: {e}"))?;
+    Ok(clipboard
+        .get_text()
+        .map_err(|e| format!("clipboard: {e}"))?)
+}
+
+/// Puts `text` on the system clipboard.
+#[cfg(feature = "clipboard")]
+fn copy(text: String) -> Result<()> {
+    let mut clipboard = arboard::Clipboard::new().map_err(|e| format!("clipboard: {e}"))?;
+    Ok(clipboard
+        .set_text(text)
+        .map_err(|e| format!("clipboard: {e}"))?)
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn paste() -> Result<String> {
+    Err("--paste requires base122 to be built with the `clipboard` feature".into())
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn copy(_text: String) -> Result<()> {
+    Err("--copy requires base122 to be built with the `clipboard` feature".into())
+}
+
+fn run_encode(options: &Options) -> Result<()> {
+    options.reject_detect_type("encode")?;
+    let (data, media_type) = options.read_typed_input()?;
+    if options.guard {
+        match looks_encoded(&data) {
+            Likelihood::Likely => {
+                return Err("input already looks like Base122 text; \
+                     encode without --guard to encode it again"
+                    .into());
+            }
+            Likelihood::Possible => {
+                writeln!(io::stderr(), "warning: input may already be Base122 text")?;
+            }
+            Likelihood::Unlikely => {}
+        }
+    }
+    let mut encoded = match options.format {
+        Format::Raw => encode(&data),
+        Format::DataUri => {
+            let media_type = media_type.as_deref().unwrap_or(DEFAULT_MEDIA_TYPE);
+            DataUri::new(media_type, data).to_string()
+        }
+    };
+    if options.writes_stdout() {
+        encoded.push('\n');
+    }
+    options.write_output(encoded.as_bytes())
+}
+
+fn run_htmlize(options: &Options) -> Result<()> {
+    options.reject_format("htmlize")?;
+    options.reject_detect_type("htmlize")?;
+    options.reject_guard("htmlize")?;
+    let (data, media_type) = options.read_media()?;
+    let name = options
+        .input
+        .as_deref()
+        .or(options.url.as_deref())
+        .and_then(|source| source.rsplit(['/', '\\']).find(|part| !part.is_empty()))
+        .unwrap_or("payload");
+
+    let html = htmlize(&data, &media_type, name);
+    options.write_output(html.as_bytes())
+}
+
+fn run_extract(options: &Options) -> Result<()> {
+    options.reject_format("extract")?;
+    options.reject_detect_type("extract")?;
+    options.reject_guard("extract")?;
+    if options.copy {
+        return Err("--copy is not supported by extract".into());
+    }
+    let document = String::from_utf8(options.read_input()?)
+        .map_err(|e| format!("input is not valid UTF-8: {e}"))?;
+    let directory = Path::new(options.output.as_deref().unwrap_or("."));
+    fs::create_dir_all(directory).map_err(|e| format!("{}: {e}", directory.display()))?;
+
+    let mut stdout = io::stdout().lock();
+    let mut count = 0;
+    for uri in extract_data_uris(&document) {
+        let data = uri
+            .decode()
+            .map_err(|e| format!("data URI at byte {}: {e}", uri.span.start))?;
+        count += 1;
+        let extension = extension_for(uri.media_type).unwrap_or("bin");
+        let path = directory.join(format!("asset-{count}.{extension}"));
+        fs::write(&path, &data).map_err(|e| format!("{}: {e}", path.display()))?;
+        writeln!(stdout, "{}: {} bytes", path.display(), data.len())?;
+    }
+    if count == 0 {
+        return Err("no Base122 data URIs found".into());
+    }
+    Ok(())
+}
+
+fn run_decode(options: &Options) -> Result<()> {
+    options.reject_format("decode")?;
+    options.reject_guard("decode")?;
+    let input = String::from_utf8(options.read_input()?)
+        .map_err(|e| format!("input is not valid UTF-8: {e}"))?;
+
+    // Newlines never appear in encoded output, so a trailing line ending
+    // can only come from the producer or the terminal.
+    let decoded = decode(input.trim_end_matches(['\n', '\r']))?;
+    if options.detect_type {
+        let media_type = sniff_media_type(&decoded);
+        writeln!(
+            io::stderr(),
+            "type: {}",
+            media_type.unwrap_or(DEFAULT_MEDIA_TYPE)
+        )?;
+        let extension = media_type.and_then(extension_for);
+        if let (Some(path), Some(extension)) = (&options.output, extension) {
+            if Path::new(path).extension().is_none() {
+                let path = format!("{path}.{extension}");
+                fs::write(&path, &decoded).map_err(|e| format!("{path}: {e}"))?;
+                writeln!(io::stderr(), "wrote {path}")?;
+                return Ok(());
+            }
+        }
+    }
+    options.write_output(&decoded)
+}
+
+fn run_verify(options: &Options) -> Result<()> {
+    options.reject_format("verify")?;
+    options.reject_detect_type("verify")?;
+    options.reject_guard("verify")?;
+    let input = options.read_input()?;
+    if let Err(mismatch) = verify_roundtrip(&input) {
+        // Without decoded data there is nothing to compare
+        if let Ok(decoded) = decode(&encode(&input)) {
+            let stderr = io::stderr();
+            let color = stderr.is_terminal() && env::var_os("NO_COLOR").is_none();
+            print_diff(&mut stderr.lock(), &input, &decoded, color)?;
+        }
+        return Err(mismatch.into());
+    }
+    writeln!(io::stdout(), "ok: {} bytes round-trip", input.len())?;
+    Ok(())
+}
+
+/// Bytes per row of the side-by-side diff.
+const DIFF_ROW: usize = 8;
+
+/// Prints `expected` and `actual` side by side as hex and characters
+/// around their first difference, with differing bytes highlighted.
+fn print_diff(out: &mut impl Write, expected: &[u8], actual: &[u8], color: bool) -> Result<()> {
+    let Some(report) = diff_decoded(expected, actual) else {
+        return Ok(());
+    };
+
+    // The row with the difference, plus one on either side
+    let first_row = (report.offset / DIFF_ROW).saturating_sub(1) * DIFF_ROW;
+    let end = expected
+        .len()
+        .max(actual.len())
+        .min(first_row + 3 * DIFF_ROW);
+    let width = DIFF_ROW * 4;
+    writeln!(out, "{:8}  {:width$} | actual", "offset", "expected")?;
+    for row in (first_row..end).step_by(DIFF_ROW) {
+        write!(out, "{row:08x}  ")?;
+        write_row(out, expected, actual, row, color)?;
+        write!(out, " | ")?;
+        write_row(out, actual, expected, row, color)?;
+        writeln!(out)?;
+    }
+    let encoded = encode(expected);
+    match locate_encoded_span(&encoded, report.offset) {
+        Some(span) => writeln!(
+            out,
+            "byte {} is encoded at {}..{}: {}",
+            report.offset,
+            span.start,
+            span.end,
+            display_escaped(&encoded[span.clone()])
+        )?,
+        None => writeln!(out, "byte {} is past the encoded data", report.offset)?,
+    }
+    writeln!(out, "encoded context: {}", display_escaped(&report.context))?;
+    Ok(())
+}
+
+/// Writes one row of `side` as hex and characters, highlighting bytes that
+/// differ from `other`.
+fn write_row(
+    out: &mut impl Write,
+    side: &[u8],
+    other: &[u8],
+    row: usize,
+    color: bool,
+) -> Result<()> {
+    let (highlight, reset) = if color {
+        ("\x1b[1;31m", "\x1b[0m")
+    } else {
+        ("", "")
+    };
+    let cells = (row..row + DIFF_ROW).map(|i| (side.get(i), side.get(i) != other.get(i)));
+
+    for (byte, differs) in cells.clone() {
+        let (start, end) = if differs {
+            (highlight, reset)
+        } else {
+            ("", "")
+        };
+        match byte {
+            Some(byte) => write!(out, "{start}{byte:02x}{end} ")?,
+            None => write!(out, "{start}--{end} ")?,
+        }
+    }
+    for (byte, differs) in cells {
+        let (start, end) = if differs {
+            (highlight, reset)
+        } else {
+            ("", "")
+        };
+        let c = match byte {
+            Some(&byte) if byte.is_ascii_graphic() || byte == b' ' => byte as char,
+            Some(_) => '.',
+            None => ' ',
+        };
+        write!(out, "{start}{c}{end}")?;
+    }
+    Ok(())
+}
+
+/// Media types of common web file extensions.
+const MEDIA_TYPES: &[(&str, &str)] = &[
+    ("avif", "image/avif"),
+    ("gif", "image/gif"),
+    ("ico", "image/x-icon"),
+    ("jpeg", "image/jpeg"),
+    ("jpg", "image/jpeg"),
+    ("png", "image/png"),
+    ("svg", "image/svg+xml"),
+    ("webp", "image/webp"),
+    ("mp3", "audio/mpeg"),
+    ("ogg", "audio/ogg"),
+    ("wav", "audio/wav"),
+    ("mp4", "video/mp4"),
+    ("webm", "video/webm"),
+    ("woff2", "font/woff2"),
+    ("pdf", "application/pdf"),
+    ("txt", "text/plain"),
+];
+
+/// Guesses the media type of the file at `path` from its extension.
+fn guess_media_type(path: &str) -> Option<String> {
+    let (_, extension) = path.rsplit_once('.')?;
+    MEDIA_TYPES
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(extension))
+        .map(|(_, media_type)| media_type.to_string())
+}
+
+/// Preferred file extension for `media_type`, ignoring any parameters.
+fn extension_for(media_type: &str) -> Option<&'static str> {
+    let media_type = media_type.split(';').next()?.trim();
+    MEDIA_TYPES
+        .iter()
+        .find(|(_, known)| known.eq_ignore_ascii_case(media_type))
+        .map(|&(extension, _)| extension)
+}
+
+/// Leading bytes of the types in [`MEDIA_TYPES`] that have a fixed
+/// signature, with `?` matching any byte.
+const SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xFF\xD8\xFF", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"RIFF????WEBP", "image/webp"),
+    (b"RIFF????WAVE", "audio/wav"),
+    (b"????ftypavif", "image/avif"),
+    (b"????ftyp", "video/mp4"),
+    (b"\0\0\x01\0", "image/x-icon"),
+    (b"ID3", "audio/mpeg"),
+    (b"\xFF\xFB", "audio/mpeg"),
+    (b"OggS", "audio/ogg"),
+    (b"\x1A\x45\xDF\xA3", "video/webm"),
+    (b"wOF2", "font/woff2"),
+    (b"%PDF-", "application/pdf"),
+];
+
+/// Detects the media type of `data` from its first bytes, falling back to
+/// SVG for XML documents with an `<svg` element and to plain text for
+/// other UTF-8 without control characters.
+fn sniff_media_type(data: &[u8]) -> Option<&'static str> {
+    let signature = SIGNATURES.iter().find(|(signature, _)| {
+        data.len() >= signature.len()
+            && signature
+                .iter()
+                .zip(data)
+                .all(|(&expected, &actual)| expected == b'?' || expected == actual)
+    });
+    if let Some(&(_, media_type)) = signature {
+        return Some(media_type);
+    }
+
+    let text = std::str::from_utf8(data).ok()?;
+    let start = text.trim_start_matches('\u{FEFF}').trim_start();
+    if start.starts_with("<svg") || (start.starts_with("<?xml") && text.contains("<svg")) {
+        Some("image/svg+xml")
+    } else if !text.is_empty() && !text.contains(|c: char| c.is_control() && !c.is_whitespace()) {
+        Some("text/plain")
+    } else {
+        None
+    }
+}
+
+/// Returns a standalone HTML page that decodes `data` from an inline
+/// Base122 string and shows it, or offers it for download if the browser
+/// cannot display `media_type`.
+fn htmlize(data: &[u8], media_type: &str, name: &str) -> String {
+    let (element, attribute) = match media_type.split('/').next() {
+        Some("image") => (
+            format!("<img id=\"payload\" alt=\"{}\">", escape_html(name)),
+            "src",
+        ),
+        Some("video") => ("<video id=\"payload\" controls></video>".to_string(), "src"),
+        Some("audio") => ("<audio id=\"payload\" controls></audio>".to_string(), "src"),
+        _ => {
+            let name = escape_html(name);
+            (
+                format!("<a id=\"payload\" download=\"{name}\">Download {name}</a>"),
+                "href",
+            )
+        }
+    };
+    let decoder = js_decoder_snippet(&SnippetOptions::new());
+    format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{title}</title>
+</head>
+<body>
+{element}
+<script>
+{decoder}
+var data = base122Decode(\"{payload}\");
+var blob = new Blob([data], {{ type: \"{media_type}\" }});
+document.getElementById(\"payload\").{attribute} = URL.createObjectURL(blob);
+</script>
+</body>
+</html>
+",
+        title = escape_html(name),
+        payload = escape_js(&encode(data)),
+        media_type = escape_js(media_type),
+    )
+}
+
+/// Escapes `text` for HTML text and double-quoted attributes.
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes `text` for a double-quoted JavaScript string inside a
+/// `<script>` element.
+///
+/// Encoded output never contains quotes, backslashes, or line breaks, but
+/// may contain `</script>`, so `<` is always escaped.
+fn escape_js(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '<' => escaped.push_str("\\x3c"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn run_transcode(args: &[String]) -> Result<()> {
+    let (mut from, mut to) = (&Profile::STANDARD, &Profile::STANDARD);
+    let mut rest = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--from" => from = find_profile(args.next().ok_or("missing value for --from")?)?,
+            "--to" => to = find_profile(args.next().ok_or("missing value for --to")?)?,
+            _ => rest.push(arg.clone()),
+        }
+    }
+    let options = Options::parse(&rest)?;
+    options.reject_format("transcode")?;
+    options.reject_detect_type("transcode")?;
+    options.reject_guard("transcode")?;
+    if options.copy {
+        return Err("--copy is not supported by transcode".into());
+    }
+
+    // One payload per line, read and written as it goes; newlines are
+    // dangerous in every profile, so they only ever separate payloads.
+    let input: Box<dyn BufRead> = match &options.input {
+        _ if options.url.is_some() || options.paste => {
+            Box::new(io::Cursor::new(options.read_input()?))
+        }
+        Some(path) => Box::new(BufReader::new(
+            fs::File::open(path).map_err(|e| format!("{path}: {e}"))?,
+        )),
+        None => Box::new(io::stdin().lock()),
+    };
+    let mut output: Box<dyn Write> = match &options.output {
+        Some(path) => Box::new(BufWriter::new(
+            fs::File::create(path).map_err(|e| format!("{path}: {e}"))?,
+        )),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+
+    let (from, to) = (
+        Engine::new().with_profile(from),
+        Engine::new().with_profile(to),
+    );
+    for (number, line) in input.lines().enumerate() {
+        let line = line?;
+        let decoded = from
+            .decode(&line)
+            .map_err(|e| format!("line {}: {e}", number + 1))?;
+        writeln!(output, "{}", to.encode(decoded))?;
+    }
+    output.flush()?;
+    Ok(())
+}
+
+/// Output format of the charset command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Listing {
+    /// Code point ranges as `U+0001-U+0009, ...`.
+    Ranges,
+    /// A JSON object, see [`charset_json`].
+    Json,
+    /// A regex character class, see [`CharsetReport::regex_class`].
+    Regex,
+}
+
+fn run_charset(args: &[String]) -> Result<()> {
+    let mut profile = &Profile::STANDARD;
+    let mut engine = Engine::new();
+    let mut listing = Listing::Ranges;
+
+    let mut name = None;
+    for arg in args {
+        match arg.as_str() {
+            "--json" => listing = Listing::Json,
+            "--regex" => listing = Listing::Regex,
+            "--terminator" => engine = engine.with_terminator(true),
+            flag if flag.starts_with('-') => {
+                return Err(format!("unknown option '{flag}'").into());
+            }
+            given if name.is_none() => {
+                profile = find_profile(given)?;
+                name = Some(given);
+            }
+            extra => return Err(format!("unexpected argument '{extra}'").into()),
+        }
+    }
+
+    let report = engine.with_profile(profile).output_charset();
+    let text = match listing {
+        Listing::Ranges => report.to_string(),
+        Listing::Json => charset_json(profile.name(), &report),
+        Listing::Regex => report.regex_class(),
+    };
+    writeln!(io::stdout(), "{text}")?;
+    Ok(())
+}
+
+/// `{"profile": ..., "count": ..., "ranges": [[first, last], ...],
+/// "terminator": ...}` with code points as numbers.
+fn charset_json(profile: &str, report: &CharsetReport) -> String {
+    let ranges: Vec<String> = report
+        .ranges()
+        .iter()
+        .map(|range| format!("[{}, {}]", *range.start() as u32, *range.end() as u32))
+        .collect();
+    let terminator = report
+        .terminator()
+        .map_or("null".to_string(), |c| (c as u32).to_string());
+    format!(
+        "{{\"profile\": \"{profile}\", \"count\": {}, \"ranges\": [{}], \"terminator\": {terminator}}}",
+        report.len(),
+        ranges.join(", "),
+    )
+}
+
+fn run(args: &[String]) -> Result<()> {
+    let Some((command, rest)) = args.split_first() else {
+        return Err(USAGE.into());
+    };
+
+    match command.as_str() {
+        "encode" => run_encode(&Options::parse(rest)?),
+        "decode" => run_decode(&Options::parse(rest)?),
+        "verify" => run_verify(&Options::parse(rest)?),
+        "htmlize" => run_htmlize(&Options::parse(rest)?),
+        "extract" => run_extract(&Options::parse(rest)?),
+        "charset" => run_charset(rest),
+        "transcode" => run_transcode(rest),
+        "help" | "-h" | "--help" => Ok(writeln!(io::stdout(), "{USAGE}")?),
+        other => Err(format!("unknown command '{other}'\n\n{USAGE}").into()),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("base122: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
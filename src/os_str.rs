@@ -0,0 +1,203 @@
+//! Encoding operating system strings and paths.
+//!
+//! File names are not always valid Unicode: Unix allows any bytes except
+//! NUL and `/`, and Windows allows unpaired UTF-16 surrogates. These
+//! helpers encode the platform's own representation, so every name
+//! round-trips exactly on the platform that produced it.
+//!
+//! The encoded bytes are the same on every platform for names that are
+//! valid Unicode: the raw bytes on Unix, and
+//! [WTF-8](https://simonsapin.github.io/wtf-8/) on Windows, which is UTF-8
+//! extended to unpaired surrogates.
+
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+use crate::{decode, encode, DecodeError};
+
+/// Encodes an operating system string, such as a file name or an
+/// environment variable, losslessly.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::ffi::OsStr;
+/// use base122_rs::{decode_os_str, encode_os_str};
+///
+/// let name = OsStr::new("report\n2024.txt");
+/// let encoded = encode_os_str(name);
+/// assert_eq!(decode_os_str(&encoded).unwrap(), name);
+/// ```
+pub fn encode_os_str(os_str: &OsStr) -> String {
+    encode(&os_bytes(os_str))
+}
+
+/// Decodes a string produced by [`encode_os_str`].
+///
+/// On Unix any decoded bytes are accepted. On Windows the bytes are read
+/// as WTF-8; byte sequences that are not, which can only come from a
+/// non-Unicode name encoded on Unix, become U+FFFD REPLACEMENT CHARACTER.
+///
+/// # Errors
+///
+/// Fails like [`decode`] on invalid Base122 input.
+pub fn decode_os_str(encoded: &str) -> Result<OsString, DecodeError> {
+    decode(encoded).map(os_string)
+}
+
+/// Encodes a path losslessly. See [`encode_os_str`].
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use base122_rs::{decode_path, encode_path};
+///
+/// let path = Path::new("photos/\"holiday\" & more/0001.jpg");
+/// assert_eq!(decode_path(&encode_path(path)).unwrap(), path);
+/// ```
+pub fn encode_path(path: &Path) -> String {
+    encode_os_str(path.as_os_str())
+}
+
+/// Decodes a path produced by [`encode_path`]. See [`decode_os_str`].
+///
+/// # Errors
+///
+/// Fails like [`decode`] on invalid Base122 input.
+pub fn decode_path(encoded: &str) -> Result<PathBuf, DecodeError> {
+    decode_os_str(encoded).map(PathBuf::from)
+}
+
+#[cfg(unix)]
+fn os_bytes(os_str: &OsStr) -> std::borrow::Cow<'_, [u8]> {
+    use std::os::unix::ffi::OsStrExt;
+
+    os_str.as_bytes().into()
+}
+
+#[cfg(unix)]
+fn os_string(bytes: Vec<u8>) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+
+    OsString::from_vec(bytes)
+}
+
+#[cfg(windows)]
+fn os_bytes(os_str: &OsStr) -> std::borrow::Cow<'_, [u8]> {
+    use std::os::windows::ffi::OsStrExt;
+
+    wtf8_encode(os_str.encode_wide()).into()
+}
+
+#[cfg(windows)]
+fn os_string(bytes: Vec<u8>) -> OsString {
+    use std::os::windows::ffi::OsStringExt;
+
+    OsString::from_wide(&wtf8_decode(&bytes))
+}
+
+// Elsewhere (e.g. WASI and wasm32) operating system strings are Unicode
+#[cfg(not(any(unix, windows)))]
+fn os_bytes(os_str: &OsStr) -> std::borrow::Cow<'_, [u8]> {
+    match os_str.to_string_lossy() {
+        std::borrow::Cow::Borrowed(s) => s.as_bytes().into(),
+        std::borrow::Cow::Owned(s) => s.into_bytes().into(),
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn os_string(bytes: Vec<u8>) -> OsString {
+    String::from_utf8_lossy(&bytes).into_owned().into()
+}
+
+/// Converts UTF-16 code units, possibly with unpaired surrogates, to WTF-8.
+#[cfg(any(windows, test))]
+fn wtf8_encode(units: impl Iterator<Item = u16>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for unit in char::decode_utf16(units) {
+        match unit {
+            Ok(c) => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+            // Surrogates take the 3-byte form UTF-8 reserves for them
+            Err(e) => {
+                let s = e.unpaired_surrogate();
+                bytes.extend_from_slice(&[
+                    0xE0 | (s >> 12) as u8,
+                    0x80 | (s >> 6 & 0x3F) as u8,
+                    0x80 | (s & 0x3F) as u8,
+                ]);
+            }
+        }
+    }
+    bytes
+}
+
+/// Converts WTF-8 to UTF-16 code units, replacing invalid sequences with
+/// U+FFFD.
+#[cfg(any(windows, test))]
+fn wtf8_decode(mut bytes: &[u8]) -> Vec<u16> {
+    let mut units = Vec::with_capacity(bytes.len());
+    loop {
+        let error = match core::str::from_utf8(bytes) {
+            Ok(valid) => {
+                units.extend(valid.encode_utf16());
+                return units;
+            }
+            Err(error) => error,
+        };
+
+        let (valid, rest) = bytes.split_at(error.valid_up_to());
+        // Checked by `from_utf8`
+        units.extend(
+            core::str::from_utf8(valid)
+                .unwrap_or_default()
+                .encode_utf16(),
+        );
+        bytes = match *rest {
+            // An encoded surrogate, U+D800 to U+DFFF
+            [0xED, second @ 0xA0..=0xBF, third @ 0x80..=0xBF, ref after @ ..] => {
+                units.push(0xD000 | u16::from(second & 0x3F) << 6 | u16::from(third & 0x3F));
+                after
+            }
+            _ => {
+                units.push(char::REPLACEMENT_CHARACTER as u16);
+                &rest[error.error_len().unwrap_or(rest.len())..]
+            }
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_non_unicode_unix_name_round_trips() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let name = OsStr::from_bytes(b"caf\xE9\0\n\xFF.txt");
+        assert!(name.to_str().is_none());
+        assert_eq!(decode_os_str(&encode_os_str(name)).unwrap(), name);
+
+        // Unicode names encode the same as their UTF-8
+        assert_eq!(encode_path(Path::new("a/b")), encode(b"a/b"));
+    }
+
+    #[test]
+    fn test_wtf8_round_trips_unpaired_surrogates() {
+        let units: Vec<u16> = "x\u{1F600}é"
+            .encode_utf16()
+            .chain([0xDFFF, 0xD800, 0x41])
+            .collect();
+        let bytes = wtf8_encode(units.iter().copied());
+        assert_eq!(&bytes[..7], "x\u{1F600}é".as_bytes());
+        assert_eq!(&bytes[7..], b"\xED\xBF\xBF\xED\xA0\x80A");
+        assert_eq!(wtf8_decode(&bytes), units);
+    }
+
+    #[test]
+    fn test_wtf8_decode_replaces_invalid_bytes() {
+        assert_eq!(wtf8_decode(b"a\xFFb\xE2\x82"), [0x61, 0xFFFD, 0x62, 0xFFFD]);
+    }
+}
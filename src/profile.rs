@@ -0,0 +1,708 @@
+//! Escaping profiles.
+//!
+//! A profile decides which 7-bit chunks are dangerous and which code points
+//! stand in for them. Every profile keeps the shape of the original scheme:
+//! a safe chunk is emitted as the ASCII character with the same value, and a
+//! dangerous chunk is folded together with the following chunk into a
+//! single escape character. What changes between profiles is the dangerous
+//! set and the blocks of 128 code points the escapes are drawn from.
+//!
+//! For escape index `i` and payload `p`, the escape is the code point
+//! `blocks[i] + p`. A dangerous chunk at the very end of the input has no
+//! payload and is emitted as `shortened + chunk` instead. Blocks may lie
+//! anywhere in the Basic Multilingual Plane, so escapes are 2 or 3 bytes of
+//! UTF-8 but always a single UTF-16 code unit.
+
+use core::fmt;
+
+use crate::{ILLEGALS, SAFE};
+
+/// Entry in [`Profile::decode`] for the shortened-escape block.
+const SHORT: u8 = 0xFE;
+
+/// Entry in [`Profile::decode`] for blocks the encoder never uses.
+const UNUSED: u8 = 0xFF;
+
+/// Number of 128-code-point blocks in the Basic Multilingual Plane.
+const BLOCKS: usize = 0x10000 >> 7;
+
+/// Flag in [`Profile::pairs`] entries for characters the encoder emits.
+pub(crate) const PAIR_VALID: u16 = 0x8000;
+
+/// Flag in [`Profile::pairs`] entries for escapes of a dangerous value,
+/// which is stored in bits 7–13.
+pub(crate) const PAIR_DANGEROUS: u16 = 0x4000;
+
+/// A set of dangerous characters and the escapes that replace them.
+///
+/// Profiles are selected on an [`Engine`](crate::Engine) with
+/// [`with_profile`](crate::Engine::with_profile). Output produced with one
+/// profile can only be decoded with the same profile. Besides the built-in
+/// profiles below, [`Profile::new`] defines custom ones.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{Engine, Profile};
+///
+/// const NAMES: Engine = Engine::new().with_profile(&Profile::FILENAME);
+///
+/// let name = NAMES.encode(b"\0report/2024:Q1");
+/// assert!(!name.contains(['/', '\\', ':', '\0']));
+/// assert_eq!(NAMES.decode(&name).unwrap(), b"\0report/2024:Q1");
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct Profile {
+    name: &'static str,
+    /// Dangerous 7-bit values, in escape-index order.
+    dangerous: &'static [u8],
+    /// First code point of the escape block for each dangerous value.
+    blocks: &'static [u16],
+    /// First code point of the block used for shortened escapes.
+    shortened: u16,
+    /// Character reserved to mark the end of a message.
+    terminator: char,
+    /// Highest code point the decoder attributes to this profile; anything
+    /// above it is reported as a foreign character rather than a bad escape.
+    max_char: u32,
+    /// Chunk value to escape index, or `SAFE`.
+    index: [u8; 128],
+    /// Code point block (`c >> 7`) to escape index, `SHORT`, or `UNUSED`.
+    decode: [u8; BLOCKS],
+    /// Split of every 2-byte character, indexed by its 11-bit code point:
+    /// `PAIR_VALID`, `PAIR_DANGEROUS` and the dangerous value, and the
+    /// payload, or 0 if the encoder never emits it.
+    pairs: [u16; 0x800],
+}
+
+impl Profile {
+    /// The original Base122 escaping, compatible with other implementations.
+    ///
+    /// Escapes `\0`, `\n`, `\r`, `"`, `&`, and `\` with 2-byte characters in
+    /// U+0080–U+07FF. This is the profile used by [`encode`](crate::encode)
+    /// and [`decode`](crate::decode).
+    pub const STANDARD: Profile = Profile::new(
+        "standard",
+        &ILLEGALS,
+        &[0x080, 0x180, 0x280, 0x380, 0x480, 0x580],
+        0x780,
+        '\u{0680}',
+    );
+
+    /// Output usable as a file name on Linux, macOS, and Windows.
+    ///
+    /// Besides control characters and DEL, escapes every character that is
+    /// reserved in paths on some platform (`"*/:<>?\|`), space and `.`
+    /// (which Windows strips from the end of names), and the uppercase
+    /// letters `A`–`Z`, so two different payloads never differ only in case
+    /// on case-insensitive file systems. Escapes are CJK ideographs
+    /// (U+4E00–U+71FF), which have no case, survive macOS Unicode
+    /// normalization, and are 3 bytes of UTF-8 each.
+    ///
+    /// Output is about 35% longer in bytes than [`STANDARD`](Self::STANDARD)
+    /// for random data. Inputs of up to 140 bytes always fit the common
+    /// 255-byte name limit. Reserved Windows device names such as `con`
+    /// are not avoided.
+    pub const FILENAME: Profile = Profile::new(
+        "filename",
+        &FILENAME_DANGEROUS,
+        &cjk_blocks::<{ FILENAME_DANGEROUS.len() }>(),
+        0x4E00 + 0x80 * FILENAME_DANGEROUS.len() as u16,
+        '\u{7180}',
+    );
+
+    /// Output that renders cleanly wherever it is displayed.
+    ///
+    /// Escapes the same characters as [`STANDARD`](Self::STANDARD), but
+    /// draws escapes only from Latin Extended-A/B, IPA and spacing modifier
+    /// letters, Cyrillic, and Armenian (U+0100–U+02FF and U+0400–U+057F).
+    /// None of them is a combining mark that would merge with a neighbor,
+    /// a control or invisible format character, or a right-to-left letter,
+    /// so encoded strings line up in terminals, logs, and UI widgets.
+    /// Three payload values map to unassigned Armenian code points, which
+    /// fonts show as a single replacement box.
+    ///
+    /// Escapes are 2 bytes, so output has the same length as with the
+    /// standard profile.
+    pub const DISPLAY: Profile = Profile::new(
+        "display",
+        &ILLEGALS,
+        &[0x100, 0x180, 0x200, 0x280, 0x400, 0x500],
+        0x480,
+        '\u{0481}',
+    );
+
+    /// The standard layout with escapes moved out of the C1 control block.
+    ///
+    /// The standard profile escapes `\0` with characters from U+0080–U+00FF,
+    /// a quarter of which are C1 controls (U+0080–U+009F) that some
+    /// terminals interpret and some legacy systems strip or re-encode. This
+    /// profile moves that one block to U+0100–U+017F and is otherwise
+    /// identical to [`STANDARD`](Self::STANDARD): only escapes of `\0`
+    /// differ, and the output has the same length. [`DISPLAY`](Self::DISPLAY)
+    /// also avoids C1 controls.
+    pub const C1_FREE: Profile = Profile::new(
+        "c1-free",
+        &ILLEGALS,
+        &[0x100, 0x180, 0x280, 0x380, 0x480, 0x580],
+        0x780,
+        '\u{0680}',
+    );
+
+    /// Output that never changes the direction of surrounding text.
+    ///
+    /// The standard profile draws escapes from the Hebrew, Arabic, and
+    /// Thaana blocks, whose right-to-left letters reorder the text around
+    /// them when displayed, and from the C1 controls, which include the
+    /// bidi paragraph separator U+0085. This profile escapes the same
+    /// characters but only emits code points whose bidi class is
+    /// left-to-right, neutral, or non-spacing, keeping logs and diff views
+    /// in order. Escapes are 2 bytes, so the output length is unchanged.
+    pub const BIDI_SAFE: Profile = Profile::new(
+        "bidi-safe",
+        &ILLEGALS,
+        &[0x100, 0x180, 0x280, 0x380, 0x480, 0x500],
+        0x200,
+        '\u{0400}',
+    );
+
+    /// Output that survives NFC and NFKC normalization unchanged.
+    ///
+    /// Databases, web frameworks, and some file systems normalize strings on
+    /// ingest. The standard profile is not stable under either form: U+0387
+    /// normalizes to U+00B7, and adjacent Hebrew and Arabic combining marks
+    /// are reordered. This profile escapes the same characters using only
+    /// code points that are their own NFC and NFKC forms and never compose
+    /// or reorder with a neighbor: Latin Extended-B, Cyrillic, and Armenian
+    /// for the common cases, and CJK ideographs (3 bytes each) for the rest.
+    ///
+    /// Output is about 2% longer in bytes than with the standard profile for
+    /// random data; the number of characters is unchanged.
+    pub const NORMALIZATION_STABLE: Profile = Profile::new(
+        "normalization-stable",
+        &ILLEGALS,
+        &[0x200, 0x400, 0x480, 0x4E00, 0x4E80, 0x4F00],
+        0x500,
+        '\u{0501}',
+    );
+
+    /// Output that can be placed in XML and HTML attribute values and text
+    /// as is.
+    ///
+    /// Escapes `"`, `&`, `'`, `<`, `>`, DEL, and every C0 control: XML 1.0
+    /// forbids most controls outright, XML 1.1 only allows DEL as a
+    /// character reference, and parsers replace tab, line feed, and
+    /// carriage return in attribute values with spaces. With `>` escaped,
+    /// `]]>` never appears, which is not allowed in text. Escapes avoid the
+    /// C1 controls; 13 of them are 2-byte characters in U+0100–U+077F and
+    /// the rest CJK ideographs, 3 bytes each. Suited to SVG `href` values,
+    /// see [`embed_in_svg_attribute`](crate::embed_in_svg_attribute).
+    ///
+    /// Output is about 17% longer in bytes than with the standard profile
+    /// for random data.
+    pub const XML: Profile = Profile::new("xml", &XML_DANGEROUS, &XML_BLOCKS, 0x780, '\u{07A0}');
+
+    /// Defines a custom profile.
+    ///
+    /// `dangerous` lists the 7-bit values to escape, and `blocks` the first
+    /// code point of the escape block for each of them, in the same order.
+    /// `shortened` is the block for a dangerous chunk at the very end of the
+    /// input, and `terminator` the character that ends a message when the
+    /// engine's terminator option is enabled. A dangerous chunk `i` with
+    /// payload `p` is escaped as the code point `blocks[i] + p`, and a
+    /// dangerous chunk `c` at the end of the input as `shortened + c`.
+    ///
+    /// Call this in a `const` item so that mistakes are compile errors.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless the profile can be decoded unambiguously:
+    ///
+    /// - there is one block per dangerous value, and fewer than 254 of them,
+    /// - dangerous values are 7-bit and distinct,
+    /// - blocks start at a multiple of 128 in U+0080–U+FFFF, avoid the
+    ///   surrogates, and do not overlap,
+    /// - the terminator is in U+0080–U+FFFF and is never emitted as an
+    ///   escape.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use base122_rs::{Engine, Profile};
+    ///
+    /// /// Standard Base122 that also keeps `<` and `'` out of the output.
+    /// const HTML: Profile = Profile::new(
+    ///     "html",
+    ///     &[0, 10, 13, 34, 38, 92, 60, 39],
+    ///     &[0x080, 0x180, 0x280, 0x380, 0x480, 0x580, 0x880, 0x980],
+    ///     0x780,
+    ///     '\u{0680}',
+    /// );
+    ///
+    /// let engine = Engine::new().with_profile(&HTML);
+    /// let encoded = engine.encode(b"<tag attr='x'>");
+    /// assert!(!encoded.contains(['<', '\'']));
+    /// assert_eq!(engine.decode(&encoded).unwrap(), b"<tag attr='x'>");
+    /// ```
+    ///
+    /// An ambiguous profile does not compile:
+    ///
+    /// ```compile_fail
+    /// use base122_rs::Profile;
+    ///
+    /// // Both escapes use the block at U+0080
+    /// const BROKEN: Profile = Profile::new("broken", &[0, 10], &[0x080, 0x080], 0x780, '\u{0680}');
+    /// ```
+    pub const fn new(
+        name: &'static str,
+        dangerous: &'static [u8],
+        blocks: &'static [u16],
+        shortened: u16,
+        terminator: char,
+    ) -> Profile {
+        assert!(
+            dangerous.len() == blocks.len(),
+            "one block per dangerous value"
+        );
+        assert!(
+            dangerous.len() < SHORT as usize,
+            "too many dangerous values"
+        );
+
+        let mut index = [SAFE; 128];
+        let mut i = 0;
+        while i < dangerous.len() {
+            let value = dangerous[i] as usize;
+            assert!(value < 128, "dangerous values are 7-bit");
+            assert!(index[value] == SAFE, "duplicate dangerous value");
+            index[value] = i as u8;
+            i += 1;
+        }
+
+        let mut decode = [UNUSED; BLOCKS];
+        let mut max_char = 0x7FF;
+        let mut i = 0;
+        while i <= blocks.len() {
+            let (start, entry) = if i < blocks.len() {
+                (blocks[i], i as u8)
+            } else {
+                (shortened, SHORT)
+            };
+            assert!(start >= 0x80 && start % 0x80 == 0, "blocks are 128-aligned");
+            assert!(start < 0xD800 || start >= 0xE000, "blocks avoid surrogates");
+            assert!(decode[start as usize >> 7] == UNUSED, "blocks overlap");
+            decode[start as usize >> 7] = entry;
+            if start >= 0x800 {
+                max_char = 0xFFFF;
+            }
+            i += 1;
+        }
+
+        let t = terminator as u32;
+        assert!(t >= 0x80 && t <= 0xFFFF, "terminator is a BMP escape");
+        let entry = decode[t as usize >> 7];
+        assert!(
+            entry == UNUSED || (entry == SHORT && index[t as usize & 0x7F] == SAFE),
+            "terminator must never be emitted"
+        );
+
+        let mut pairs = [0; 0x800];
+        let mut c = 0x80;
+        while c < 0x800 {
+            let payload = c as u16 & 0x7F;
+            pairs[c] = match decode[c >> 7] {
+                _ if c as u32 == t => 0,
+                UNUSED => 0,
+                SHORT => PAIR_VALID | payload,
+                entry => {
+                    let dangerous = dangerous[entry as usize] as u16;
+                    PAIR_VALID | PAIR_DANGEROUS | dangerous << 7 | payload
+                }
+            };
+            c += 1;
+        }
+
+        Profile {
+            name,
+            dangerous,
+            blocks,
+            shortened,
+            terminator,
+            max_char,
+            index,
+            decode,
+            pairs,
+        }
+    }
+
+    /// Short lowercase name of the profile, e.g. `"standard"`.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The 7-bit values this profile escapes.
+    pub const fn dangerous(&self) -> &'static [u8] {
+        self.dangerous
+    }
+
+    /// The character that ends a message when the engine's terminator
+    /// option is enabled.
+    pub const fn terminator(&self) -> char {
+        self.terminator
+    }
+
+    /// Escape index of `chunk`, or `None` if it is safe.
+    #[inline]
+    pub(crate) fn escape_index(&self, chunk: u8) -> Option<usize> {
+        let index = self.index[chunk as usize];
+        (index != SAFE).then_some(index as usize)
+    }
+
+    /// Code point of the escape for dangerous value `index` carrying
+    /// `payload`.
+    #[inline]
+    pub(crate) fn escape(&self, index: usize, payload: u8) -> u32 {
+        self.blocks[index] as u32 + payload as u32
+    }
+
+    /// Code point of the shortened escape for a final dangerous `chunk`.
+    #[inline]
+    pub(crate) fn shortened(&self, chunk: u8) -> u32 {
+        self.shortened as u32 + chunk as u32
+    }
+
+    /// The blocks of 128 code points holding escapes, as `c >> 7` and the
+    /// dangerous value they stand for (`None` for the shortened block).
+    #[cfg(feature = "alloc")]
+    pub(crate) fn escape_blocks(&self) -> impl Iterator<Item = (usize, Option<u8>)> + '_ {
+        self.decode
+            .iter()
+            .enumerate()
+            .filter(|&(_, &entry)| entry != UNUSED)
+            .map(|(block, &entry)| {
+                (
+                    block,
+                    (entry != SHORT).then(|| self.dangerous[entry as usize]),
+                )
+            })
+    }
+
+    /// Upper bound on the encoded length in bytes of `len` input bytes,
+    /// saturating at `usize::MAX`.
+    pub(crate) fn max_encoded_len(&self, len: usize) -> usize {
+        // `(len * 8).div_ceil(7)` without overflowing
+        let chunks = len.saturating_add(len.div_ceil(7));
+        if self.max_char > 0x7FF {
+            // Each 3-byte escape stands for at most two chunks
+            chunks.saturating_add(chunks.div_ceil(2)).saturating_add(1)
+        } else {
+            chunks.saturating_add(1)
+        }
+    }
+
+    /// Splits a non-ASCII character into the chunks it stands for.
+    ///
+    /// Returns the dangerous value (`None` for a shortened escape) and the
+    /// payload, or `None` if the encoder never emits `c`.
+    #[inline]
+    pub(crate) fn split(&self, c: char) -> Option<(Option<u8>, u8)> {
+        if c == self.terminator {
+            return None;
+        }
+        let c = c as u32;
+        let entry = *self.decode.get(c as usize >> 7)?;
+        let payload = (c & 0x7F) as u8;
+        match entry {
+            UNUSED => None,
+            SHORT => Some((None, payload)),
+            index => Some((Some(self.dangerous[index as usize]), payload)),
+        }
+    }
+
+    /// Table entry for the 2-byte UTF-8 sequence `lead`, `continuation`:
+    /// [`PAIR_VALID`] if the encoder emits it, [`PAIR_DANGEROUS`] and the
+    /// dangerous value in bits 7–13 for a full escape, and the payload in
+    /// the low 7 bits.
+    #[inline]
+    pub(crate) fn pair(&self, lead: u8, continuation: u8) -> u16 {
+        self.pairs[(lead as usize & 0x1F) << 6 | continuation as usize & 0x3F]
+    }
+
+    /// Whether `c` lies outside the range of characters this profile uses,
+    /// as opposed to being a malformed escape.
+    #[inline]
+    pub(crate) fn is_foreign(&self, c: char) -> bool {
+        c as u32 > self.max_char
+    }
+}
+
+impl fmt::Debug for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Profile")
+            .field("name", &self.name)
+            .field("dangerous", &self.dangerous)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Dangerous set of [`Profile::FILENAME`].
+const FILENAME_DANGEROUS: [u8; 70] = {
+    const RESERVED: &[u8] = b"\"*/:<>?\\| .\x7F";
+    let mut set = [0u8; 70];
+    let mut len = 0;
+    while len < 32 {
+        set[len] = len as u8;
+        len += 1;
+    }
+    let mut i = 0;
+    while i < RESERVED.len() {
+        set[len] = RESERVED[i];
+        len += 1;
+        i += 1;
+    }
+    let mut letter = b'A';
+    while letter <= b'Z' {
+        set[len] = letter;
+        len += 1;
+        letter += 1;
+    }
+    assert!(len == set.len());
+    set
+};
+
+/// Dangerous set of [`Profile::XML`].
+const XML_DANGEROUS: [u8; 38] = {
+    const MARKUP: &[u8] = b"\"&'<>\x7F";
+    let mut set = [0u8; 38];
+    let mut len = 0;
+    while len < 32 {
+        set[len] = len as u8;
+        len += 1;
+    }
+    let mut i = 0;
+    while i < MARKUP.len() {
+        set[len] = MARKUP[i];
+        len += 1;
+        i += 1;
+    }
+    set
+};
+
+/// Escape blocks of [`Profile::XML`]: U+0100–U+077F, then CJK ideographs.
+const XML_BLOCKS: [u16; 38] = {
+    let cjk = cjk_blocks::<38>();
+    let mut blocks = [0u16; 38];
+    let mut i = 0;
+    while i < blocks.len() {
+        blocks[i] = if i < 13 {
+            0x100 + 0x80 * i as u16
+        } else {
+            cjk[i - 13]
+        };
+        i += 1;
+    }
+    blocks
+};
+
+/// `N` consecutive escape blocks starting at U+4E00, the first CJK
+/// Unified Ideograph.
+const fn cjk_blocks<const N: usize>() -> [u16; N] {
+    let mut blocks = [0u16; N];
+    let mut i = 0;
+    while i < N {
+        blocks[i] = 0x4E00 + 0x80 * i as u16;
+        i += 1;
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::{generate, Profile as Shape};
+    use crate::{emit_escape, encode, parse_escape, Engine, ILLEGAL_INDEX};
+    use unicode_normalization::char::is_combining_mark;
+
+    /// Every character `profile` can emit besides ASCII.
+    fn escapes(profile: &Profile) -> Vec<char> {
+        let full = (0..profile.dangerous.len()).flat_map(|i| (0..128).map(move |p| (i, p)));
+        full.map(|(i, p)| profile.escape(i, p))
+            .chain(profile.dangerous.iter().map(|&d| profile.shortened(d)))
+            .map(|code| char::from_u32(code).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_standard_matches_reference_tables() {
+        let profile = &Profile::STANDARD;
+        for chunk in 0..128u8 {
+            assert_eq!(profile.index[chunk as usize], ILLEGAL_INDEX[chunk as usize]);
+            if let Some(index) = profile.escape_index(chunk) {
+                let code = profile.escape(index, 0x55);
+                let [b1, b2] = emit_escape(index as u8, 0x55);
+                let c = char::from_u32(code).unwrap();
+                assert_eq!(c.encode_utf8(&mut [0; 4]).as_bytes(), [b1, b2]);
+            }
+        }
+        for c in (0x80..0x800).filter_map(char::from_u32) {
+            assert_eq!(profile.split(c), parse_escape(c as u32), "{c:?}");
+        }
+    }
+
+    #[test]
+    fn test_pair_table_matches_split() {
+        let profiles = [
+            &Profile::STANDARD,
+            &Profile::FILENAME,
+            &Profile::DISPLAY,
+            &Profile::C1_FREE,
+            &Profile::BIDI_SAFE,
+            &Profile::NORMALIZATION_STABLE,
+            &Profile::XML,
+        ];
+        for profile in profiles {
+            for c in (0x80..0x800).filter_map(char::from_u32) {
+                let mut utf8 = [0; 2];
+                c.encode_utf8(&mut utf8);
+                let expected = profile.split(c).filter(|_| !profile.is_foreign(c));
+                let entry = profile.pair(utf8[0], utf8[1]);
+                let split = (entry & PAIR_VALID != 0).then(|| {
+                    let dangerous =
+                        (entry & PAIR_DANGEROUS != 0).then_some((entry >> 7) as u8 & 0x7F);
+                    (dangerous, entry as u8 & 0x7F)
+                });
+                assert_eq!(split, expected, "{c:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_display_profile_has_no_combining_marks() {
+        let display = escapes(&Profile::DISPLAY);
+        assert!(display.iter().all(|&c| !is_combining_mark(c)));
+        assert!(display
+            .iter()
+            .all(|&c| !c.is_control() && !c.is_whitespace()));
+        assert!(!is_combining_mark(Profile::DISPLAY.terminator()));
+
+        // The standard profile does emit combining marks (e.g. U+0591)
+        assert!(escapes(&Profile::STANDARD)
+            .into_iter()
+            .any(is_combining_mark));
+
+        let engine = Engine::new().with_profile(&Profile::DISPLAY);
+        let data = generate(Shape::Image, 3000, 4);
+        let encoded = engine.encode(&data);
+        assert_eq!(encoded.len(), encode(&data).len());
+        assert_eq!(engine.decode(&encoded).unwrap(), data);
+    }
+
+    #[test]
+    fn test_c1_free_profile() {
+        let is_c1 = |c: &char| ('\u{80}'..='\u{9F}').contains(c);
+        assert!(!escapes(&Profile::C1_FREE).iter().any(is_c1));
+        assert!(!escapes(&Profile::DISPLAY).iter().any(is_c1));
+        assert!(escapes(&Profile::STANDARD).iter().any(is_c1));
+
+        // Only escapes of a null chunk differ from the standard output
+        let engine = Engine::new().with_profile(&Profile::C1_FREE);
+        let data = b"no nulls here\n\"&\\";
+        assert_eq!(engine.encode(data), encode(data));
+        let nulls = [0u8; 16];
+        assert_ne!(engine.encode(nulls), encode(&nulls));
+        assert_eq!(engine.decode(engine.encode(nulls)).unwrap(), nulls);
+    }
+
+    #[test]
+    fn test_bidi_safe_profile() {
+        use unicode_bidi::{bidi_class, BidiClass};
+
+        let directional = |c: &char| {
+            !matches!(
+                bidi_class(*c),
+                BidiClass::L | BidiClass::ON | BidiClass::NSM
+            )
+        };
+        assert!(!escapes(&Profile::BIDI_SAFE).iter().any(directional));
+        assert!(!directional(&Profile::BIDI_SAFE.terminator()));
+        assert!(escapes(&Profile::STANDARD).iter().any(directional));
+
+        let engine = Engine::new().with_profile(&Profile::BIDI_SAFE);
+        let data = generate(Shape::Random, 3000, 2);
+        assert_eq!(engine.decode(engine.encode(&data)).unwrap(), data);
+    }
+
+    #[test]
+    fn test_normalization_stable_profile() {
+        use unicode_normalization::UnicodeNormalization;
+
+        let stable = |s: &str| s.nfc().eq(s.chars()) && s.nfkc().eq(s.chars());
+        let profile = &Profile::NORMALIZATION_STABLE;
+        let engine = Engine::new().with_profile(profile);
+
+        // Every escape next to every other, in both orders
+        let all: String = escapes(profile).into_iter().collect();
+        let reversed: String = all.chars().rev().collect();
+        assert!(stable(&all) && stable(&reversed));
+
+        for shape in [Shape::Random, Shape::Text, Shape::Image] {
+            let data = generate(shape, 5000, 6);
+            let encoded = engine.encode(&data);
+            assert!(stable(&encoded));
+            assert_eq!(engine.decode(&encoded).unwrap(), data);
+        }
+
+        let standard: String = escapes(&Profile::STANDARD).into_iter().collect();
+        assert!(!stable(&standard));
+    }
+
+    #[test]
+    fn test_xml_profile() {
+        // Characters XML 1.0 allows in attribute values as is
+        let xml_safe = |c: &char| match c {
+            '\t' | '\n' | '\r' | '"' | '&' | '\'' | '<' | '>' => false,
+            '\u{80}'..='\u{9F}' => false,
+            c => !c.is_control(),
+        };
+        assert!(escapes(&Profile::XML).iter().all(xml_safe));
+        assert!(xml_safe(&Profile::XML.terminator()));
+
+        let engine = Engine::new().with_profile(&Profile::XML);
+        for shape in [Shape::Random, Shape::Text, Shape::Image] {
+            let data = generate(shape, 3000, 5);
+            let encoded = engine.encode(&data);
+            assert!(encoded.chars().all(|c| xml_safe(&c)));
+            assert_eq!(engine.decode(&encoded).unwrap(), data);
+        }
+
+        // Input whose chunks spell `]]>]]>]]`, which is not allowed in text
+        let word = b"]]>]]>]]"
+            .iter()
+            .fold(0u64, |word, &c| word << 7 | c as u64);
+        let data = &word.to_be_bytes()[1..];
+        assert_eq!(encode(data), "]]>]]>]]");
+        let encoded = engine.encode(data);
+        assert!(!encoded.contains("]]>"));
+        assert_eq!(engine.decode(&encoded).unwrap(), data);
+    }
+
+    #[test]
+    fn test_filename_profile() {
+        let engine = Engine::new().with_profile(&Profile::FILENAME);
+        for shape in [Shape::Random, Shape::Text, Shape::Image] {
+            let data = generate(shape, 2000, 9);
+            let name = engine.encode(&data);
+            assert!(name.chars().all(|c| {
+                c.is_ascii_lowercase()
+                    || c.is_ascii_digit()
+                    || "!#$%&'()+,-;=@[]^_`{}~".contains(c)
+                    || ('\u{4E00}'..'\u{7200}').contains(&c)
+            }));
+            assert_eq!(engine.decode(&name).unwrap(), data);
+        }
+
+        // Worst case: every chunk is dangerous and escaped in 3 bytes
+        assert!(engine.encode([0; 140]).len() <= 255);
+        assert_ne!(engine.encode(b"Name"), encode(b"Name"));
+    }
+}
@@ -0,0 +1,184 @@
+//! Splitting encoded data across QR codes.
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+
+#[cfg(feature = "alloc")]
+use crate::encode;
+
+/// Error correction level of a QR code.
+///
+/// Higher levels survive more damage to the printed symbol but leave less
+/// room for data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum QrEcLevel {
+    /// Recovers about 7% of the symbol.
+    L,
+    /// Recovers about 15% of the symbol.
+    M,
+    /// Recovers about 25% of the symbol.
+    Q,
+    /// Recovers about 30% of the symbol.
+    H,
+}
+
+/// Data codewords of each QR code version, by error correction level, from
+/// ISO/IEC 18004 table 7.
+const DATA_CODEWORDS: [[u16; 4]; 40] = [
+    [19, 16, 13, 9],
+    [34, 28, 22, 16],
+    [55, 44, 34, 26],
+    [80, 64, 48, 36],
+    [108, 86, 62, 46],
+    [136, 108, 76, 60],
+    [156, 124, 88, 66],
+    [194, 154, 110, 86],
+    [232, 182, 132, 100],
+    [274, 216, 154, 122],
+    [324, 254, 180, 140],
+    [370, 290, 206, 158],
+    [428, 334, 244, 180],
+    [461, 365, 261, 197],
+    [523, 415, 295, 223],
+    [589, 453, 325, 253],
+    [647, 507, 367, 283],
+    [721, 563, 397, 313],
+    [795, 627, 445, 341],
+    [861, 669, 485, 385],
+    [932, 714, 512, 406],
+    [1006, 782, 568, 442],
+    [1094, 860, 614, 464],
+    [1174, 914, 664, 514],
+    [1276, 1000, 718, 538],
+    [1370, 1062, 754, 596],
+    [1468, 1128, 808, 628],
+    [1531, 1193, 871, 661],
+    [1631, 1267, 911, 701],
+    [1735, 1373, 985, 745],
+    [1843, 1455, 1033, 793],
+    [1955, 1541, 1115, 845],
+    [2071, 1631, 1171, 901],
+    [2191, 1725, 1231, 961],
+    [2306, 1812, 1286, 986],
+    [2434, 1914, 1354, 1054],
+    [2566, 1992, 1426, 1096],
+    [2702, 2102, 1502, 1142],
+    [2812, 2216, 1582, 1222],
+    [2956, 2334, 1666, 1276],
+];
+
+/// The most bytes a single byte-mode segment holds in a QR code of
+/// `version` (1 to 40) at `ec_level`, or `None` for a version out of range.
+///
+/// The segment header takes 4 bits for the mode and 8 bits for the length,
+/// or 16 from version 10 on. A generator that also writes an ECI header,
+/// such as one declaring UTF-8, leaves one or two bytes less.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{qr_byte_capacity, QrEcLevel};
+///
+/// assert_eq!(qr_byte_capacity(1, QrEcLevel::L), Some(17));
+/// assert_eq!(qr_byte_capacity(40, QrEcLevel::H), Some(1273));
+/// assert_eq!(qr_byte_capacity(41, QrEcLevel::L), None);
+/// ```
+pub const fn qr_byte_capacity(version: u8, ec_level: QrEcLevel) -> Option<usize> {
+    if version < 1 || version > 40 {
+        return None;
+    }
+    let codewords = DATA_CODEWORDS[version as usize - 1][ec_level as usize] as usize;
+    let header_bits = if version < 10 { 4 + 8 } else { 4 + 16 };
+    Some((codewords * 8 - header_bits) / 8)
+}
+
+/// Encodes `data` as a sequence of segments that each fit in one QR code
+/// of `version` at `ec_level`, in byte mode.
+///
+/// Every segment is a complete encoding of its own slice of `data`, so
+/// each printed code can be scanned and decoded on its own, and decoding
+/// the segments in order and concatenating the results gives `data`. A
+/// single segment fits in one code; longer data becomes a sequence of
+/// codes that the reader must keep in order, such as by numbering them on
+/// the page. Empty data gives no segments.
+///
+/// A segment's UTF-8 is at most [`qr_byte_capacity`] bytes long. Byte mode
+/// is ISO-8859-1 by default, so a scanner may show a segment with escapes
+/// as mojibake; take the raw bytes of the scan and read them as UTF-8.
+///
+/// # Panics
+///
+/// Panics if `version` is not between 1 and 40.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{decode, qr_byte_capacity, to_qr_segments, QrEcLevel};
+///
+/// let data: Vec<u8> = (0..=255).cycle().take(2000).collect();
+/// let segments = to_qr_segments(&data, 10, QrEcLevel::M);
+/// assert_eq!(segments.len(), 11);
+///
+/// let mut decoded = Vec::new();
+/// for segment in &segments {
+///     assert!(segment.len() <= qr_byte_capacity(10, QrEcLevel::M).unwrap());
+///     decoded.extend(decode(segment).unwrap());
+/// }
+/// assert_eq!(decoded, data);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn to_qr_segments(data: &[u8], version: u8, ec_level: QrEcLevel) -> Vec<String> {
+    let Some(capacity) = qr_byte_capacity(version, ec_level) else {
+        panic!("QR code versions are 1 to 40, not {version}");
+    };
+    // `n` bytes encode to at most `ceil(8n / 7) + 1` bytes of UTF-8
+    let fragment_len = (capacity - 1) * 7 / 8;
+    data.chunks(fragment_len).map(encode).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode;
+
+    #[test]
+    fn test_capacities() {
+        // ISO/IEC 18004 table 7, byte mode
+        let expected = [
+            (1, [17, 14, 11, 7]),
+            (9, [230, 180, 130, 98]),
+            (10, [271, 213, 151, 119]),
+            (27, [1465, 1125, 805, 625]),
+            (40, [2953, 2331, 1663, 1273]),
+        ];
+        let levels = [QrEcLevel::L, QrEcLevel::M, QrEcLevel::Q, QrEcLevel::H];
+        for (version, capacities) in expected {
+            for (level, capacity) in levels.into_iter().zip(capacities) {
+                assert_eq!(qr_byte_capacity(version, level), Some(capacity));
+            }
+        }
+        assert_eq!(qr_byte_capacity(0, QrEcLevel::L), None);
+    }
+
+    #[test]
+    fn test_segments_fit() {
+        // Zeros escape every chunk, the longest encoding per byte
+        let inputs: [Vec<u8>; 3] = [
+            vec![0; 5000],
+            (0..=255).cycle().take(5000).collect(),
+            vec![b'a'; 5000],
+        ];
+        for data in &inputs {
+            for (version, level) in [(1, QrEcLevel::H), (5, QrEcLevel::Q), (40, QrEcLevel::L)] {
+                let capacity = qr_byte_capacity(version, level).unwrap();
+                let segments = to_qr_segments(data, version, level);
+                assert!(segments.iter().all(|s| s.len() <= capacity));
+
+                let decoded: Vec<u8> = segments.iter().flat_map(|s| decode(s).unwrap()).collect();
+                assert_eq!(&decoded, data);
+            }
+        }
+        assert!(to_qr_segments(&[], 1, QrEcLevel::L).is_empty());
+    }
+}
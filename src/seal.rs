@@ -0,0 +1,148 @@
+//! Authenticated encryption around the encoder.
+//!
+//! [`seal`] encrypts a payload with XChaCha20-Poly1305 under a 256-bit key
+//! and a fresh random nonce, then Base122-encodes the result; [`open`]
+//! reverses both steps and rejects anything that was not sealed with the
+//! same key. This covers the common case of putting secrets into cookies,
+//! URLs, or HTML without hand-rolling the cryptography around the encoder.
+//!
+//! The sealed bytes are the 24-byte nonce, then the ciphertext, then the
+//! 16-byte authentication tag, so a sealed string is about 46 characters
+//! longer than the plain encoding of the same payload.
+
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+
+use crate::{decode, encode, DecodeError};
+
+/// Length in bytes of a sealing key.
+pub const KEY_LEN: usize = 32;
+
+/// Length of the random nonce prepended to the ciphertext.
+const NONCE_LEN: usize = 24;
+
+/// Length of the authentication tag appended to the ciphertext.
+const TAG_LEN: usize = 16;
+
+/// Error returned by [`open`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum OpenError {
+    /// The input is not valid Base122.
+    Decode(DecodeError),
+    /// The decoded input is too short to hold a nonce and tag.
+    Truncated,
+    /// The input was not sealed with this key, or has been modified.
+    Authentication,
+}
+
+impl fmt::Display for OpenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpenError::Decode(error) => write!(f, "decode failed: {error}"),
+            OpenError::Truncated => f.write_str("sealed data is truncated"),
+            OpenError::Authentication => f.write_str("sealed data failed authentication"),
+        }
+    }
+}
+
+impl core::error::Error for OpenError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            OpenError::Decode(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<DecodeError> for OpenError {
+    fn from(error: DecodeError) -> Self {
+        OpenError::Decode(error)
+    }
+}
+
+/// Encrypts and authenticates `data` under `key`, then encodes it.
+///
+/// Every call draws a new random nonce from the operating system, so
+/// sealing the same payload twice gives different strings.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::seal::{open, seal};
+///
+/// let key = [7u8; 32];
+/// let cookie = seal(b"user=42", &key);
+///
+/// assert_eq!(open(&cookie, &key).unwrap(), b"user=42");
+/// assert!(open(&cookie, &[8u8; 32]).is_err());
+/// ```
+pub fn seal(data: impl AsRef<[u8]>, key: &[u8; KEY_LEN]) -> String {
+    let cipher = XChaCha20Poly1305::new(key.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let mut sealed = Vec::with_capacity(NONCE_LEN + data.as_ref().len() + TAG_LEN);
+    sealed.extend_from_slice(&nonce);
+    // Encryption only fails for inputs beyond the cipher's ~256 GiB limit
+    let ciphertext = cipher
+        .encrypt(&nonce, data.as_ref())
+        .expect("payload too large to seal");
+    sealed.extend_from_slice(&ciphertext);
+
+    encode(&sealed)
+}
+
+/// Decodes `sealed`, verifies it, and decrypts it with `key`.
+///
+/// # Errors
+///
+/// Returns [`OpenError::Authentication`] if `sealed` was produced with a
+/// different key or has been altered in any way; no partially decrypted
+/// data is ever returned.
+pub fn open(sealed: impl AsRef<str>, key: &[u8; KEY_LEN]) -> Result<Vec<u8>, OpenError> {
+    let decoded = decode(sealed.as_ref())?;
+    if decoded.len() < NONCE_LEN + TAG_LEN {
+        return Err(OpenError::Truncated);
+    }
+
+    let (nonce, ciphertext) = decoded.split_at(NONCE_LEN);
+    let cipher = XChaCha20Poly1305::new(key.into());
+    cipher
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| OpenError::Authentication)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; KEY_LEN] = [0x42; KEY_LEN];
+
+    #[test]
+    fn test_seal_roundtrip() {
+        for data in [&b""[..], b"secret", &[0; 100]] {
+            let sealed = seal(data, &KEY);
+            assert_eq!(open(&sealed, &KEY).unwrap(), data);
+            assert_ne!(sealed, seal(data, &KEY), "nonces must differ");
+        }
+    }
+
+    #[test]
+    fn test_open_rejects_tampering() {
+        let sealed = seal(b"secret", &KEY);
+        let mut bytes = decode(&sealed).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+
+        assert_eq!(open(encode(&bytes), &KEY), Err(OpenError::Authentication));
+        assert_eq!(open(encode(&[1, 2, 3]), &KEY), Err(OpenError::Truncated));
+        assert!(matches!(
+            open("\u{0800}", &KEY),
+            Err(OpenError::Decode(DecodeError::InvalidCharacter { .. }))
+        ));
+    }
+}
@@ -0,0 +1,132 @@
+//! Redaction of secret payloads in logs.
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use crate::{decode, encode, DecodeError};
+
+/// A value whose [`Debug`](fmt::Debug) and [`Display`](fmt::Display)
+/// output is redacted.
+///
+/// Wrapping an encoded credential keeps it out of logs, panic messages,
+/// and `{:?}` dumps of the structs that hold it; reading it takes an
+/// explicit call to [`expose_secret`](Secret::expose_secret). With the
+/// `zeroize` feature, `Secret` implements `Zeroize`, so
+/// `Zeroizing<Secret<String>>` also wipes the value when dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{decode_secret, encode_secret};
+///
+/// let token = encode_secret(b"hunter2");
+/// assert_eq!(format!("{token:?}"), "Secret([REDACTED])");
+/// assert_eq!(token.to_string(), "[REDACTED]");
+///
+/// let key = decode_secret(token.expose_secret()).unwrap();
+/// assert_eq!(key.expose_secret(), b"hunter2");
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct Secret<T>(T);
+
+impl<T> Secret<T> {
+    /// Wraps `value`.
+    pub const fn new(value: T) -> Self {
+        Secret(value)
+    }
+
+    /// Returns the wrapped value.
+    pub fn expose_secret(&self) -> &T {
+        &self.0
+    }
+
+    /// Unwraps the value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Secret(value)
+    }
+}
+
+impl<T> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret([REDACTED])")
+    }
+}
+
+impl<T> fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<T: zeroize::Zeroize> zeroize::Zeroize for Secret<T> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Encodes `data` into a [`Secret`] string.
+#[cfg(feature = "alloc")]
+pub fn encode_secret(data: impl AsRef<[u8]>) -> Secret<String> {
+    Secret(encode(data.as_ref()))
+}
+
+/// Decodes `encoded` into [`Secret`] bytes.
+///
+/// # Errors
+///
+/// Fails like [`decode`] on invalid input.
+#[cfg(feature = "alloc")]
+pub fn decode_secret(encoded: impl AsRef<str>) -> Result<Secret<Vec<u8>>, DecodeError> {
+    decode(encoded.as_ref()).map(Secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    struct Config {
+        user: &'static str,
+        token: Secret<String>,
+    }
+
+    #[test]
+    fn test_debug_redacts() {
+        let config = Config {
+            user: "admin",
+            token: encode_secret(b"hunter2"),
+        };
+        let debug = format!("{config:?}");
+        assert!(debug.contains("admin"));
+        assert!(!debug.contains(config.token.expose_secret().as_str()));
+        assert_eq!(format!("{:#?}", config.token), "Secret([REDACTED])");
+    }
+
+    #[test]
+    fn test_secret_roundtrip() {
+        let token = encode_secret([0, 1, 2, 255]);
+        let bytes = decode_secret(token.expose_secret()).unwrap();
+        assert_eq!(bytes.into_inner(), [0, 1, 2, 255]);
+        assert!(decode_secret("\u{0800}").is_err());
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_zeroize() {
+        use zeroize::Zeroize;
+
+        let mut token = encode_secret(b"hunter2");
+        token.zeroize();
+        assert!(token.expose_secret().is_empty());
+    }
+}
@@ -0,0 +1,162 @@
+//! Ed25519 signatures around the encoder.
+//!
+//! [`encode_signed`] signs a payload with an Ed25519 signing key and
+//! encodes the signature together with the payload; [`decode_signed`]
+//! checks the signature against the sender's public key before returning
+//! anything. Unlike a checksum, which anyone can recompute after editing a
+//! payload, this lets the recipient of a pasted string check who produced
+//! it.
+//!
+//! The signed bytes are the 64-byte signature followed by the payload, so a
+//! signed string is about 73 characters longer than the plain encoding of
+//! the same payload. The payload itself is not encrypted; see
+//! [`seal`](crate::seal) for confidentiality.
+
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use ed25519_dalek::{Signature, Signer};
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
+
+use crate::{decode, encode, DecodeError};
+
+/// Length of the signature prepended to the payload.
+const SIGNATURE_LEN: usize = Signature::BYTE_SIZE;
+
+/// Error returned by [`decode_signed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum VerifyError {
+    /// The input is not valid Base122.
+    Decode(DecodeError),
+    /// The decoded input is too short to hold a signature.
+    Truncated,
+    /// The input was not signed by the holder of this key, or has been
+    /// modified.
+    Signature,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::Decode(error) => write!(f, "decode failed: {error}"),
+            VerifyError::Truncated => f.write_str("signed data is truncated"),
+            VerifyError::Signature => f.write_str("signature verification failed"),
+        }
+    }
+}
+
+impl core::error::Error for VerifyError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            VerifyError::Decode(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<DecodeError> for VerifyError {
+    fn from(error: DecodeError) -> Self {
+        VerifyError::Decode(error)
+    }
+}
+
+/// Signs `data` with `signing_key` and encodes the signature and payload.
+///
+/// Ed25519 signatures are deterministic, so signing the same payload with
+/// the same key always gives the same string.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::sign::{decode_signed, encode_signed, SigningKey};
+///
+/// let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+/// let signed = encode_signed(b"release 1.2.0", &signing_key);
+///
+/// let verifying_key = signing_key.verifying_key();
+/// assert_eq!(decode_signed(&signed, &verifying_key).unwrap(), b"release 1.2.0");
+///
+/// let impostor = SigningKey::from_bytes(&[8u8; 32]).verifying_key();
+/// assert!(decode_signed(&signed, &impostor).is_err());
+/// ```
+pub fn encode_signed(data: impl AsRef<[u8]>, signing_key: &SigningKey) -> String {
+    let data = data.as_ref();
+    let signature = signing_key.sign(data);
+
+    let mut signed = Vec::with_capacity(SIGNATURE_LEN + data.len());
+    signed.extend_from_slice(&signature.to_bytes());
+    signed.extend_from_slice(data);
+    encode(&signed)
+}
+
+/// Decodes `signed` and returns its payload if the signature was made by
+/// the holder of `verifying_key`.
+///
+/// # Errors
+///
+/// Returns [`VerifyError::Signature`] if `signed` was signed with a
+/// different key or has been altered in any way; an unverified payload is
+/// never returned.
+pub fn decode_signed(
+    signed: impl AsRef<str>,
+    verifying_key: &VerifyingKey,
+) -> Result<Vec<u8>, VerifyError> {
+    let mut decoded = decode(signed.as_ref())?;
+    if decoded.len() < SIGNATURE_LEN {
+        return Err(VerifyError::Truncated);
+    }
+
+    let (signature, payload) = decoded.split_at(SIGNATURE_LEN);
+    let signature = Signature::from_slice(signature).map_err(|_| VerifyError::Signature)?;
+    // Strict verification also rejects malleable signatures
+    verifying_key
+        .verify_strict(payload, &signature)
+        .map_err(|_| VerifyError::Signature)?;
+
+    decoded.drain(..SIGNATURE_LEN);
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; 32] = [0x42; 32];
+
+    #[test]
+    fn test_signed_roundtrip() {
+        let signing_key = SigningKey::from_bytes(&KEY);
+        for data in [&b""[..], b"origin", &[0; 100]] {
+            let signed = encode_signed(data, &signing_key);
+            assert_eq!(
+                decode_signed(&signed, &signing_key.verifying_key()).unwrap(),
+                data
+            );
+            assert_eq!(signed, encode_signed(data, &signing_key));
+        }
+    }
+
+    #[test]
+    fn test_decode_signed_rejects_tampering() {
+        let signing_key = SigningKey::from_bytes(&KEY);
+        let verifying_key = signing_key.verifying_key();
+        let mut bytes = decode(&encode_signed(b"origin", &signing_key)).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+
+        assert_eq!(
+            decode_signed(encode(&bytes), &verifying_key),
+            Err(VerifyError::Signature)
+        );
+        assert_eq!(
+            decode_signed(encode(&[1, 2, 3]), &verifying_key),
+            Err(VerifyError::Truncated)
+        );
+        assert!(matches!(
+            decode_signed("\u{0800}", &verifying_key),
+            Err(VerifyError::Decode(DecodeError::InvalidCharacter { .. }))
+        ));
+    }
+}
@@ -0,0 +1,210 @@
+//! Encoding and decoding into caller-provided buffers.
+
+use core::fmt;
+
+use crate::{decoded_len, split_char, Accumulator, Base122Ext, DecodeError, Engine, Profile};
+
+/// Error returned when encoded output does not fit the buffer provided.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BufferTooSmall {
+    required: usize,
+    capacity: usize,
+}
+
+impl BufferTooSmall {
+    /// Length in bytes the complete output needs.
+    pub fn required(&self) -> usize {
+        self.required
+    }
+
+    /// Length in bytes of the buffer provided.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl fmt::Display for BufferTooSmall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "output needs {} bytes but the buffer holds {}",
+            self.required, self.capacity
+        )
+    }
+}
+
+impl core::error::Error for BufferTooSmall {}
+
+/// Encodes `data` into the start of `out` and returns the number of bytes
+/// written.
+///
+/// Needs no allocator, for embedded targets and FFI callers that own the
+/// output memory. The bytes written are valid UTF-8 and identical to
+/// [`encode`](crate::encode) of `data`. Size `out` with
+/// [`Engine::max_encoded_len`] for inputs of a known maximum length, or
+/// exactly with [`Engine::encoded_len`].
+///
+/// # Errors
+///
+/// Returns [`BufferTooSmall`] if the encoded text is longer than `out`.
+/// The exact length is checked first, so `out` is then left untouched and
+/// the error reports the length needed.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::encode_to_slice;
+///
+/// let mut out = [0u8; 32];
+/// let written = encode_to_slice(b"\0sensor\n", &mut out).unwrap();
+/// assert_eq!(&out[..written], base122_rs::encode(b"\0sensor\n").as_bytes());
+///
+/// let error = encode_to_slice(b"\0sensor\n", &mut out[..4]).unwrap_err();
+/// assert_eq!((error.required(), error.capacity()), (11, 4));
+/// ```
+pub fn encode_to_slice(data: &[u8], out: &mut [u8]) -> Result<usize, BufferTooSmall> {
+    let required = Engine::STANDARD.encoded_len(data);
+    if required > out.len() {
+        return Err(BufferTooSmall {
+            required,
+            capacity: out.len(),
+        });
+    }
+
+    let mut written = 0;
+    for c in data.iter().copied().base122_encode() {
+        written += c.encode_utf8(&mut out[written..]).len();
+    }
+    Ok(written)
+}
+
+/// Decodes `encoded` into the start of `out` and returns the number of
+/// bytes written.
+///
+/// Needs no allocator, so `out` can be a stack buffer or arena memory. A
+/// buffer of `encoded.len() * 7 / 8` bytes is always large enough.
+///
+/// # Errors
+///
+/// Fails like [`decode`](crate::decode) on invalid input, and with
+/// [`DecodeError::OutputTooSmall`] if `out` fills up. That error carries
+/// the length the complete output needs, so the caller can retry with a
+/// buffer of that size. The rest of the input is still checked first, so
+/// invalid input always reports the problem with the input. In either
+/// case the contents of `out` are unspecified.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{decode_to_slice, encode, DecodeError};
+///
+/// let encoded = encode(b"\0sensor\n");
+/// let mut out = [0u8; 32];
+/// let written = decode_to_slice(&encoded, &mut out)?;
+/// assert_eq!(&out[..written], b"\0sensor\n");
+///
+/// assert!(matches!(
+///     decode_to_slice(&encoded, &mut out[..4]),
+///     Err(DecodeError::OutputTooSmall { required: 8, .. })
+/// ));
+/// # Ok::<(), DecodeError>(())
+/// ```
+pub fn decode_to_slice(encoded: &str, out: &mut [u8]) -> Result<usize, DecodeError> {
+    let mut accumulator = Accumulator::default();
+    let mut written = 0;
+
+    for (position, character) in encoded.char_indices() {
+        let (illegal, payload) = split_char(&Profile::STANDARD, position, character)?;
+        for chunk in illegal.into_iter().chain([payload]) {
+            let Some(byte) = accumulator.push7(chunk) else {
+                continue;
+            };
+            let Some(slot) = out.get_mut(written) else {
+                return Err(output_too_small(encoded, position));
+            };
+            *slot = byte;
+            written += 1;
+        }
+    }
+    Ok(written)
+}
+
+/// The error for output that filled up while decoding the character at
+/// `position` of `encoded`.
+///
+/// The rest of `encoded` is checked first: an error in it takes precedence,
+/// since no buffer would be large enough. Otherwise
+/// [`DecodeError::OutputTooSmall`] reports the length of the whole output.
+pub(crate) fn output_too_small(encoded: &str, position: usize) -> DecodeError {
+    for (offset, character) in encoded[position..].char_indices() {
+        if let Err(error) = split_char(&Profile::STANDARD, position + offset, character) {
+            return error;
+        }
+    }
+    DecodeError::OutputTooSmall {
+        position,
+        required: decoded_len(&Profile::STANDARD, encoded),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::{generate, Profile};
+    use crate::{decode, encode};
+
+    #[test]
+    fn test_encode_to_slice_matches_encode() {
+        let mut out = [0; 256];
+        for len in 0..150 {
+            let data = generate(Profile::DangerousDensity(0.5), len, len as u64);
+            let written = encode_to_slice(&data, &mut out).unwrap();
+            assert_eq!(&out[..written], encode(&data).as_bytes(), "len {len}");
+        }
+    }
+
+    #[test]
+    fn test_encode_to_slice_exact_fit() {
+        let data = [0, 10, 13, 34, 38, 92, 0];
+        let required = encode(&data).len();
+        let mut out = [0xFF; 32];
+        assert_eq!(encode_to_slice(&data, &mut out[..required]), Ok(required));
+
+        let mut short = [0xFF; 32];
+        assert_eq!(
+            encode_to_slice(&data, &mut short[..required - 1]),
+            Err(BufferTooSmall {
+                required,
+                capacity: required - 1
+            })
+        );
+        assert_eq!(short, [0xFF; 32]);
+    }
+
+    #[test]
+    fn test_decode_to_slice() {
+        let mut out = [0; 256];
+        for len in [0, 1, 7, 8, 200] {
+            let data = generate(Profile::DangerousDensity(0.5), len, len as u64);
+            let encoded = encode(&data);
+            let written = decode_to_slice(&encoded, &mut out).unwrap();
+            assert_eq!(&out[..written], &data[..], "len {len}");
+
+            if len > 0 {
+                let error = decode_to_slice(&encoded, &mut out[..len - 1]).unwrap_err();
+                let DecodeError::OutputTooSmall { required, .. } = error else {
+                    panic!("{error:?}");
+                };
+                assert_eq!(required, len);
+            }
+        }
+
+        // A problem with the input wins over the full buffer
+        let bad = format!("{}\u{0800}", encode(b"payload"));
+        assert_eq!(
+            decode_to_slice(&bad, &mut out[..2]),
+            decode(&bad).map(|decoded| decoded.len())
+        );
+    }
+}
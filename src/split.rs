@@ -0,0 +1,139 @@
+//! Splitting input into independently encodable pieces.
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+
+#[cfg(feature = "alloc")]
+use crate::encode;
+
+/// Number of input bytes that encode to a whole number of 7-bit chunks.
+///
+/// 7 bytes are exactly 56 bits, or 8 chunks, so a piece whose length is a
+/// multiple of this never ends in a zero-padded partial chunk.
+const ALIGNMENT: usize = 7;
+
+/// Splits `data` into pieces that can be encoded independently and then
+/// concatenated.
+///
+/// Every piece except the last is a multiple of 7 bytes long, so its
+/// encoding ends exactly on a chunk boundary. Encoding each piece on its own
+/// (on different threads or different machines) and joining the strings in
+/// order gives text that [`decode`](crate::decode) turns back into `data`.
+/// The joined string can differ from [`encode`] of the whole
+/// input when a piece ends in a dangerous chunk, but it is equally valid.
+///
+/// `target_chunk` is rounded down to a multiple of 7, with a minimum of 7.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{decode, encode, split_for_parallel};
+///
+/// let data: Vec<u8> = (0..=255).cycle().take(10_000).collect();
+/// let encoded: String = split_for_parallel(&data, 4096).map(encode).collect();
+///
+/// assert_eq!(decode(&encoded).unwrap(), data);
+/// ```
+pub fn split_for_parallel(data: &[u8], target_chunk: usize) -> impl Iterator<Item = &[u8]> {
+    let size = (target_chunk - target_chunk % ALIGNMENT).max(ALIGNMENT);
+    data.chunks(size)
+}
+
+/// Encodes `data` as a sequence of segments of at most `max_len`
+/// characters each.
+///
+/// Every segment is a complete encoding of its own slice of `data`, so it
+/// can be decoded without the others, and decoding the segments in order
+/// and concatenating the results gives `data`. For UIs and protocols that
+/// paginate large payloads.
+///
+/// Each segment except the last encodes `max_len * 7 / 8` bytes, the most
+/// that can never need more than `max_len` characters. When `max_len` is a
+/// multiple of 8, the segments are also aligned to whole chunks and can be
+/// joined into a single decodable string like the pieces of
+/// [`split_for_parallel`].
+///
+/// # Panics
+///
+/// Panics if `max_len` is less than 2, the length of the encoding of a
+/// single byte.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{decode, encode_segments};
+///
+/// let data: Vec<u8> = (0..=255).collect();
+/// let mut decoded = Vec::new();
+/// for segment in encode_segments(&data, 100) {
+///     assert!(segment.chars().count() <= 100);
+///     decoded.extend(decode(&segment).unwrap());
+/// }
+/// assert_eq!(decoded, data);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn encode_segments(data: &[u8], max_len: usize) -> impl Iterator<Item = String> + '_ {
+    assert!(max_len >= 2, "segments must hold at least 2 characters");
+    // `n` bytes are `ceil(8n / 7)` chunks, and a character never holds less
+    // than one chunk. `max_len * 7 / 8`, without overflowing.
+    data.chunks(max_len / 8 * 7 + max_len % 8 * 7 / 8)
+        .map(encode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode, encode};
+
+    #[test]
+    fn test_split_sizes() {
+        let data = [0u8; 100];
+        let sizes: Vec<usize> = split_for_parallel(&data, 30).map(<[u8]>::len).collect();
+        assert_eq!(sizes, [28, 28, 28, 16]);
+
+        // Targets below the alignment still make progress
+        assert_eq!(split_for_parallel(&data, 0).count(), 15);
+        assert_eq!(split_for_parallel(&[], 64).count(), 0);
+    }
+
+    #[test]
+    fn test_split_pieces_concatenate() {
+        // Zeros make every piece end in a dangerous chunk and a shortened escape
+        let inputs: Vec<Vec<u8>> = vec![
+            vec![0; 200],
+            (0..500).map(|i| (i * 37 % 256) as u8).collect(),
+            b"\n\r\"&\\".repeat(40),
+        ];
+
+        for data in inputs {
+            for target in [1, 7, 13, 64] {
+                let encoded: String = split_for_parallel(&data, target).map(encode).collect();
+                assert_eq!(decode(&encoded).unwrap(), data, "target {target}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_segments_bounds() {
+        let data: Vec<u8> = (0..1000).map(|i| (i * 37 % 256) as u8).collect();
+        for max_len in [2, 3, 8, 15, 64, 2000] {
+            let segments: Vec<String> = encode_segments(&data, max_len).collect();
+            assert!(segments.iter().all(|s| s.chars().count() <= max_len));
+
+            let decoded: Vec<u8> = segments.iter().flat_map(|s| decode(s).unwrap()).collect();
+            assert_eq!(decoded, data, "max_len {max_len}");
+        }
+
+        // Safe data uses every character a segment may hold
+        let safe = [b'a'; 70];
+        assert!(encode_segments(&safe, 16).all(|s| s.len() == 16));
+        assert_eq!(
+            encode_segments(&safe, 64).collect::<String>(),
+            encode(&safe)
+        );
+
+        // No limit at all gives one segment
+        let segments: Vec<String> = encode_segments(&data, usize::MAX).collect();
+        assert_eq!(segments, [encode(&data)]);
+    }
+}
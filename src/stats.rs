@@ -0,0 +1,394 @@
+//! Output size prediction from data samples.
+//!
+//! Base122 output size in UTF-8 bytes is almost entirely determined by the
+//! input length: every 7-bit chunk becomes one output byte, whether it is
+//! emitted directly or as half of a 2-byte escape. What varies with the data
+//! is the number of *characters*: each escape folds two chunks into a single
+//! character, which matters when the encoded string is measured in code
+//! points or UTF-16 code units (e.g. JavaScript string length).
+
+use crate::{ChunkWidth, Chunks, Engine, Profile};
+
+/// Estimated encoding overhead derived from a data sample.
+///
+/// Produced by [`predict_overhead`] or [`Engine::predict_overhead`]. The
+/// counts describe the sample itself; the methods extrapolate them to
+/// inputs of any length that share the sample's dangerous-chunk statistics,
+/// encoded with the same engine.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Prediction {
+    /// Length of the sample in bytes.
+    pub sample_len: usize,
+    /// Number of chunks in the sample.
+    pub chunks: usize,
+    /// Number of escape sequences the sample encodes to.
+    pub escapes: usize,
+    engine: Engine,
+    /// Bytes by which the sample's escapes exceed one byte per chunk, which
+    /// profiles with 3-byte escapes add.
+    escape_excess: usize,
+}
+
+impl Prediction {
+    /// The engine the prediction is for.
+    pub fn engine(&self) -> Engine {
+        self.engine
+    }
+
+    /// Fraction of chunks that were folded into escape sequences.
+    ///
+    /// Each escape covers two chunks (the dangerous chunk and the one after
+    /// it), so this ranges from `0.0` for data with no dangerous chunks up to
+    /// `1.0` for data consisting entirely of them.
+    pub fn escape_ratio(&self) -> f64 {
+        if self.chunks == 0 {
+            return 0.0;
+        }
+        (2 * self.escapes) as f64 / self.chunks as f64
+    }
+
+    /// Predicted UTF-8 length in bytes of encoding `input_len` bytes.
+    ///
+    /// Every chunk becomes one output byte unless the profile's escapes
+    /// take 3 bytes, so with the standard profile this does not depend on
+    /// the sample. It may be exceeded by one or two bytes when the final
+    /// chunk is dangerous. Includes the terminator if the engine adds one.
+    pub fn encoded_len(&self, input_len: usize) -> usize {
+        let chunks = self.chunks_for(input_len);
+        let excess = if self.chunks == 0 {
+            0
+        } else {
+            // Round to nearest; `f64::round` is not available without std
+            (chunks as f64 * self.escape_excess as f64 / self.chunks as f64 + 0.5) as usize
+        };
+        chunks + excess + self.terminator_len()
+    }
+
+    /// Predicted number of characters when encoding `input_len` bytes.
+    ///
+    /// Every output character is a single UTF-16 code unit, so this is also
+    /// the expected JavaScript `string.length` of the result.
+    pub fn encoded_chars(&self, input_len: usize) -> usize {
+        let chunks = self.chunks_for(input_len) as f64;
+        let chars = (chunks * (1.0 - self.escape_ratio() / 2.0) + 0.5) as usize;
+        chars + usize::from(self.engine.terminator())
+    }
+
+    /// Predicted size in bytes relative to the input size (≈1.14 with the
+    /// standard profile).
+    pub fn byte_ratio(&self) -> f64 {
+        let excess = if self.chunks == 0 {
+            0.0
+        } else {
+            self.escape_excess as f64 / self.chunks as f64
+        };
+        self.chunks_per_byte() * (1.0 + excess)
+    }
+
+    /// Predicted size in characters relative to the input size.
+    pub fn char_ratio(&self) -> f64 {
+        self.chunks_per_byte() * (1.0 - self.escape_ratio() / 2.0)
+    }
+
+    /// Predicted savings in characters compared to Base64, as a fraction.
+    ///
+    /// Base64 produces 4 characters per 3 input bytes; a value of `0.2`
+    /// means the Base122 output is expected to be 20% shorter.
+    pub fn char_savings_vs_base64(&self) -> f64 {
+        1.0 - self.char_ratio() / (4.0 / 3.0)
+    }
+
+    fn chunks_per_byte(&self) -> f64 {
+        8.0 / f64::from(self.engine.chunk_width().bits())
+    }
+
+    fn chunks_for(&self, input_len: usize) -> usize {
+        (self.engine.framed_len(input_len) * 8).div_ceil(self.engine.chunk_width().bits() as usize)
+    }
+
+    fn terminator_len(&self) -> usize {
+        self.engine.terminator_len()
+    }
+}
+
+/// Predicts the encoding overhead of [`encode`](crate::encode) for data
+/// resembling `sample`.
+///
+/// The sample is scanned with the same chunking as [`encode`](crate::encode),
+/// without producing any output, so it is cheap to run on a representative
+/// prefix of a large dataset before committing to encoding all of it. Use
+/// [`Engine::predict_overhead`] for other profiles and chunk widths.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{encode, predict_overhead};
+///
+/// let sample = b"The quick brown fox jumps over the lazy dog";
+/// let prediction = predict_overhead(sample);
+///
+/// let encoded = encode(sample);
+/// assert_eq!(prediction.encoded_len(sample.len()), encoded.len());
+/// assert_eq!(prediction.encoded_chars(sample.len()), encoded.chars().count());
+///
+/// // Project the output size of a terabyte with similar content
+/// let projected = prediction.encoded_chars(1 << 40);
+/// assert!(projected > 1 << 40);
+/// ```
+pub fn predict_overhead(sample: impl AsRef<[u8]>) -> Prediction {
+    predict(Engine::new(), sample.as_ref())
+}
+
+pub(crate) fn predict(engine: Engine, sample: &[u8]) -> Prediction {
+    let mut prediction = Prediction {
+        sample_len: sample.len(),
+        chunks: 0,
+        escapes: 0,
+        engine,
+        escape_excess: 0,
+    };
+    if engine.chunk_width() == ChunkWidth::Six {
+        prediction.chunks = (sample.len() * 8).div_ceil(6);
+        return prediction;
+    }
+
+    let profile = engine.profile();
+    let mut chunks = Chunks::new(sample);
+    while let Some(bits) = chunks.next() {
+        prediction.chunks += 1;
+        let Some(index) = profile.escape_index(bits) else {
+            continue;
+        };
+        if let Some(payload) = chunks.next() {
+            prediction.chunks += 1;
+            prediction.escapes += 1;
+            // 2-byte escapes cost one byte per chunk
+            if profile.escape(index, payload) > 0x7FF {
+                prediction.escape_excess += 1;
+            }
+        }
+    }
+    prediction
+}
+
+/// Chunk statistics aggregated over a corpus of files.
+///
+/// Produced by [`profile_corpus`], or built up blob by blob with
+/// [`add`](CorpusProfile::add). The histogram shows how often a given set
+/// of dangerous values would be escaped in real data, which helps when
+/// choosing or designing a [`Profile`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorpusProfile {
+    /// Number of blobs added.
+    pub blobs: u64,
+    /// Total length of the blobs in bytes.
+    pub bytes: u64,
+    /// Total number of 7-bit chunks in the blobs.
+    pub chunks: u64,
+    /// Number of occurrences of each 7-bit chunk value.
+    pub histogram: [u64; 128],
+}
+
+impl CorpusProfile {
+    /// Creates an empty profile.
+    pub const fn new() -> Self {
+        CorpusProfile {
+            blobs: 0,
+            bytes: 0,
+            chunks: 0,
+            histogram: [0; 128],
+        }
+    }
+
+    /// Adds the chunks of one blob, chunked as [`encode`](crate::encode)
+    /// would chunk it on its own.
+    pub fn add(&mut self, blob: impl AsRef<[u8]>) {
+        let blob = blob.as_ref();
+        for bits in Chunks::new(blob) {
+            self.histogram[bits as usize] += 1;
+            self.chunks += 1;
+        }
+        self.blobs += 1;
+        self.bytes += blob.len() as u64;
+    }
+
+    /// Fraction of chunks equal to `chunk`.
+    pub fn frequency(&self, chunk: u8) -> f64 {
+        if self.chunks == 0 {
+            return 0.0;
+        }
+        self.histogram[chunk as usize & 0x7F] as f64 / self.chunks as f64
+    }
+
+    /// Fraction of chunks that `profile` treats as dangerous.
+    pub fn dangerous_ratio(&self, profile: &Profile) -> f64 {
+        profile
+            .dangerous()
+            .iter()
+            .map(|&chunk| self.frequency(chunk))
+            .sum()
+    }
+
+    /// Estimated size in characters relative to the input size when
+    /// encoding data like the corpus with `profile`.
+    ///
+    /// Assumes dangerous chunks occur independently: with a fraction `p` of
+    /// dangerous chunks, each character stands for `1 + p` chunks on
+    /// average, since a dangerous chunk shares its character with the next
+    /// one.
+    pub fn char_ratio(&self, profile: &Profile) -> f64 {
+        8.0 / 7.0 / (1.0 + self.dangerous_ratio(profile))
+    }
+
+    /// All 7-bit values, least common first.
+    ///
+    /// Escaping values from the front produces the fewest non-ASCII
+    /// characters, which suits channels where those are costly, such as
+    /// URLs that percent-encode them.
+    pub fn by_frequency(&self) -> [u8; 128] {
+        let mut values = [0u8; 128];
+        for (value, slot) in (0..).zip(values.iter_mut()) {
+            *slot = value;
+        }
+        values.sort_unstable_by_key(|&value| (self.histogram[value as usize], value));
+        values
+    }
+}
+
+impl Default for CorpusProfile {
+    fn default() -> Self {
+        CorpusProfile::new()
+    }
+}
+
+/// Aggregates chunk statistics over many blobs, such as the files of a
+/// dataset.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{profile_corpus, Profile};
+///
+/// let files: [&[u8]; 3] = [b"{\"id\": 1}\n", b"{\"id\": 2}\n", &[0, 0, 0, 0]];
+/// let corpus = profile_corpus(files);
+///
+/// assert_eq!(corpus.blobs, 3);
+/// assert!(corpus.dangerous_ratio(&Profile::STANDARD) > 0.0);
+/// // Fewer escapes means denser output
+/// assert!(corpus.char_ratio(&Profile::STANDARD) < 8.0 / 7.0);
+/// ```
+pub fn profile_corpus<I>(blobs: I) -> CorpusProfile
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    let mut profile = CorpusProfile::new();
+    for blob in blobs {
+        profile.add(blob);
+    }
+    profile
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode;
+
+    #[test]
+    fn test_predict_empty() {
+        let prediction = predict_overhead([]);
+        assert_eq!(prediction.chunks, 0);
+        assert_eq!(prediction.escape_ratio(), 0.0);
+        assert_eq!(prediction.encoded_len(0), 0);
+    }
+
+    #[test]
+    fn test_predict_matches_sample_encoding() {
+        let samples: Vec<Vec<u8>> = vec![
+            b"Hello, World!".to_vec(),
+            vec![0; 64],
+            (0..=255).collect(),
+            (0..1000).map(|i| (i * 37 % 256) as u8).collect(),
+        ];
+
+        for sample in samples {
+            let prediction = predict_overhead(&sample);
+            let encoded = encode(&sample);
+            assert_eq!(prediction.chunks, (sample.len() * 8).div_ceil(7));
+            assert_eq!(
+                prediction.encoded_chars(sample.len()),
+                encoded.chars().count()
+            );
+            assert_eq!(
+                encoded.chars().filter(|c| !c.is_ascii()).count(),
+                prediction.escapes
+                    + usize::from(encoded.len() > prediction.encoded_len(sample.len()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_corpus_histogram() {
+        let corpus = profile_corpus([&[0u8; 7][..], b"", &[0xFF; 7]]);
+        assert_eq!((corpus.blobs, corpus.bytes, corpus.chunks), (3, 14, 16));
+        assert_eq!(corpus.histogram[0], 8);
+        assert_eq!(corpus.histogram[0x7F], 8);
+        assert_eq!(corpus.frequency(0), 0.5);
+        assert_eq!(corpus.dangerous_ratio(&Profile::STANDARD), 0.5);
+
+        let order = corpus.by_frequency();
+        assert_eq!(&order[..3], &[1, 2, 3]);
+        assert_eq!(&order[126..], &[0, 0x7F]);
+    }
+
+    #[test]
+    fn test_corpus_char_ratio_tracks_encoding() {
+        let blobs: Vec<Vec<u8>> = (0..20)
+            .map(|seed| {
+                (0..5000)
+                    .map(|i| ((i * 31 + seed * 7) % 251) as u8)
+                    .collect()
+            })
+            .collect();
+        let corpus = profile_corpus(&blobs);
+
+        let input: usize = blobs.iter().map(Vec::len).sum();
+        let chars: usize = blobs.iter().map(|blob| encode(blob).chars().count()).sum();
+        let actual = chars as f64 / input as f64;
+        assert!((corpus.char_ratio(&Profile::STANDARD) - actual).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_predict_follows_engine() {
+        // Sampling the whole input leaves nothing to extrapolate
+        let data: Vec<u8> = (0..3000).map(|i| (i * 37 % 256) as u8).collect();
+        for profile in [&Profile::STANDARD, &Profile::NORMALIZATION_STABLE] {
+            for width in [ChunkWidth::Seven, ChunkWidth::Six] {
+                let engine = Engine::new()
+                    .with_profile(profile)
+                    .with_chunk_width(width)
+                    .with_terminator(true);
+                let prediction = engine.predict_overhead(&data);
+                let encoded = engine.encode(&data);
+                assert_eq!(
+                    prediction.encoded_len(data.len()),
+                    encoded.len(),
+                    "{profile:?} {width:?}"
+                );
+                assert_eq!(
+                    prediction.encoded_chars(data.len()),
+                    encoded.chars().count(),
+                    "{profile:?} {width:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_predict_all_dangerous() {
+        // All-zero input: every chunk is a null and gets folded into an escape
+        let prediction = predict_overhead([0; 700]);
+        assert_eq!(prediction.escape_ratio(), 1.0);
+        assert!(prediction.char_savings_vs_base64() > 0.5);
+    }
+}
@@ -0,0 +1,250 @@
+//! Incremental encoder and decoder state machines.
+//!
+//! These hold the few bits of state needed to encode or decode input that
+//! arrives in arbitrary pieces, and emit output one byte at a time through
+//! a callback. They never allocate, so they back both the `no_std` stream
+//! adapters and the allocating convenience types.
+
+#[cfg(any(feature = "alloc", feature = "embedded-io"))]
+use crate::{emit_escape, ILLEGAL_INDEX, SAFE, SHORTENED};
+use crate::{parse_escape, Accumulator, DecodeError};
+
+/// A dangerous chunk waiting for the chunk that follows it.
+#[cfg(any(feature = "alloc", feature = "embedded-io"))]
+#[derive(Debug, Clone, Copy)]
+struct Dangerous {
+    /// Index of the chunk in `ILLEGALS`.
+    index: u8,
+    /// The chunk itself, needed if the input ends before a payload arrives.
+    bits: u8,
+}
+
+/// Incremental encoder state.
+///
+/// Produces exactly the same output as [`encode`](crate::encode) for the
+/// concatenation of all pushed bytes.
+#[cfg(any(feature = "alloc", feature = "embedded-io"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct EncodeState {
+    /// Input bits not yet formed into a chunk, right-aligned.
+    bits: u16,
+    /// Number of valid bits in `bits` (at most 6 between pushes).
+    bit_count: u32,
+    /// A dangerous chunk whose escape sequence is not yet complete.
+    pending: Option<Dangerous>,
+}
+
+#[cfg(any(feature = "alloc", feature = "embedded-io"))]
+impl EncodeState {
+    /// Feeds one input byte, emitting any completed output bytes.
+    pub(crate) fn push(&mut self, byte: u8, emit: &mut impl FnMut(u8)) {
+        self.bits = (self.bits << 8) | byte as u16;
+        self.bit_count += 8;
+
+        while self.bit_count >= 7 {
+            self.bit_count -= 7;
+            let chunk = ((self.bits >> self.bit_count) & 0x7F) as u8;
+            self.chunk(chunk, emit);
+        }
+        self.bits &= (1 << self.bit_count) - 1;
+    }
+
+    /// Flushes the final partial chunk and any pending escape.
+    ///
+    /// The state is reset afterwards and can encode a new, unrelated input.
+    pub(crate) fn finish(&mut self, emit: &mut impl FnMut(u8)) {
+        if self.bit_count > 0 {
+            // Pad the final chunk with zero bits
+            let chunk = ((self.bits << (7 - self.bit_count)) & 0x7F) as u8;
+            self.chunk(chunk, emit);
+        }
+        if let Some(dangerous) = self.pending {
+            // Last 7 bits are dangerous - use shortened marker
+            let [b1, b2] = emit_escape(SHORTENED, dangerous.bits);
+            emit(b1);
+            emit(b2);
+        }
+        *self = EncodeState::default();
+    }
+
+    fn chunk(&mut self, chunk: u8, emit: &mut impl FnMut(u8)) {
+        if let Some(dangerous) = self.pending.take() {
+            let [b1, b2] = emit_escape(dangerous.index, chunk);
+            emit(b1);
+            emit(b2);
+            return;
+        }
+
+        let index = ILLEGAL_INDEX[chunk as usize];
+        if index == SAFE {
+            emit(chunk);
+        } else {
+            self.pending = Some(Dangerous { index, bits: chunk });
+        }
+    }
+}
+
+/// Incremental decoder state over encoded bytes.
+///
+/// Works on raw bytes rather than `char`s so input may be split anywhere,
+/// including between the two bytes of an escape sequence.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct DecodeState {
+    accumulator: Accumulator,
+    /// First byte of a 2-byte character and its offset, awaiting the second.
+    lead: Option<(u8, usize)>,
+    /// Offset of the next input byte.
+    position: usize,
+}
+
+impl DecodeState {
+    /// Feeds one encoded byte, emitting any completed output bytes.
+    pub(crate) fn push(&mut self, byte: u8, emit: &mut impl FnMut(u8)) -> Result<(), DecodeError> {
+        let position = self.position;
+        self.position += 1;
+
+        if let Some((lead, lead_position)) = self.lead.take() {
+            if byte & 0b11000000 != 0b10000000 {
+                return Err(DecodeError::InvalidByte { position, byte });
+            }
+            let c = ((lead as u32 & 0b00011111) << 6) | (byte as u32 & 0b00111111);
+            let Some((illegal, payload)) = parse_escape(c) else {
+                return Err(DecodeError::InvalidEscape {
+                    position: lead_position,
+                    character: char::from_u32(c).unwrap_or(char::REPLACEMENT_CHARACTER),
+                });
+            };
+            if let Some(illegal) = illegal {
+                self.accumulator.push7(illegal).map(&mut *emit);
+            }
+            self.accumulator.push7(payload).map(emit);
+            return Ok(());
+        }
+
+        match byte {
+            0x00..=0x7F => {
+                self.accumulator.push7(byte).map(emit);
+            }
+            // Lead bytes of 2-byte sequences (0xC0 and 0xC1 would be overlong)
+            0xC2..=0xDF => self.lead = Some((byte, position)),
+            _ => return Err(DecodeError::InvalidByte { position, byte }),
+        }
+        Ok(())
+    }
+
+    /// Feeds a slice of encoded bytes.
+    #[cfg(any(feature = "alloc", feature = "embedded-io"))]
+    pub(crate) fn push_slice(
+        &mut self,
+        data: &[u8],
+        emit: &mut impl FnMut(u8),
+    ) -> Result<(), DecodeError> {
+        for &byte in data {
+            self.push(byte, emit)?;
+        }
+        Ok(())
+    }
+
+    /// Checks that the input did not end inside a 2-byte character.
+    ///
+    /// The state is reset afterwards and can decode a new, unrelated input.
+    pub(crate) fn finish(&mut self) -> Result<(), DecodeError> {
+        let position = self.position;
+        let truncated = self.lead.is_some();
+        *self = DecodeState::default();
+        if truncated {
+            return Err(DecodeError::UnexpectedEnd { position });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode, encode};
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len)
+            .map(|i| match i % 5 {
+                0 => 0,
+                1 => (i * 37 % 256) as u8,
+                2 => 92 << 1,
+                _ => (i * i % 256) as u8,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_state_matches_encode() {
+        for len in 0..100 {
+            let data = sample(len);
+            for split in [1, 2, 3, 7, 64] {
+                let mut state = EncodeState::default();
+                let mut output = Vec::new();
+                for piece in data.chunks(split) {
+                    for &byte in piece {
+                        state.push(byte, &mut |b| output.push(b));
+                    }
+                }
+                state.finish(&mut |b| output.push(b));
+                assert_eq!(output, encode(&data).into_bytes(), "len {len}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_state_matches_decode() {
+        for len in 0..100 {
+            let data = sample(len);
+            let encoded = encode(&data);
+            for split in [1, 2, 3, 64] {
+                let mut state = DecodeState::default();
+                let mut output = Vec::new();
+                for piece in encoded.as_bytes().chunks(split) {
+                    state.push_slice(piece, &mut |b| output.push(b)).unwrap();
+                }
+                state.finish().unwrap();
+                assert_eq!(output, decode(&encoded).unwrap(), "len {len}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_state_errors() {
+        let mut state = DecodeState::default();
+        let mut sink = |_| {};
+        assert_eq!(
+            state.push_slice("ab€".as_bytes(), &mut sink),
+            Err(DecodeError::InvalidByte {
+                position: 2,
+                byte: 0xE2
+            })
+        );
+
+        let mut state = DecodeState::default();
+        assert_eq!(
+            state.push_slice(&[b'a', 0xC2, b'b'], &mut sink),
+            Err(DecodeError::InvalidByte {
+                position: 2,
+                byte: b'b'
+            })
+        );
+
+        let mut state = DecodeState::default();
+        state.push_slice(&[b'a', 0xC2], &mut sink).unwrap();
+        assert_eq!(
+            state.finish(),
+            Err(DecodeError::UnexpectedEnd { position: 2 })
+        );
+
+        let mut state = DecodeState::default();
+        assert_eq!(
+            state.push_slice("x\u{0680}".as_bytes(), &mut sink),
+            Err(DecodeError::InvalidEscape {
+                position: 1,
+                character: '\u{0680}'
+            })
+        );
+    }
+}
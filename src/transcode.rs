@@ -0,0 +1,100 @@
+//! Converting between Base64 and Base122 text.
+//!
+//! The reference JavaScript tooling works on Base64 strings, e.g. the
+//! contents of `data:` URIs it rewrites. These helpers go between the two
+//! encodings directly, for code ported from it.
+
+use alloc::string::String;
+
+use crate::{decode, encode, ChunkWidth, DecodeError, Engine};
+
+/// Standard Base64, which is the 6-bit engine plus padding.
+const BASE64: Engine = Engine::new().with_chunk_width(ChunkWidth::Six);
+
+/// Re-encodes standard Base64 text as Base122.
+///
+/// Padding is optional. Like the reference `encodeFromBase64`.
+///
+/// # Errors
+///
+/// Fails with [`DecodeError::InvalidCharacter`] at the byte offset of the
+/// first character outside the Base64 alphabet.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{decode, encode_from_base64};
+///
+/// let encoded = encode_from_base64("AGhpCg==").unwrap();
+/// assert_eq!(decode(&encoded).unwrap(), b"\0hi\n");
+/// ```
+pub fn encode_from_base64(base64: &str) -> Result<String, DecodeError> {
+    let data = BASE64.decode(base64.trim_end_matches('='))?;
+    Ok(encode(&data))
+}
+
+/// Decodes Base122 text and re-encodes it as padded standard Base64.
+///
+/// # Errors
+///
+/// Fails like [`decode`] on invalid input.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{decode_to_base64, encode};
+///
+/// assert_eq!(decode_to_base64(&encode(b"\0hi\n")).unwrap(), "AGhpCg==");
+/// ```
+pub fn decode_to_base64(encoded: &str) -> Result<String, DecodeError> {
+    let mut base64 = BASE64.encode(decode(encoded)?);
+    // Unpadded Base64 is never 1 character short of a multiple of 4
+    let padding = (4 - base64.len() % 4) % 4;
+    base64.extend(core::iter::repeat_n('=', padding));
+    Ok(base64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::{generate, Profile};
+
+    #[test]
+    fn test_base64_round_trip() {
+        // RFC 4648 test vectors
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (plain, base64) in vectors {
+            let encoded = encode_from_base64(base64).unwrap();
+            assert_eq!(encoded, encode(plain.as_bytes()));
+            assert_eq!(decode_to_base64(&encoded).unwrap(), base64);
+            assert_eq!(
+                encode_from_base64(base64.trim_end_matches('=')).unwrap(),
+                encoded
+            );
+        }
+
+        let data = generate(Profile::Random, 1000, 13);
+        let base64 = decode_to_base64(&encode(&data)).unwrap();
+        assert_eq!(decode(&encode_from_base64(&base64).unwrap()).unwrap(), data);
+    }
+
+    #[test]
+    fn test_base64_errors() {
+        assert!(matches!(
+            encode_from_base64("Zm9v-A=="),
+            Err(DecodeError::InvalidCharacter {
+                position: 4,
+                character: '-'
+            })
+        ));
+        assert!(decode_to_base64("\u{0680}").is_err());
+    }
+}
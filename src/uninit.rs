@@ -0,0 +1,98 @@
+//! Decoding into uninitialized memory.
+
+use core::mem::MaybeUninit;
+
+use crate::slice::output_too_small;
+use crate::{split_char, Accumulator, DecodeError, Profile};
+
+/// Decodes `encoded` into a possibly uninitialized buffer and returns the
+/// initialized prefix.
+///
+/// For zero-copy deserializers and arena allocators that hand out
+/// uninitialized memory, this avoids zeroing a large buffer only to
+/// overwrite it. A buffer of `encoded.len() * 7 / 8` bytes is always large
+/// enough. Needs no allocator.
+///
+/// # Errors
+///
+/// Fails like [`decode`](crate::decode) on invalid input, and with
+/// [`DecodeError::OutputTooSmall`], reporting the length needed, if
+/// `output` fills up. In either case the contents of `output` are
+/// unspecified.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::mem::MaybeUninit;
+///
+/// use base122_rs::{decode_to_uninit, encode};
+///
+/// let encoded = encode(b"\0zero-copy\n");
+/// let mut buffer = [MaybeUninit::<u8>::uninit(); 64];
+///
+/// let decoded = decode_to_uninit(&encoded, &mut buffer).unwrap();
+/// assert_eq!(decoded, b"\0zero-copy\n");
+/// ```
+pub fn decode_to_uninit<'a>(
+    encoded: &str,
+    output: &'a mut [MaybeUninit<u8>],
+) -> Result<&'a mut [u8], DecodeError> {
+    let mut accumulator = Accumulator::default();
+    let mut written = 0;
+
+    for (position, character) in encoded.char_indices() {
+        let (illegal, payload) = split_char(&Profile::STANDARD, position, character)?;
+        for chunk in illegal.into_iter().chain([payload]) {
+            let Some(byte) = accumulator.push7(chunk) else {
+                continue;
+            };
+            let Some(slot) = output.get_mut(written) else {
+                return Err(output_too_small(encoded, position));
+            };
+            slot.write(byte);
+            written += 1;
+        }
+    }
+
+    let initialized = &mut output[..written];
+    // SAFETY: every element of `initialized` was written above, and
+    // `MaybeUninit<u8>` has the same layout as `u8`
+    #[allow(unsafe_code)]
+    let initialized = unsafe { &mut *(initialized as *mut [MaybeUninit<u8>] as *mut [u8]) };
+    Ok(initialized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode, encode};
+
+    #[test]
+    fn test_decode_to_uninit_matches_decode() {
+        let mut buffer = vec![MaybeUninit::uninit(); 1000];
+        for len in [0, 1, 7, 8, 500] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 29 % 256) as u8).collect();
+            let encoded = encode(&data);
+            let decoded = decode_to_uninit(&encoded, &mut buffer[..encoded.len() * 7 / 8]);
+            assert_eq!(decoded.unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_decode_to_uninit_errors() {
+        let mut buffer = [MaybeUninit::uninit(); 4];
+        let encoded = encode(b"too long");
+        assert_eq!(
+            decode_to_uninit(&encoded, &mut buffer),
+            // The sixth chunk completes the fifth byte
+            Err(DecodeError::OutputTooSmall {
+                position: 5,
+                required: 8
+            })
+        );
+        assert_eq!(
+            decode_to_uninit("a\u{0680}", &mut buffer),
+            decode("a\u{0680}").map(|_| &mut [][..])
+        );
+    }
+}
@@ -0,0 +1,85 @@
+//! UTF-16 output for JavaScript string interop.
+//!
+//! The original Base122 technique measures its savings in JavaScript
+//! string storage, which is UTF-16. Every character the encoder emits is at
+//! most U+07FF, so each one is exactly one UTF-16 code unit and the
+//! sequence returned by [`encode_utf16`] is what a JavaScript string holding
+//! the encoded text contains, unit for unit.
+
+use alloc::vec::Vec;
+
+use crate::{decode_chars_to_vec, encode, max_decoded_len, DecodeError};
+
+/// Encodes `data` as a sequence of UTF-16 code units.
+///
+/// Equivalent to `encode(data).encode_utf16().collect()`. The length of the
+/// result is the `length` of the equivalent JavaScript string.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{decode_utf16, encode, encode_utf16};
+///
+/// let units = encode_utf16(b"\0binary\n");
+/// assert_eq!(units.len(), encode(b"\0binary\n").chars().count());
+/// assert_eq!(decode_utf16(&units).unwrap(), b"\0binary\n");
+/// ```
+pub fn encode_utf16(data: impl AsRef<[u8]>) -> Vec<u16> {
+    // Every character is below U+0800, so one code unit per character
+    encode(data.as_ref()).chars().map(|c| c as u16).collect()
+}
+
+/// Decodes a sequence of UTF-16 code units produced by [`encode_utf16`].
+///
+/// # Errors
+///
+/// Fails like [`decode`](crate::decode) on code units the encoder never
+/// produces, including unpaired surrogates. Error positions are indices
+/// into `units` rather than byte offsets.
+pub fn decode_utf16(units: &[u16]) -> Result<Vec<u8>, DecodeError> {
+    let characters = units.iter().enumerate().map(|(position, &unit)| {
+        // Surrogates cannot form a `char`; report them as U+FFFD, which is
+        // rejected as out of range either way
+        let character = char::from_u32(unit as u32).unwrap_or(char::REPLACEMENT_CHARACTER);
+        (position, character)
+    });
+
+    let mut decoded = Vec::with_capacity(max_decoded_len(units.len() * 2));
+    decode_chars_to_vec(characters, &mut decoded)?;
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::{generate, Profile};
+
+    #[test]
+    fn test_utf16_matches_str_encoding() {
+        for len in [0, 1, 7, 8, 500] {
+            let data = generate(Profile::Image, len, 11);
+            let units = encode_utf16(&data);
+            let expected: Vec<u16> = encode(&data).encode_utf16().collect();
+            assert_eq!(units, expected);
+            assert_eq!(decode_utf16(&units).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_decode_utf16_errors() {
+        assert_eq!(
+            decode_utf16(&[b'a' as u16, 0xD800]),
+            Err(DecodeError::InvalidCharacter {
+                position: 1,
+                character: char::REPLACEMENT_CHARACTER
+            })
+        );
+        assert_eq!(
+            decode_utf16(&[0x0680]),
+            Err(DecodeError::InvalidEscape {
+                position: 0,
+                character: '\u{0680}'
+            })
+        );
+    }
+}
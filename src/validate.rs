@@ -0,0 +1,144 @@
+//! Validation without decoding.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use crate::{decode, decoded_len, DecodeError, Profile, WordWriter, ILLEGALS, SHORTENED};
+
+/// Bytes checked together between early exits.
+const BLOCK: usize = 64;
+
+/// Returns whether [`decode`] would accept `encoded`.
+///
+/// Only checks the input, without producing any output, so it is much
+/// faster than decoding: each escape is checked from its first byte alone,
+/// and bytes are checked in blocks the compiler turns into SIMD code.
+/// Useful for rejecting malformed payloads before queuing them for
+/// processing.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{encode, is_valid};
+///
+/// assert!(is_valid(&encode(b"\0\n\r\"&\\ payload")));
+/// assert!(!is_valid("not \u{0680} base122"));
+/// assert!(!is_valid("\u{20AC}"));
+/// ```
+pub fn is_valid(encoded: &str) -> bool {
+    let mut blocks = encoded.as_bytes().chunks_exact(BLOCK);
+    for block in &mut blocks {
+        // No early exit within a block, so the check vectorizes
+        if !block.iter().fold(true, |valid, &b| valid & valid_byte(b)) {
+            return false;
+        }
+    }
+    blocks.remainder().iter().all(|&b| valid_byte(b))
+}
+
+/// Decodes `encoded` in two passes: a full [`is_valid`] scan, then a
+/// decoding loop with no checks at all.
+///
+/// Equivalent to [`decode`], but all-or-nothing: nothing is decoded unless
+/// the whole input is valid, so no output is allocated or written for a
+/// payload that turns out to be corrupt near its end. The validation scan
+/// is an order of magnitude faster than decoding, so valid input costs
+/// about as much as with [`decode`] and invalid input much less.
+///
+/// # Errors
+///
+/// Returns the same error as [`decode`] for invalid input.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::{decode, decode_validated, encode};
+///
+/// let encoded = encode(b"trusted after validation");
+/// assert_eq!(decode_validated(&encoded), decode(&encoded));
+/// assert!(decode_validated("\u{0680}").is_err());
+/// ```
+#[cfg(feature = "alloc")]
+pub fn decode_validated(encoded: &str) -> Result<Vec<u8>, DecodeError> {
+    if !is_valid(encoded) {
+        // Rare, so let the checking decoder find and describe the problem
+        return decode(encoded);
+    }
+
+    let mut decoded = Vec::with_capacity(decoded_len(&Profile::STANDARD, encoded));
+    let mut writer = WordWriter::new(&mut decoded);
+    let mut bytes = encoded.bytes();
+    while let Some(byte) = bytes.next() {
+        if byte < 0x80 {
+            writer.push(byte);
+            continue;
+        }
+
+        // A valid 2-byte escape `110iii1p 10pppppp`
+        let continuation = bytes.next().unwrap_or_default();
+        let index = (byte >> 2) & 0b111;
+        if index != SHORTENED {
+            writer.push(ILLEGALS[index as usize]);
+        }
+        writer.push((byte & 1) << 6 | (continuation & 0x3F));
+    }
+    writer.finish();
+    Ok(decoded)
+}
+
+/// Whether `byte` may appear in valid encoded text.
+///
+/// The input is known to be UTF-8, so every character can be judged by its
+/// first byte. A standard escape U+0080 + 256 × index + payload starts with
+/// `110iii1p`: the fixed bit keeps it off the overlong leads, and `iii`
+/// must name a dangerous character or the shortened marker, not the unused
+/// index 6. Continuation bytes belong to an escape already checked.
+#[inline]
+fn valid_byte(byte: u8) -> bool {
+    // ASCII and continuation bytes are below 0xC0
+    byte < 0xC0 || (byte & 0b1110_0010 == 0b1100_0010 && (byte >> 2) & 0b111 != 6)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::{generate, Profile};
+    use crate::{decode, encode};
+
+    #[test]
+    fn test_is_valid_agrees_with_decode_on_every_character() {
+        // Everything from U+0800 up is rejected alike; spot-check it
+        let samples = [0x800, 0xFFFF, 0x10000, 0x10FFFF];
+        for c in (0..0x800).chain(samples).filter_map(char::from_u32) {
+            // Pad so the character lands in a whole block too
+            for padding in [0, BLOCK - 1] {
+                let input = format!("{}{c}", "a".repeat(padding));
+                assert_eq!(is_valid(&input), decode(&input).is_ok(), "{c:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_valid_encoded_data() {
+        for len in [0, 1, 7, 8, 9, 100, 1000] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 37 % 256) as u8).collect();
+            let encoded = encode(&data);
+            assert!(is_valid(&encoded));
+            assert!(!is_valid(&format!("{encoded}\u{0680}")));
+        }
+    }
+
+    #[test]
+    fn test_decode_validated_matches_decode() {
+        for density in [0.0, 0.1, 1.0] {
+            for len in [0, 1, 6, 7, 8, 9, 1000] {
+                let encoded = encode(&generate(Profile::DangerousDensity(density), len, 2));
+                assert_eq!(decode_validated(&encoded), decode(&encoded));
+            }
+        }
+        for invalid in ["ab\u{20AC}", "\u{0680}", "x\u{0100}"] {
+            assert_eq!(decode_validated(invalid), decode(invalid));
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! Round-trip self-checks and decoded-data diffs.
+
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use crate::locate::chunk_position;
+use crate::{decode_to_vec, decoded_len, encode, DecodeError, Profile};
+
+/// Number of encoded characters shown on each side of a difference.
+const CONTEXT_CHARS: usize = 4;
+
+/// Where a round trip through [`encode`] and [`decode`](crate::decode)
+/// first diverged from the original data.
+///
+/// Returned by [`verify_roundtrip`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Mismatch {
+    /// Offset of the first differing byte.
+    pub offset: usize,
+    /// The original byte at `offset`, or `None` if the decoded data is
+    /// longer than the original.
+    pub expected: Option<u8>,
+    /// The decoded byte at `offset`, or `None` if the decoded data ended
+    /// early.
+    pub actual: Option<u8>,
+    /// The error that stopped decoding, if decoding failed.
+    pub error: Option<DecodeError>,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "round trip differs at byte {}: ", self.offset)?;
+        match (self.expected, self.actual) {
+            (Some(expected), Some(actual)) => {
+                write!(f, "expected 0x{expected:02X}, got 0x{actual:02X}")?
+            }
+            (Some(_), None) => f.write_str("decoded data ends early")?,
+            (None, Some(_)) => f.write_str("decoded data is too long")?,
+            (None, None) => f.write_str("decoding failed")?,
+        }
+        if let Some(error) = self.error {
+            write!(f, " ({error})")?;
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for Mismatch {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        self.error
+            .as_ref()
+            .map(|error| error as &(dyn core::error::Error + 'static))
+    }
+}
+
+/// Encodes `data`, decodes the result, and checks that it matches.
+///
+/// A cheap assertion for CI jobs and for checking alternative
+/// implementations against this one.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::verify_roundtrip;
+///
+/// assert_eq!(verify_roundtrip(b"\0\n\r\"&\\ payload"), Ok(()));
+/// ```
+pub fn verify_roundtrip(data: impl AsRef<[u8]>) -> Result<(), Mismatch> {
+    let data = data.as_ref();
+    let encoded = encode(data);
+
+    let mut decoded = Vec::with_capacity(decoded_len(&Profile::STANDARD, &encoded));
+    let error = decode_to_vec(&encoded, &mut decoded).err();
+    compare(data, &decoded, error)
+}
+
+/// Compares original and decoded data, reporting the first difference.
+fn compare(expected: &[u8], actual: &[u8], error: Option<DecodeError>) -> Result<(), Mismatch> {
+    let Some(offset) = first_difference(expected, actual) else {
+        return match error {
+            // Everything decoded matched but decoding still failed
+            Some(error) => Err(Mismatch {
+                offset: actual.len(),
+                expected: None,
+                actual: None,
+                error: Some(error),
+            }),
+            None => Ok(()),
+        };
+    };
+
+    Err(Mismatch {
+        offset,
+        expected: expected.get(offset).copied(),
+        actual: actual.get(offset).copied(),
+        error,
+    })
+}
+
+/// The first point at which two byte strings diverge, traced back to the
+/// Base122 text that carries it.
+///
+/// Returned by [`diff_decoded`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffReport {
+    /// Offset of the first differing byte.
+    pub offset: usize,
+    /// Index of the first differing bit within that byte, counting from the
+    /// most significant bit (`0..8`). Zero when one input is a prefix of
+    /// the other.
+    pub bit: u32,
+    /// The expected byte at `offset`, if `expected` is long enough.
+    pub expected: Option<u8>,
+    /// The actual byte at `offset`, if `actual` is long enough.
+    pub actual: Option<u8>,
+    /// Index of the 7-bit chunk that holds the differing bit.
+    pub chunk: usize,
+    /// Byte offset of the character carrying that chunk in the encoding of
+    /// `expected`, or its length if the chunk lies past the end.
+    pub encoded_position: usize,
+    /// The encoded characters around `encoded_position`.
+    pub context: String,
+}
+
+impl fmt::Display for DiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "first difference at byte {}, bit {}",
+            self.offset, self.bit
+        )?;
+        match (self.expected, self.actual) {
+            (Some(expected), Some(actual)) => {
+                write!(f, " (expected 0x{expected:02X}, got 0x{actual:02X})")?
+            }
+            (Some(_), None) => f.write_str(" (actual data ends)")?,
+            (None, _) => f.write_str(" (expected data ends)")?,
+        }
+        write!(
+            f,
+            "; chunk {} encoded at byte {} in {:?}",
+            self.chunk, self.encoded_position, self.context
+        )
+    }
+}
+
+/// Finds the first difference between `expected` and `actual` decoded data.
+///
+/// Returns `None` if they are equal. Otherwise the report locates the first
+/// differing bit and the characters of `encode(expected)` that carry it,
+/// which is usually where a port or an interop peer went wrong.
+///
+/// # Examples
+///
+/// ```rust
+/// use base122_rs::diff_decoded;
+///
+/// assert_eq!(diff_decoded(b"same", b"same"), None);
+///
+/// let report = diff_decoded(b"payload", b"paYload").unwrap();
+/// assert_eq!((report.offset, report.bit), (2, 2));
+/// // Bit 18 of the input is in the third 7-bit chunk
+/// assert_eq!(report.chunk, 2);
+/// ```
+pub fn diff_decoded(expected: impl AsRef<[u8]>, actual: impl AsRef<[u8]>) -> Option<DiffReport> {
+    let (expected, actual) = (expected.as_ref(), actual.as_ref());
+    let offset = first_difference(expected, actual)?;
+    let (expected_byte, actual_byte) = (expected.get(offset).copied(), actual.get(offset).copied());
+
+    let bit = match (expected_byte, actual_byte) {
+        (Some(e), Some(a)) => (e ^ a).leading_zeros(),
+        _ => 0,
+    };
+    let chunk = (offset * 8 + bit as usize) / 7;
+
+    let encoded = encode(expected);
+    let encoded_position = chunk_position(&encoded, chunk);
+    let start = encoded[..encoded_position]
+        .char_indices()
+        .rev()
+        .nth(CONTEXT_CHARS - 1)
+        .map_or(0, |(i, _)| i);
+    let end = encoded[encoded_position..]
+        .char_indices()
+        .nth(CONTEXT_CHARS + 1)
+        .map_or(encoded.len(), |(i, _)| encoded_position + i);
+
+    Some(DiffReport {
+        offset,
+        bit,
+        expected: expected_byte,
+        actual: actual_byte,
+        chunk,
+        encoded_position,
+        context: encoded[start..end].into(),
+    })
+}
+
+/// Offset of the first byte at which `a` and `b` differ, counting a length
+/// difference as a difference just past the shorter one.
+pub(crate) fn first_difference(a: &[u8], b: &[u8]) -> Option<usize> {
+    a.iter()
+        .zip(b)
+        .position(|(x, y)| x != y)
+        .or_else(|| (a.len() != b.len()).then(|| a.len().min(b.len())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::{generate, Profile};
+
+    #[test]
+    fn test_verify_roundtrip_corpus() {
+        for profile in [Profile::Random, Profile::Text, Profile::Image] {
+            for len in [0, 1, 6, 7, 8, 1000] {
+                assert_eq!(verify_roundtrip(generate(profile, len, 5)), Ok(()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_diff_decoded_locates_escape() {
+        // Byte 7 starts chunk 8; with a null chunk 8 the diff lands in an escape
+        let expected = [b'a', b'b', b'c', b'd', b'e', b'f', b'g', 0, 0, b'x'];
+        let mut actual = expected;
+        actual[7] = 0x01;
+
+        let report = diff_decoded(expected, actual).unwrap();
+        assert_eq!((report.offset, report.bit, report.chunk), (7, 7, 9));
+
+        let encoded = encode(&expected);
+        let escape = encoded.char_indices().find(|(_, c)| !c.is_ascii()).unwrap();
+        assert_eq!(report.encoded_position, escape.0);
+        assert!(report.context.contains(escape.1));
+    }
+
+    #[test]
+    fn test_diff_decoded_length_difference() {
+        let report = diff_decoded(b"abc", b"ab").unwrap();
+        assert_eq!((report.offset, report.bit), (2, 0));
+        assert_eq!((report.expected, report.actual), (Some(b'c'), None));
+
+        // Past the last chunk of `expected` there is no character to blame
+        let report = diff_decoded(b"1234567", b"12345678").unwrap();
+        assert_eq!(report.chunk, 8);
+        assert_eq!(report.encoded_position, encode(b"1234567").len());
+    }
+
+    #[test]
+    fn test_compare_reports_first_difference() {
+        let error = DecodeError::UnexpectedEnd { position: 3 };
+
+        assert_eq!(compare(b"abc", b"abc", None), Ok(()));
+        assert_eq!(
+            compare(b"abc", b"abd", None),
+            Err(Mismatch {
+                offset: 2,
+                expected: Some(b'c'),
+                actual: Some(b'd'),
+                error: None
+            })
+        );
+        assert_eq!(
+            compare(b"abc", b"a", Some(error)),
+            Err(Mismatch {
+                offset: 1,
+                expected: Some(b'b'),
+                actual: None,
+                error: Some(error)
+            })
+        );
+        assert_eq!(compare(b"a", b"ab", None).unwrap_err().expected, None);
+    }
+}
@@ -0,0 +1,132 @@
+//! Streaming encoder and decoder for JavaScript.
+//!
+//! [`EncoderStream`] takes `Uint8Array` chunks and returns string chunks,
+//! and [`DecoderStream`] the reverse, so browser code can plug Base122
+//! into the Streams API and process large files without holding them in
+//! memory:
+//!
+//! ```js
+//! import { EncoderStream } from "base122-rs";
+//!
+//! function base122EncodeStream() {
+//!   const encoder = new EncoderStream();
+//!   return new TransformStream({
+//!     transform(chunk, controller) { controller.enqueue(encoder.push(chunk)); },
+//!     flush(controller) { controller.enqueue(encoder.finish()); },
+//!   });
+//! }
+//!
+//! const text = file.stream().pipeThrough(base122EncodeStream());
+//! ```
+//!
+//! Build with `wasm-pack build --features wasm`.
+
+use alloc::{string::String, vec::Vec};
+
+use wasm_bindgen::prelude::*;
+
+use crate::stream::{DecodeState, EncodeState};
+
+/// Encodes a byte stream pushed in chunks.
+#[wasm_bindgen]
+#[derive(Debug, Default)]
+pub struct EncoderStream {
+    state: EncodeState,
+}
+
+#[wasm_bindgen]
+impl EncoderStream {
+    /// Creates an encoder.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> EncoderStream {
+        EncoderStream::default()
+    }
+
+    /// Encodes the next chunk of input and returns the text completed so
+    /// far.
+    ///
+    /// Every returned chunk is whole characters, and the concatenation of
+    /// all chunks and the result of [`finish`](EncoderStream::finish) is
+    /// [`encode`](crate::encode) of the whole input.
+    pub fn push(&mut self, chunk: &[u8]) -> String {
+        let mut encoded = Vec::with_capacity(chunk.len() * 8 / 7 + 2);
+        for &byte in chunk {
+            self.state.push(byte, &mut |b| encoded.push(b));
+        }
+        into_string(encoded)
+    }
+
+    /// Encodes the end of the input and returns the last characters.
+    ///
+    /// The encoder is reset and can encode a new, unrelated input.
+    pub fn finish(&mut self) -> String {
+        let mut encoded = Vec::with_capacity(2);
+        self.state.finish(&mut |b| encoded.push(b));
+        into_string(encoded)
+    }
+}
+
+/// Decodes Base122 text pushed in chunks.
+#[wasm_bindgen]
+#[derive(Debug, Default)]
+pub struct DecoderStream {
+    state: DecodeState,
+}
+
+#[wasm_bindgen]
+impl DecoderStream {
+    /// Creates a decoder.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> DecoderStream {
+        DecoderStream::default()
+    }
+
+    /// Decodes the next chunk of text and returns the bytes completed so
+    /// far.
+    ///
+    /// Throws on invalid input, with the byte offset of the problem in the
+    /// whole stream.
+    pub fn push(&mut self, chunk: &str) -> Result<Vec<u8>, JsError> {
+        let mut decoded = Vec::with_capacity(chunk.len());
+        self.state
+            .push_slice(chunk.as_bytes(), &mut |b| decoded.push(b))
+            .map_err(JsError::from)?;
+        Ok(decoded)
+    }
+
+    /// Checks that the text did not end inside a character.
+    ///
+    /// The decoder is reset and can decode a new, unrelated input.
+    pub fn finish(&mut self) -> Result<(), JsError> {
+        self.state.finish().map_err(JsError::from)
+    }
+}
+
+/// The encoder only emits ASCII and whole 2-byte escapes.
+fn into_string(encoded: Vec<u8>) -> String {
+    String::from_utf8(encoded).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::{generate, Profile};
+    use crate::encode;
+
+    #[test]
+    fn test_streams_round_trip() {
+        let data = generate(Profile::DangerousDensity(0.5), 3000, 12);
+        let mut encoder = EncoderStream::new();
+        let mut chunks: Vec<String> = data.chunks(100).map(|c| encoder.push(c)).collect();
+        chunks.push(encoder.finish());
+        assert_eq!(chunks.concat(), encode(&data));
+
+        let mut decoder = DecoderStream::new();
+        let mut decoded = Vec::new();
+        for chunk in &chunks {
+            decoded.extend(decoder.push(chunk).unwrap());
+        }
+        assert!(decoder.finish().is_ok());
+        assert_eq!(decoded, data);
+    }
+}
@@ -0,0 +1,235 @@
+//! Extractor and responder for web frameworks.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::Utf8Error;
+
+use crate::{decode, encode, DecodeError};
+
+/// Content type of encoded responses.
+const CONTENT_TYPE: &str = "text/plain; charset=utf-8";
+
+/// A request or response body carried as Base122 text.
+///
+/// As an extractor, reads the whole request body and decodes it, rejecting
+/// the request with `400 Bad Request` and a [`Base122Rejection`] if it is
+/// not Base122 text. As a responder, encodes `T` and sends it as
+/// `text/plain; charset=utf-8`. Implemented for axum with the `axum`
+/// feature and for actix-web with the `actix-web` feature.
+///
+/// Framework body size limits apply to the encoded request body.
+///
+/// # Examples
+///
+/// A handler for either framework that takes a Base122 payload and
+/// answers with its bytes reversed, also encoded:
+///
+/// ```rust
+/// use base122_rs::Base122Body;
+///
+/// async fn reverse(Base122Body(mut data): Base122Body) -> Base122Body {
+///     data.reverse();
+///     Base122Body(data)
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Base122Body<T = Vec<u8>>(pub T);
+
+/// Rejection of a request whose body is not Base122 text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base122Rejection {
+    /// The body is not UTF-8.
+    NotUtf8(Utf8Error),
+    /// The body is UTF-8 but not valid Base122.
+    Invalid(DecodeError),
+}
+
+impl fmt::Display for Base122Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Base122Rejection::NotUtf8(error) => write!(f, "request body is not UTF-8: {error}"),
+            Base122Rejection::Invalid(error) => write!(f, "request body is not Base122: {error}"),
+        }
+    }
+}
+
+impl core::error::Error for Base122Rejection {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Base122Rejection::NotUtf8(error) => Some(error),
+            Base122Rejection::Invalid(error) => Some(error),
+        }
+    }
+}
+
+/// Decodes a request body.
+fn decode_body<T: From<Vec<u8>>>(body: &[u8]) -> Result<Base122Body<T>, Base122Rejection> {
+    let text = core::str::from_utf8(body).map_err(Base122Rejection::NotUtf8)?;
+    let data = decode(text).map_err(Base122Rejection::Invalid)?;
+    Ok(Base122Body(data.into()))
+}
+
+/// Encodes a response body.
+fn encode_body<T: AsRef<[u8]>>(body: &Base122Body<T>) -> String {
+    encode(body.0.as_ref())
+}
+
+#[cfg(feature = "axum")]
+mod axum {
+    use alloc::string::ToString;
+
+    use axum_core::extract::{FromRequest, Request};
+    use axum_core::response::{IntoResponse, Response};
+    use bytes::Bytes;
+    use http::{header, StatusCode};
+
+    use super::*;
+
+    impl<S, T> FromRequest<S> for Base122Body<T>
+    where
+        S: Send + Sync,
+        T: From<Vec<u8>>,
+    {
+        type Rejection = Response;
+
+        async fn from_request(req: Request, state: &S) -> Result<Self, Response> {
+            let body = Bytes::from_request(req, state)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            decode_body(&body).map_err(IntoResponse::into_response)
+        }
+    }
+
+    impl<T: AsRef<[u8]>> IntoResponse for Base122Body<T> {
+        fn into_response(self) -> Response {
+            ([(header::CONTENT_TYPE, CONTENT_TYPE)], encode_body(&self)).into_response()
+        }
+    }
+
+    impl IntoResponse for Base122Rejection {
+        fn into_response(self) -> Response {
+            (StatusCode::BAD_REQUEST, self.to_string()).into_response()
+        }
+    }
+}
+
+#[cfg(feature = "actix-web")]
+mod actix {
+    use alloc::boxed::Box;
+    use core::future::Future;
+    use core::pin::Pin;
+
+    use actix_web::body::BoxBody;
+    use actix_web::dev::Payload;
+    use actix_web::http::StatusCode;
+    use actix_web::web::Bytes;
+    use actix_web::{FromRequest, HttpRequest, HttpResponse, Responder, ResponseError};
+
+    use super::*;
+
+    impl<T: From<Vec<u8>> + 'static> FromRequest for Base122Body<T> {
+        type Error = actix_web::Error;
+        type Future = Pin<Box<dyn Future<Output = Result<Self, actix_web::Error>>>>;
+
+        fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+            let body = Bytes::from_request(req, payload);
+            Box::pin(async move { Ok(decode_body(&body.await?)?) })
+        }
+    }
+
+    impl<T: AsRef<[u8]>> Responder for Base122Body<T> {
+        type Body = BoxBody;
+
+        fn respond_to(self, _req: &HttpRequest) -> HttpResponse {
+            HttpResponse::Ok()
+                .content_type(CONTENT_TYPE)
+                .body(encode_body(&self))
+        }
+    }
+
+    impl ResponseError for Base122Rejection {
+        fn status_code(&self) -> StatusCode {
+            StatusCode::BAD_REQUEST
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::future::Future;
+    use core::pin::pin;
+    use core::task::{Context, Poll, Waker};
+
+    /// Runs a future that never waits on anything outside itself.
+    fn ready<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    #[cfg(feature = "axum")]
+    #[test]
+    fn test_axum() {
+        use axum_core::body::Body;
+        use axum_core::extract::{FromRequest, Request};
+        use axum_core::response::IntoResponse;
+        use bytes::Bytes;
+        use http::StatusCode;
+
+        let data = vec![0, 10, 13, 255];
+        let request = Request::new(Body::from(encode(&data)));
+        let Base122Body(decoded): Base122Body =
+            ready(Base122Body::from_request(request, &())).unwrap();
+        assert_eq!(decoded, data);
+
+        let request = Request::new(Body::from("\u{20AC}"));
+        let response = ready(Base122Body::<Vec<u8>>::from_request(request, &())).unwrap_err();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = Base122Body(data.clone()).into_response();
+        assert_eq!(response.headers()["content-type"], CONTENT_TYPE);
+        let body = ready(Bytes::from_request(Request::new(response.into_body()), &())).unwrap();
+        assert_eq!(body, encode(&data).as_bytes());
+    }
+
+    #[cfg(feature = "actix-web")]
+    #[test]
+    fn test_actix() {
+        use actix_web::body::MessageBody;
+        use actix_web::http::StatusCode;
+        use actix_web::test::TestRequest;
+        use actix_web::{FromRequest, Responder};
+
+        let data = vec![0, 10, 13, 255];
+        let (request, mut payload) = TestRequest::default()
+            .set_payload(encode(&data))
+            .to_http_parts();
+        let Base122Body(decoded): Base122Body =
+            ready(Base122Body::from_request(&request, &mut payload)).unwrap();
+        assert_eq!(decoded, data);
+
+        let (request, mut payload) = TestRequest::default()
+            .set_payload(vec![0xFF])
+            .to_http_parts();
+        let error =
+            ready(Base122Body::<Vec<u8>>::from_request(&request, &mut payload)).unwrap_err();
+        assert_eq!(
+            error.as_response_error().status_code(),
+            StatusCode::BAD_REQUEST
+        );
+
+        let response = Base122Body(data.clone()).respond_to(&request);
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            CONTENT_TYPE
+        );
+        let body = response.into_body().try_into_bytes().unwrap();
+        assert_eq!(body, encode(&data).as_bytes());
+    }
+}